@@ -0,0 +1,226 @@
+//! Derive macros for `ser-sled`, re-exported by it with the `derive` feature.
+
+use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Generates `open(db: &ser_sled::Db) -> Result<Self, ser_sled::error::Error>` for a
+/// struct whose fields are strict trees, e.g. `BincodeTree<K, V>`. Every tree is
+/// opened under the name of its field, or the one given with
+/// `#[ser_sled(name = "...")]`, and its schema fingerprint is checked.
+#[proc_macro_derive(SerSledSchema, attributes(ser_sled))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_schema(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_schema(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "SerSledSchema needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "SerSledSchema can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut names: Vec<LitStr> = Vec::new();
+    let mut opened = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let name =
+            tree_name(field)?.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+
+        if names.iter().any(|other| other.value() == name.value()) {
+            return Err(syn::Error::new_spanned(
+                &name,
+                format!("the tree name `{}` is used twice", name.value()),
+            ));
+        }
+
+        let ty = &field.ty;
+        opened.push(quote! {
+            #ident: <#ty as ::ser_sled::schema::SchemaTree>::open_by_name(db, #name)?
+        });
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// The names of the trees opened by `open`, in field order.
+            pub const TREE_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            /// Opens every tree of the schema, checking that each one was last opened
+            /// with the same key and value types.
+            pub fn open(db: &::ser_sled::Db) -> ::core::result::Result<Self, ::ser_sled::error::Error> {
+                ::core::result::Result::Ok(Self {
+                    #(#opened,)*
+                })
+            }
+        }
+    })
+}
+
+/// The name given with `#[ser_sled(name = "...")]`, if any.
+fn tree_name(field: &syn::Field) -> syn::Result<Option<LitStr>> {
+    let mut name = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("ser_sled"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(name)
+}
+
+/// Implements `ser_sled::keys::OrderedKey` for a struct by encoding its fields in
+/// order, so that the byte order of keys matches the order derived with
+/// `#[derive(PartialOrd, Ord)]`, which compares fields in the same order. Every field
+/// must be an `OrderedKey`: floats are rejected, wrap them in `keys::OrderedFloat`.
+#[proc_macro_derive(OrderedKey)]
+pub fn derive_ordered_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_ordered_key(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_ordered_key(mut input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "OrderedKey can only be derived for structs",
+            ))
+        }
+    };
+
+    for field in fields {
+        if let Some(float) = find_float(&field.ty) {
+            return Err(syn::Error::new_spanned(
+                float,
+                "floats have no total order, wrap them in `ser_sled::keys::OrderedFloat`",
+            ));
+        }
+    }
+
+    let members: Vec<syn::Member> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(i.into()),
+        })
+        .collect();
+
+    // Spanned on the field types, so that a field which isn't an `OrderedKey` is
+    // reported on the field itself
+    let encoded = fields.iter().zip(&members).map(|(field, member)| {
+        quote_spanned! {field.ty.span()=>
+            ::ser_sled::keys::OrderedKey::encode_ordered(&self.#member, out);
+        }
+    });
+    let decoded: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ty = &field.ty;
+            quote_spanned! {ty.span()=>
+                <#ty as ::ser_sled::keys::OrderedKey>::decode_ordered(input)?
+            }
+        })
+        .collect();
+
+    let constructed = match fields {
+        Fields::Named(_) => quote! { Self { #(#members: #decoded,)* } },
+        Fields::Unnamed(_) => quote! { Self(#(#decoded,)*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    for param in input.generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::ser_sled::keys::OrderedKey));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::ser_sled::keys::OrderedKey for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode_ordered(&self, out: &mut ::std::vec::Vec<u8>) {
+                #(#encoded)*
+            }
+
+            #[allow(unused_variables)]
+            fn decode_ordered(
+                input: &mut &[u8],
+            ) -> ::core::result::Result<Self, ::ser_sled::error::Error> {
+                ::core::result::Result::Ok(#constructed)
+            }
+        }
+    })
+}
+
+/// The first `f32` or `f64` in `ty`, including in tuples, arrays and type arguments
+/// other than those of `OrderedFloat`.
+fn find_float(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            if path.path.is_ident("f32") || path.path.is_ident("f64") {
+                return Some(ty);
+            }
+
+            path.path
+                .segments
+                .iter()
+                .find_map(|segment| match &segment.arguments {
+                    PathArguments::AngleBracketed(_) if segment.ident == "OrderedFloat" => None,
+                    PathArguments::AngleBracketed(args) => {
+                        args.args.iter().find_map(|arg| match arg {
+                            GenericArgument::Type(ty) => find_float(ty),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                })
+        }
+        Type::Tuple(tuple) => tuple.elems.iter().find_map(find_float),
+        Type::Array(array) => find_float(&array.elem),
+        Type::Paren(paren) => find_float(&paren.elem),
+        Type::Group(group) => find_float(&group.elem),
+        _ => None,
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{error::Error, schema, Db};
+
+/// How many entries [`Db::copy_tree`] and [`Db::rename_tree`] write per batch.
+pub const COPY_BATCH_SIZE: usize = 1000;
+
+/// Copies the raw entries of `src` into `dst`, [`COPY_BATCH_SIZE`] at a time.
+pub(crate) fn copy_entries(src: &sled::Tree, dst: &sled::Tree) -> Result<usize, Error> {
+    let mut batch = sled::Batch::default();
+    let mut pending = 0;
+    let mut copied = 0;
+
+    for res in src.iter() {
+        let (key, value) = res?;
+        batch.insert(key, value);
+        pending += 1;
+
+        if pending == COPY_BATCH_SIZE {
+            dst.apply_batch(std::mem::take(&mut batch))?;
+            copied += pending;
+            pending = 0;
+        }
+    }
+
+    dst.apply_batch(batch)?;
+
+    Ok(copied + pending)
+}
+
+/// Copies `src` into the empty tree `dst`, then drops `src`. The schema fingerprint
+/// and validator of `src` move along with its entries.
+pub(crate) fn rename_tree(db: &Db, src: &str, dst: &str) -> Result<usize, Error> {
+    let dst_tree = db.inner_db.open_tree(dst)?;
+    if !dst_tree.is_empty() {
+        return Err(Error::IllegalOperation);
+    }
+
+    let renamed = copy_entries(&db.inner_db.open_tree(src)?, &dst_tree)?;
+
+    let schemas = db.inner_db.open_tree(schema::SCHEMA_TREE)?;
+    match schemas.remove(src)? {
+        Some(fingerprint) => schemas.insert(dst, fingerprint)?,
+        None => schemas.remove(dst)?,
+    };
+    db.move_table(src, dst);
+    db.inner_db.drop_tree(src)?;
+
+    Ok(renamed)
+}
+
+/// Drops the tree `name` along with its schema fingerprint and validator.
+pub(crate) fn drop_tree(db: &Db, name: &str) -> Result<bool, Error> {
+    db.inner_db.open_tree(schema::SCHEMA_TREE)?.remove(name)?;
+    db.forget_table(name);
+
+    Ok(db.inner_db.drop_tree(name)?)
+}
+
+/// Name of the tree sled always opens, which can't be dropped.
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+pub(crate) fn tree_names(db: &Db) -> Vec<String> {
+    db.inner_db
+        .tree_names()
+        .into_iter()
+        .filter(|name| name != DEFAULT_TREE)
+        .map(|name| String::from_utf8_lossy(&name).into_owned())
+        .collect()
+}
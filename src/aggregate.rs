@@ -0,0 +1,129 @@
+use bincode::{Decode, Encode};
+use std::ops::Bound;
+
+use crate::bincode_tree::BincodeTree;
+use crate::{error::Error, Db, StrictTree};
+
+/// How many events [`AggregateStore::load`] replays before persisting a new snapshot,
+/// unless changed with [`AggregateStore::with_snapshot_every`].
+pub const DEFAULT_SNAPSHOT_EVERY: u64 = 100;
+
+pub(crate) fn snapshots_name(store_name: &str) -> String {
+    format!("{store_name}_snapshots")
+}
+
+/// An append-only event log paired with snapshots of the state folded from it,
+/// opened with [`Db::open_aggregate_store`].
+///
+/// Events are stored in the `<name>` tree under increasing sequence numbers taken from
+/// [`Db::generate_id`], and the latest snapshot in the `<name>_snapshots` tree under the
+/// sequence number of the last event it includes.
+#[derive(Clone)]
+pub struct AggregateStore<E: Encode + Decode, S: Encode + Decode> {
+    db: Db,
+    events: BincodeTree<u64, E>,
+    snapshots: BincodeTree<u64, S>,
+    snapshot_every: u64,
+}
+
+impl<E: Encode + Decode, S: Encode + Decode> AggregateStore<E, S> {
+    pub(crate) fn new(db: Db, events: BincodeTree<u64, E>, snapshots: BincodeTree<u64, S>) -> Self {
+        Self {
+            db,
+            events,
+            snapshots,
+            snapshot_every: DEFAULT_SNAPSHOT_EVERY,
+        }
+    }
+
+    /// Persists a snapshot whenever [`AggregateStore::load`] had to replay at least
+    /// `events` events. `0` disables automatic snapshots.
+    pub fn with_snapshot_every(mut self, events: u64) -> Self {
+        self.snapshot_every = events;
+        self
+    }
+
+    /// Appends an event and returns its sequence number.
+    pub fn append(&self, event: &E) -> Result<u64, Error> {
+        let sequence = self.db.generate_id()?;
+        self.events.insert(&sequence, event)?;
+
+        Ok(sequence)
+    }
+
+    /// Rebuilds the current state: starts from the latest snapshot, or `init` if there is
+    /// none, and folds every event appended after it.
+    pub fn load<F: FnMut(S, E) -> S>(&self, init: S, mut fold: F) -> Result<S, Error> {
+        let (mut state, since) = match self.snapshots.last()? {
+            Some((sequence, snapshot)) => (snapshot, Some(sequence)),
+            None => (init, None),
+        };
+
+        let events = match since {
+            Some(sequence) => self
+                .events
+                .range((Bound::Excluded(sequence), Bound::Unbounded))?
+                .collect::<Vec<_>>(),
+            None => self.events.iter().collect(),
+        };
+
+        let replayed = events.len() as u64;
+        let mut last = since;
+
+        for (sequence, event) in events {
+            state = fold(state, event);
+            last = Some(sequence);
+        }
+
+        if let Some(sequence) = last {
+            if self.snapshot_every != 0 && replayed >= self.snapshot_every {
+                self.snapshot(sequence, &state)?;
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Persists `state` as the result of folding every event up to and including `sequence`,
+    /// replacing any older snapshot.
+    pub fn snapshot(&self, sequence: u64, state: &S) -> Result<(), Error> {
+        self.snapshots.insert(&sequence, state)?;
+
+        while let Some((older, _)) = self.snapshots.first()? {
+            if older >= sequence {
+                break;
+            }
+
+            self.snapshots.remove(&older)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the latest snapshot and the sequence number of the last event it includes.
+    pub fn latest_snapshot(&self) -> Result<Option<(u64, S)>, Error> {
+        self.snapshots.last()
+    }
+
+    /// Iterates over every event in order, with its sequence number.
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = (u64, E)> + '_ {
+        self.events.iter()
+    }
+
+    /// Iterates over the events appended after `sequence`.
+    pub fn events_since(
+        &self,
+        sequence: u64,
+    ) -> Result<impl DoubleEndedIterator<Item = (u64, E)> + '_, Error> {
+        self.events
+            .range((Bound::Excluded(sequence), Bound::Unbounded))
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,230 @@
+use bincode::config::{Config, Configuration, Limit};
+use bincode::{Decode, Encode};
+use serde::{de::DeserializeOwned, Serialize};
+use std::ops::RangeBounds;
+use std::str::FromStr;
+
+use crate::bincode_tree::BincodeTree;
+use crate::keys::OrderedKey;
+use crate::serde_tree::SerdeTree;
+use crate::{error::Error, DefaultConfig, StrictTree};
+
+/// The encoding used by an [`AnyTree`], typically read from configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Bincode,
+    Serde,
+}
+
+impl FromStr for Codec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bincode" => Ok(Codec::Bincode),
+            "serde" => Ok(Codec::Serde),
+            _ => Err(Error::UnknownCodec(s.to_string())),
+        }
+    }
+}
+
+/// Types that can be stored in any kind of strict tree.
+pub trait AnyItem: Encode + Decode + Serialize + DeserializeOwned {}
+
+impl<T: Encode + Decode + Serialize + DeserializeOwned> AnyItem for T {}
+
+/// A strict tree whose codec is chosen at runtime, so that switching codecs
+/// is a configuration change rather than a change of types throughout the codebase.
+/// Keys are encoded with [`crate::keys::OrderedKeyCodec`] either way, but values
+/// aren't compatible between codecs: a tree written with one codec must be read with the same one.
+#[derive(Clone)]
+pub enum AnyTree<K: OrderedKey, V: AnyItem, C = DefaultConfig> {
+    Bincode(BincodeTree<K, V, C>),
+    Serde(SerdeTree<K, V, C>),
+}
+
+impl<K: OrderedKey, V: AnyItem, E, I, L> AnyTree<K, V, Configuration<E, I, L>> {
+    /// See [`crate::bincode_tree::RelaxedTree::with_limit`].
+    pub fn with_limit<const N: usize>(self) -> AnyTree<K, V, Configuration<E, I, Limit<N>>> {
+        match self {
+            AnyTree::Bincode(tree) => AnyTree::Bincode(tree.with_limit::<N>()),
+            AnyTree::Serde(tree) => AnyTree::Serde(tree.with_limit::<N>()),
+        }
+    }
+}
+
+impl<K: OrderedKey, V: AnyItem, C: Config + Default> AnyTree<K, V, C> {
+    pub fn with_codec(tree: sled::Tree, codec: Codec) -> Self {
+        match codec {
+            Codec::Bincode => AnyTree::Bincode(BincodeTree::new(tree)),
+            Codec::Serde => AnyTree::Serde(SerdeTree::new(tree)),
+        }
+    }
+
+    /// See [`BincodeTree::with_strict_decode`]. Enabled by default.
+    pub fn with_strict_decode(self, strict: bool) -> Self {
+        match self {
+            AnyTree::Bincode(tree) => AnyTree::Bincode(tree.with_strict_decode(strict)),
+            AnyTree::Serde(tree) => AnyTree::Serde(tree.with_strict_decode(strict)),
+        }
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::flush`].
+    pub fn flush(&self) -> Result<usize, Error> {
+        match self {
+            AnyTree::Bincode(tree) => tree.flush(),
+            AnyTree::Serde(tree) => tree.flush(),
+        }
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::flush_async`].
+    pub async fn flush_async(&self) -> Result<usize, Error> {
+        match self {
+            AnyTree::Bincode(tree) => tree.flush_async().await,
+            AnyTree::Serde(tree) => tree.flush_async().await,
+        }
+    }
+
+    pub fn codec(&self) -> Codec {
+        match self {
+            AnyTree::Bincode(_) => Codec::Bincode,
+            AnyTree::Serde(_) => Codec::Serde,
+        }
+    }
+}
+
+/// Iterator over either kind of tree.
+enum AnyIter<B, S> {
+    Bincode(B),
+    Serde(S),
+}
+
+impl<T, B: Iterator<Item = T>, S: Iterator<Item = T>> Iterator for AnyIter<B, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            AnyIter::Bincode(iter) => iter.next(),
+            AnyIter::Serde(iter) => iter.next(),
+        }
+    }
+}
+
+impl<T, B, S> DoubleEndedIterator for AnyIter<B, S>
+where
+    B: DoubleEndedIterator<Item = T>,
+    S: DoubleEndedIterator<Item = T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            AnyIter::Bincode(iter) => iter.next_back(),
+            AnyIter::Serde(iter) => iter.next_back(),
+        }
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $tree:ident => $call:expr) => {
+        match $self {
+            AnyTree::Bincode($tree) => $call,
+            AnyTree::Serde($tree) => $call,
+        }
+    };
+}
+
+impl<KeyItem: OrderedKey, ValueItem: AnyItem, C: Config + Default> StrictTree<KeyItem, ValueItem>
+    for AnyTree<KeyItem, ValueItem, C>
+{
+    /// Uses [`Codec::Bincode`]. See [`AnyTree::with_codec`] to pick another codec.
+    fn new(tree: sled::Tree) -> Self {
+        Self::with_codec(tree, Codec::default())
+    }
+
+    fn get(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
+        delegate!(self, tree => tree.get(key))
+    }
+
+    fn get_or_init<F: FnOnce() -> ValueItem>(
+        &self,
+        key: KeyItem,
+        init_func: F,
+    ) -> Result<Option<ValueItem>, Error> {
+        delegate!(self, tree => tree.get_or_init(key, init_func))
+    }
+
+    fn insert(&self, key: &KeyItem, value: &ValueItem) -> Result<Option<ValueItem>, Error> {
+        delegate!(self, tree => tree.insert(key, value))
+    }
+
+    fn first(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.first())
+    }
+
+    fn last(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.last())
+    }
+
+    fn pop_max(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.pop_max())
+    }
+
+    fn pop_min(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.pop_min())
+    }
+
+    fn pop_n_min(&self, n: usize) -> Result<Vec<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.pop_n_min(n))
+    }
+
+    fn pop_n_max(&self, n: usize) -> Result<Vec<(KeyItem, ValueItem)>, Error> {
+        delegate!(self, tree => tree.pop_n_max(n))
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (KeyItem, ValueItem)> {
+        match self {
+            AnyTree::Bincode(tree) => AnyIter::Bincode(tree.iter()),
+            AnyTree::Serde(tree) => AnyIter::Serde(tree.iter()),
+        }
+    }
+
+    fn range_key_bytes<KeyBytes: AsRef<[u8]>, R: RangeBounds<KeyBytes>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, ValueItem)> {
+        match self {
+            AnyTree::Bincode(tree) => AnyIter::Bincode(tree.range_key_bytes(range)),
+            AnyTree::Serde(tree) => AnyIter::Serde(tree.range_key_bytes(range)),
+        }
+    }
+
+    fn range<R: RangeBounds<KeyItem>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (KeyItem, ValueItem)>, Error> {
+        Ok(match self {
+            AnyTree::Bincode(tree) => AnyIter::Bincode(tree.range(range)?),
+            AnyTree::Serde(tree) => AnyIter::Serde(tree.range(range)?),
+        })
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        delegate!(self, tree => tree.clear())
+    }
+
+    fn contains_key(&self, key: &KeyItem) -> Result<bool, Error> {
+        delegate!(self, tree => tree.contains_key(key))
+    }
+
+    fn len(&self) -> usize {
+        delegate!(self, tree => tree.len())
+    }
+
+    fn is_empty(&self) -> bool {
+        delegate!(self, tree => tree.is_empty())
+    }
+
+    fn remove(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
+        delegate!(self, tree => tree.remove(key))
+    }
+}
@@ -0,0 +1,95 @@
+//! Compressed single-file backups, see [`Db::backup_to_archive`].
+//!
+//! An archive is [`ARCHIVE_MAGIC`] followed by a zstd stream holding an export in
+//! the format of [`crate::export`] and the CRC32 of that export.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{error::Error, export, Db};
+
+/// First bytes of every archive.
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"SERSLEDZ";
+
+/// Passes written bytes through to `inner`, computing their checksum.
+struct ChecksumWriter<W> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(crate) fn backup(db: &Db, path: &Path) -> Result<usize, Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(&ARCHIVE_MAGIC)?;
+
+    let encoder = zstd::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    let mut writer = ChecksumWriter {
+        inner: encoder,
+        hasher: crc32fast::Hasher::new(),
+    };
+    let exported = export::write_export(db, &mut writer)?;
+
+    let mut encoder = writer.inner;
+    encoder.write_all(&writer.hasher.finalize().to_be_bytes())?;
+    encoder.finish()?.flush()?;
+
+    Ok(exported)
+}
+
+pub(crate) fn restore(db: &Db, path: &Path) -> Result<usize, Error> {
+    // Check the whole archive before writing anything
+    verify(&mut open(path)?)?;
+
+    export::read_export(db, &mut open(path)?)
+}
+
+/// Opens the archive at `path` and returns a reader of its decompressed content.
+fn open(path: &Path) -> Result<impl Read, Error> {
+    let mut file = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; ARCHIVE_MAGIC.len()];
+    file.read_exact(&mut magic)?;
+    if magic != ARCHIVE_MAGIC {
+        return Err(Error::InvalidArchive("not a ser-sled archive"));
+    }
+
+    Ok(zstd::Decoder::with_buffer(file)?)
+}
+
+/// Checks that the last 4 bytes of `reader` are the checksum of the bytes before them.
+fn verify<R: Read>(reader: &mut R) -> Result<(), Error> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut pending = Vec::new();
+
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        pending.extend_from_slice(&buf[..read]);
+        if pending.len() > 4 {
+            let hashed = pending.len() - 4;
+            hasher.update(&pending[..hashed]);
+            pending.drain(..hashed);
+        }
+    }
+
+    match <[u8; 4]>::try_from(pending) {
+        Ok(checksum) if u32::from_be_bytes(checksum) == hasher.finalize() => Ok(()),
+        _ => Err(Error::InvalidArchive("checksum mismatch")),
+    }
+}
@@ -0,0 +1,169 @@
+use bincode::config::{Config, Configuration, Limit};
+use bincode::{Decode, Encode};
+use std::ops::RangeBounds;
+
+use crate::bincode_tree::BincodeTree;
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::{error::Error, DefaultConfig, StrictTree};
+
+/// Runs `f` on tokio's blocking pool. Panics inside `f` are resumed on the caller.
+async fn blocking<T, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(Error::Cancelled),
+    }
+}
+
+/// A [`BincodeTree`] whose methods mirror [`StrictTree`] but return futures, running
+/// every sled operation on tokio's blocking pool so that async tasks are never blocked.
+/// Iterators are collected on the blocking pool and returned as a `Vec`.
+#[derive(Clone)]
+pub struct AsyncBincodeTree<K, V: Encode + Decode, C = DefaultConfig, KC = OrderedKeyCodec> {
+    inner_tree: BincodeTree<K, V, C, KC>,
+}
+
+impl<K, V: Encode + Decode, C, KC> From<BincodeTree<K, V, C, KC>>
+    for AsyncBincodeTree<K, V, C, KC>
+{
+    fn from(tree: BincodeTree<K, V, C, KC>) -> Self {
+        Self { inner_tree: tree }
+    }
+}
+
+impl<K, V: Encode + Decode, E, I, L, KC> AsyncBincodeTree<K, V, Configuration<E, I, L>, KC> {
+    /// See [`crate::bincode_tree::RelaxedTree::with_limit`].
+    pub fn with_limit<const N: usize>(
+        self,
+    ) -> AsyncBincodeTree<K, V, Configuration<E, I, Limit<N>>, KC> {
+        self.inner_tree.with_limit::<N>().into()
+    }
+}
+
+impl<K, V, C, KC> AsyncBincodeTree<K, V, C, KC>
+where
+    K: Clone + Send + Sync + 'static,
+    V: Encode + Decode + Clone + Send + Sync + 'static,
+    C: Config + Default + Send + Sync + 'static,
+    KC: KeyCodec<K> + Clone + Send + Sync + 'static,
+{
+    pub fn new(tree: sled::Tree) -> Self {
+        BincodeTree::new(tree).into()
+    }
+
+    /// See [`BincodeTree::with_strict_decode`]. Enabled by default.
+    pub fn with_strict_decode(self, strict: bool) -> Self {
+        self.inner_tree.with_strict_decode(strict).into()
+    }
+
+    /// Returns the underlying blocking tree.
+    pub fn blocking(&self) -> &BincodeTree<K, V, C, KC> {
+        &self.inner_tree
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::flush`]. sled flushes asynchronously
+    /// already, so this doesn't use the blocking pool.
+    pub async fn flush(&self) -> Result<usize, Error> {
+        self.inner_tree.flush_async().await
+    }
+
+    pub async fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        let (tree, key) = (self.inner_tree.clone(), key.clone());
+        blocking(move || tree.get(&key)).await
+    }
+
+    pub async fn get_or_init<F: FnOnce() -> V + Send + 'static>(
+        &self,
+        key: K,
+        init_func: F,
+    ) -> Result<Option<V>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.get_or_init(key, init_func)).await
+    }
+
+    pub async fn get_or_insert_with<F: FnOnce() -> V + Send + 'static>(
+        &self,
+        key: K,
+        init_func: F,
+    ) -> Result<V, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.get_or_insert_with(key, init_func)).await
+    }
+
+    pub async fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        let (tree, key, value) = (self.inner_tree.clone(), key.clone(), value.clone());
+        blocking(move || tree.insert(&key, &value)).await
+    }
+
+    pub async fn first(&self) -> Result<Option<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.first()).await
+    }
+
+    pub async fn last(&self) -> Result<Option<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.last()).await
+    }
+
+    pub async fn pop_max(&self) -> Result<Option<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.pop_max()).await
+    }
+
+    pub async fn pop_min(&self) -> Result<Option<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.pop_min()).await
+    }
+
+    pub async fn pop_n_min(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.pop_n_min(n)).await
+    }
+
+    pub async fn pop_n_max(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.pop_n_max(n)).await
+    }
+
+    pub async fn iter(&self) -> Result<Vec<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || Ok(tree.iter().collect())).await
+    }
+
+    pub async fn range<R: RangeBounds<K> + Send + 'static>(
+        &self,
+        range: R,
+    ) -> Result<Vec<(K, V)>, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || Ok(tree.range(range)?.collect())).await
+    }
+
+    pub async fn clear(&self) -> Result<(), Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || tree.clear()).await
+    }
+
+    pub async fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        let (tree, key) = (self.inner_tree.clone(), key.clone());
+        blocking(move || tree.contains_key(&key)).await
+    }
+
+    pub async fn len(&self) -> Result<usize, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || Ok(tree.len())).await
+    }
+
+    pub async fn is_empty(&self) -> Result<bool, Error> {
+        let tree = self.inner_tree.clone();
+        blocking(move || Ok(tree.is_empty())).await
+    }
+
+    pub async fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let (tree, key) = (self.inner_tree.clone(), key.clone());
+        blocking(move || tree.remove(&key)).await
+    }
+}
@@ -0,0 +1,446 @@
+//! Storage engines for typed trees.
+//!
+//! [`KvBackend`] is the part of a sled tree that the typed layer needs: an ordered map
+//! of byte strings. [`crate::tree::Tree`] is generic over it, and stored in a sled tree
+//! by default, so the same typed tree, with the same codecs and quotas, can run on
+//! sled or on a [`MemoryBackend`], e.g. in unit tests through [`MemoryTree`].
+//!
+//! Features that rely on sled itself, like transactions, subscribers, merge operators
+//! and the wrappers built on them, are only available on trees opened from a
+//! [`crate::Db`].
+
+use sled::IVec;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::sync::{Arc, RwLock};
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::error::Error;
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::tree::{Batch, Tree};
+use crate::{BytesRange, DefaultConfig, StrictTree};
+
+/// An ordered map of byte strings that typed trees can be stored in. Clones share
+/// their entries, like sled trees.
+pub trait KvBackend: Clone {
+    type Iter: DoubleEndedIterator<Item = Result<(IVec, IVec), Error>>;
+
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Error>;
+    /// Inserts `value` under `key`, returning the previous value.
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Error>;
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Error>;
+    /// Atomically replaces the value of `key` with `new` if it is still `old`, `None`
+    /// meaning no value. Returns whether it was replaced.
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        old: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> Result<bool, Error>;
+    /// Atomically applies the operations of `batch`.
+    fn apply_batch(&self, batch: Batch<Vec<u8>, Vec<u8>>) -> Result<(), Error>;
+    /// Iterates over the entries of `range`, in key order.
+    fn range(&self, range: BytesRange) -> Self::Iter;
+    /// Atomically removes and returns the entry with the smallest key.
+    fn pop_min(&self) -> Result<Option<(IVec, IVec)>, Error>;
+    /// Atomically removes and returns the entry with the largest key.
+    fn pop_max(&self) -> Result<Option<(IVec, IVec)>, Error>;
+    /// Atomically removes up to `n` entries from one end of the map, in iteration order.
+    fn pop_n(&self, n: usize, from_max: bool) -> Result<Vec<(IVec, IVec)>, Error>;
+    fn clear(&self) -> Result<(), Error>;
+    fn len(&self) -> usize;
+    /// Makes the writes so far durable, if the backend is persistent. Returns how many
+    /// bytes were written.
+    fn flush(&self) -> Result<usize, Error>;
+
+    /// The backend stored in `tree`, for [`StrictTree::new`].
+    ///
+    /// # Panics
+    ///
+    /// By default, as entries aren't moved between engines behind the caller's back.
+    /// Backends other than sled are made with their own constructors and handed to
+    /// [`Tree::with_backend`].
+    fn from_sled_tree(tree: sled::Tree) -> Self {
+        drop(tree);
+        panic!("this backend can't be opened from a sled tree, use Tree::with_backend")
+    }
+
+    fn contains_key(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    fn iter(&self) -> Self::Iter {
+        self.range((Unbounded, Unbounded))
+    }
+
+    /// Iterates over the entries whose key starts with `prefix`.
+    fn scan_prefix(&self, prefix: &[u8]) -> Self::Iter {
+        self.range(prefix_range(prefix))
+    }
+
+    fn first(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        self.iter().next().transpose()
+    }
+
+    fn last(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        self.iter().next_back().transpose()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+type SledEntry = sled::Result<(IVec, IVec)>;
+
+impl KvBackend for sled::Tree {
+    type Iter = std::iter::Map<sled::Iter, fn(SledEntry) -> Result<(IVec, IVec), Error>>;
+
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(sled::Tree::get(self, key)?)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(sled::Tree::insert(self, key, value)?)
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(sled::Tree::remove(self, key)?)
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        old: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> Result<bool, Error> {
+        Ok(sled::Tree::compare_and_swap(self, key, old, new)?.is_ok())
+    }
+
+    fn apply_batch(&self, batch: Batch<Vec<u8>, Vec<u8>>) -> Result<(), Error> {
+        let mut sled_batch = sled::Batch::default();
+        for (key, value) in batch {
+            match value {
+                Some(value) => sled_batch.insert(key, value),
+                None => sled_batch.remove(key),
+            }
+        }
+
+        Ok(sled::Tree::apply_batch(self, sled_batch)?)
+    }
+
+    fn range(&self, range: BytesRange) -> Self::Iter {
+        sled::Tree::range(self, range).map(|entry| entry.map_err(Error::from))
+    }
+
+    fn pop_min(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(sled::Tree::pop_min(self)?)
+    }
+
+    fn pop_max(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(sled::Tree::pop_max(self)?)
+    }
+
+    fn pop_n(&self, n: usize, from_max: bool) -> Result<Vec<(IVec, IVec)>, Error> {
+        crate::pop_n(self, n, from_max)
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        Ok(sled::Tree::clear(self)?)
+    }
+
+    fn len(&self) -> usize {
+        sled::Tree::len(self)
+    }
+
+    fn flush(&self) -> Result<usize, Error> {
+        Ok(sled::Tree::flush(self)?)
+    }
+
+    fn from_sled_tree(tree: sled::Tree) -> Self {
+        tree
+    }
+
+    fn contains_key(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(sled::Tree::contains_key(self, key)?)
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Self::Iter {
+        sled::Tree::scan_prefix(self, prefix).map(|entry| entry.map_err(Error::from))
+    }
+
+    fn first(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(sled::Tree::first(self)?)
+    }
+
+    fn last(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(sled::Tree::last(self)?)
+    }
+
+    fn is_empty(&self) -> bool {
+        sled::Tree::is_empty(self)
+    }
+}
+
+/// A [`KvBackend`] keeping its entries in a `BTreeMap`, lost when the last clone of it
+/// is dropped. Like those of sled, its iterators may see the writes made while they
+/// run, as they read [`MEMORY_ITER_CHUNK`] entries at a time.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    entries: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.entries.read().expect("lock isn't poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.entries.write().expect("lock isn't poisoned")
+    }
+}
+
+impl KvBackend for MemoryBackend {
+    type Iter = MemoryIter;
+
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(self.read().get(key).map(|value| value.as_slice().into()))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(self
+            .write()
+            .insert(key.to_vec(), value.to_vec())
+            .map(IVec::from))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Error> {
+        Ok(self.write().remove(key).map(IVec::from))
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        old: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> Result<bool, Error> {
+        let mut entries = self.write();
+        if entries.get(key).map(Vec::as_slice) != old {
+            return Ok(false);
+        }
+
+        match new {
+            Some(new) => entries.insert(key.to_vec(), new.to_vec()),
+            None => entries.remove(key),
+        };
+
+        Ok(true)
+    }
+
+    fn apply_batch(&self, batch: Batch<Vec<u8>, Vec<u8>>) -> Result<(), Error> {
+        let mut entries = self.write();
+        for (key, value) in batch {
+            match value {
+                Some(value) => entries.insert(key, value),
+                None => entries.remove(&key),
+            };
+        }
+
+        Ok(())
+    }
+
+    fn range(&self, (start, end): BytesRange) -> Self::Iter {
+        MemoryIter {
+            entries: self.entries.clone(),
+            start,
+            end,
+            front: VecDeque::new(),
+            back: VecDeque::new(),
+        }
+    }
+
+    fn pop_min(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(self
+            .write()
+            .pop_first()
+            .map(|(key, value)| (key.into(), value.into())))
+    }
+
+    fn pop_max(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(self
+            .write()
+            .pop_last()
+            .map(|(key, value)| (key.into(), value.into())))
+    }
+
+    fn pop_n(&self, n: usize, from_max: bool) -> Result<Vec<(IVec, IVec)>, Error> {
+        let mut entries = self.write();
+        let popped = (0..n).map_while(|_| match from_max {
+            true => entries.pop_last(),
+            false => entries.pop_first(),
+        });
+
+        Ok(popped
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.write().clear();
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    fn flush(&self) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    fn contains_key(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.read().contains_key(key))
+    }
+
+    fn first(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(self.read().first_key_value().map(to_entry))
+    }
+
+    fn last(&self) -> Result<Option<(IVec, IVec)>, Error> {
+        Ok(self.read().last_key_value().map(to_entry))
+    }
+}
+
+/// How many entries a [`MemoryIter`] reads at a time.
+pub const MEMORY_ITER_CHUNK: usize = 128;
+
+/// Iterator over a range of a [`MemoryBackend`].
+pub struct MemoryIter {
+    entries: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+    /// Bounds of the entries that weren't read yet.
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    front: VecDeque<(IVec, IVec)>,
+    back: VecDeque<(IVec, IVec)>,
+}
+
+impl MemoryIter {
+    /// Reads the next chunk of entries from the start of the remaining range, or from
+    /// its end if `from_back`.
+    fn read_chunk(&mut self, from_back: bool) {
+        let range = (self.start.clone(), self.end.clone());
+        // `BTreeMap::range` panics on empty ranges like `b"b".."a"`
+        if is_empty_range(&range) {
+            return;
+        }
+
+        let entries = self.entries.read().expect("lock isn't poisoned");
+        let chunk = entries.range::<Vec<u8>, _>(range);
+        if from_back {
+            for entry in chunk.rev().take(MEMORY_ITER_CHUNK) {
+                self.back.push_front(to_entry(entry));
+            }
+            if let Some((key, _)) = self.back.front() {
+                self.end = Excluded(key.to_vec());
+            }
+        } else {
+            for entry in chunk.take(MEMORY_ITER_CHUNK) {
+                self.front.push_back(to_entry(entry));
+            }
+            if let Some((key, _)) = self.front.back() {
+                self.start = Excluded(key.to_vec());
+            }
+        }
+    }
+}
+
+impl Iterator for MemoryIter {
+    type Item = Result<(IVec, IVec), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front.is_empty() {
+            self.read_chunk(false);
+        }
+
+        self.front
+            .pop_front()
+            .or_else(|| self.back.pop_front())
+            .map(Ok)
+    }
+}
+
+impl DoubleEndedIterator for MemoryIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.back.is_empty() {
+            self.read_chunk(true);
+        }
+
+        self.back
+            .pop_back()
+            .or_else(|| self.front.pop_back())
+            .map(Ok)
+    }
+}
+
+fn to_entry((key, value): (&Vec<u8>, &Vec<u8>)) -> (IVec, IVec) {
+    (key.as_slice().into(), value.as_slice().into())
+}
+
+fn is_empty_range((start, end): &BytesRange) -> bool {
+    match (start, end) {
+        (Included(start), Included(end)) => start > end,
+        (Included(start), Excluded(end))
+        | (Excluded(start), Included(end))
+        | (Excluded(start), Excluded(end)) => start >= end,
+        _ => false,
+    }
+}
+
+/// The range of the keys starting with `prefix`.
+fn prefix_range(prefix: &[u8]) -> BytesRange {
+    // The smallest key after every key starting with `prefix`, if there is one
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return (Included(prefix.to_vec()), Excluded(end));
+        }
+    }
+
+    (Included(prefix.to_vec()), Unbounded)
+}
+
+/// Converts the bounds of a range of byte strings into a [`BytesRange`].
+pub(crate) fn bytes_range<KB: AsRef<[u8]>, R: std::ops::RangeBounds<KB>>(range: R) -> BytesRange {
+    let to_bytes = |bound: Bound<&KB>| bound.map(|key| key.as_ref().to_vec());
+
+    (to_bytes(range.start_bound()), to_bytes(range.end_bound()))
+}
+
+/// A strict tree kept in memory, to unit-test code written against
+/// [`crate::StrictTree`] or [`crate::tree::Tree`] without a [`crate::Db`]. Its clones
+/// share their entries.
+pub type MemoryTree<K, V, VC = BincodeCodec, KC = OrderedKeyCodec> =
+    Tree<K, V, VC, DefaultConfig, KC, MemoryBackend>;
+
+impl<K, V, VC: ValueCodec<V>, KC: KeyCodec<K>> Default for MemoryTree<K, V, VC, KC> {
+    fn default() -> Self {
+        Self::with_backend(MemoryBackend::new())
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, KC: KeyCodec<K>> MemoryTree<K, V, VC, KC> {
+    /// A tree holding `entries`, e.g. as a test fixture.
+    pub fn from_entries<I: IntoIterator<Item = (K, V)>>(entries: I) -> Result<Self, Error> {
+        let tree = Self::default();
+        for (key, value) in entries {
+            tree.insert(&key, &value)?;
+        }
+
+        Ok(tree)
+    }
+}
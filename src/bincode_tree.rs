@@ -1,17 +1,26 @@
+use bincode::config::{Config, Configuration, Limit};
 use bincode::{Decode, Encode};
-use std::ops::Bound::{Excluded, Included, Unbounded};
-use std::{marker::PhantomData, ops::RangeBounds};
+use sled::IVec;
+use std::ops::RangeBounds;
+use std::sync::Arc;
 
-use crate::{error::Error, StrictTree};
-use crate::{RelaxedBincodeTree, BINCODE_CONFIG};
+use crate::codec::{bincode_decode as decode, bincode_encode as encode, BincodeCodec};
+use crate::keys::{KeyCodec, OrderedKey, OrderedKeyCodec};
+use crate::quota::{Quota, QuotaState};
+use crate::subscriber::Subscriber;
+use crate::tree::{RawTree, Tree};
+use crate::{check, error::Error, patch::Patch, projection::FieldSelector};
+use crate::{DefaultConfig, RelaxedBincodeTree};
+
+pub use crate::tree::Batch;
 
 /// A wrapper around a `sled::Tree` for types implementing `bincode::Decode` and/or `bincode::Encode`.
 /// This allows you to work with ANY type as long as they implement them, so you can have deserialisation
 /// issues if the type you are expecting isn't the one that is actually used.
 /// For this reason [`BincodeTree`] is recommended instead.
 #[derive(Clone)]
-pub struct RelaxedTree {
-    inner_tree: sled::Tree,
+pub struct RelaxedTree<C = DefaultConfig> {
+    inner_tree: RawTree<C>,
 }
 
 /// Type strict tree for types implementing `bincode::Decode` _and_ `bincode::Encode`.
@@ -21,288 +30,468 @@ pub struct RelaxedTree {
 /// While this should prevent type errors, it is only a best effort:
 /// [`sled`] stores everything as bytes, and therefore it is never a guarantee
 /// that the things stored in the tree are of the type you expect.
-#[derive(Clone)]
-pub struct BincodeTree<K: Encode + Decode, V: Encode + Decode> {
-    inner_tree: RelaxedTree,
-    key_type: PhantomData<K>,
-    value_type: PhantomData<V>,
+///
+/// Keys are encoded with `KC`, by default the order-preserving [`OrderedKeyCodec`].
+/// See the [`crate::keys`] module.
+pub type BincodeTree<K, V, C = DefaultConfig, KC = OrderedKeyCodec> =
+    Tree<K, V, BincodeCodec, C, KC>;
+
+impl<E, I, L> RelaxedTree<Configuration<E, I, L>> {
+    /// Limits how many bytes a single decode is allowed to claim, so that a
+    /// corrupted length prefix returns [`Error::LimitExceeded`] instead of
+    /// attempting a huge allocation.
+    pub fn with_limit<const N: usize>(self) -> RelaxedTree<Configuration<E, I, Limit<N>>> {
+        RelaxedTree {
+            inner_tree: self.inner_tree.with_limit::<N>(),
+        }
+    }
 }
 
-impl RelaxedBincodeTree for RelaxedTree {
-    fn new(sled_tree: sled::Tree) -> Self {
-        Self {
-            inner_tree: sled_tree,
+impl<C> RelaxedTree<C> {
+    /// Replaces the bincode configuration used to encode and decode keys and values,
+    /// for instance to use fixed-size integers or a decode size limit for untrusted
+    /// data. Entries written with another configuration won't decode.
+    pub fn with_config<C2: Config>(self, config: C2) -> RelaxedTree<C2> {
+        RelaxedTree {
+            inner_tree: self.inner_tree.with_config(config),
         }
     }
+}
 
-    /// Retrieve value from table.
-    fn get<K: Encode, V: Decode>(&self, key: &K) -> Result<Option<V>, Error> {
-        let bytes = bincode::encode_to_vec(key, BINCODE_CONFIG)?;
-
-        match self.inner_tree.get(bytes)? {
-            Some(res_ivec) => {
-                let (deser, _size) = bincode::decode_from_slice::<V, _>(&res_ivec, BINCODE_CONFIG)?;
-
-                Ok(Some(deser))
-            }
-            None => Ok(None),
+impl<K, V, C, KC> BincodeTree<K, V, C, KC> {
+    /// Turns this tree into a [`RelaxedTree`] over the same entries, e.g. to rewrite
+    /// them as other types during a migration, and back with
+    /// [`RelaxedTree::assume_types`]. Its configuration, strict decoding and quota are
+    /// kept, but key validation isn't. The relaxed tree encodes keys with bincode, so
+    /// unless `KC` is [`crate::keys::BincodeKeyCodec`], read its entries with `iter`
+    /// or the `*_raw` methods rather than by key.
+    pub fn into_relaxed(self) -> RelaxedTree<C> {
+        RelaxedTree {
+            inner_tree: self.into_raw(),
         }
     }
+}
 
-    /// Insert value into table.
-    fn insert<K: Encode, V: Encode + Decode>(
-        &self,
-        key: &K,
-        value: &V,
-    ) -> Result<Option<V>, Error> {
-        let key_bytes = bincode::encode_to_vec(key, BINCODE_CONFIG)?;
-        let value_bytes = bincode::encode_to_vec(value, BINCODE_CONFIG)?;
-
-        match self.inner_tree.insert(key_bytes, value_bytes)? {
-            Some(ivec) => {
-                let (old_value, _size) = bincode::decode_from_slice::<V, _>(&ivec, BINCODE_CONFIG)?;
-
-                Ok(Some(old_value))
-            }
-            None => Ok(None),
-        }
+impl<C: Config> RelaxedTree<C> {
+    /// Turns this tree back into a strict [`BincodeTree`] of keys `K` and values `V`,
+    /// keeping its configuration and quota and enabling strict decoding. Nothing is
+    /// checked, not even the tree's schema fingerprint as
+    /// [`crate::Db::open_bincode_tree`] would. Relaxed trees encode keys with bincode
+    /// while strict trees default to [`OrderedKeyCodec`]: to read keys written by this
+    /// tree, use [`RelaxedTree::assume_types_with_key_codec`] with
+    /// [`crate::keys::BincodeKeyCodec`].
+    pub fn assume_types<K: OrderedKey, V: Encode + Decode>(self) -> BincodeTree<K, V, C> {
+        Tree::from_raw(self.inner_tree)
     }
 
-    fn first<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
-        match self.inner_tree.first()? {
-            Some((key_ivec, value_ivec)) => {
-                let (key, _size) = bincode::decode_from_slice::<K, _>(&key_ivec, BINCODE_CONFIG)?;
+    /// [`RelaxedTree::assume_types`] with keys encoded by `KC`.
+    pub fn assume_types_with_key_codec<K, V: Encode + Decode, KC: KeyCodec<K>>(
+        self,
+    ) -> BincodeTree<K, V, C, KC> {
+        Tree::from_raw(self.inner_tree)
+    }
 
-                let (value, _size) =
-                    bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG)?;
+    /// When enabled, decoding a key or value that leaves trailing bytes returns
+    /// [`Error::TrailingBytes`]. This usually means the stored type isn't the one
+    /// you are decoding as. Disabled by default for relaxed trees.
+    pub fn with_strict_decode(mut self, strict: bool) -> Self {
+        self.inner_tree.strict_decode = strict;
+        self
+    }
 
-                Ok(Some((key, value)))
-            }
-            None => Ok(None),
-        }
+    /// Limits the number of entries and bytes of the tree, enforced by the writes of
+    /// this handle as told by the quota's [`crate::quota::QuotaPolicy`]. See [`Quota`]
+    /// for how the tree's usage is tracked.
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.inner_tree.quota = Some(Arc::new(QuotaState::new(quota)));
+        self
     }
 
-    fn last<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
-        match self.inner_tree.last()? {
-            Some((key_ivec, value_ivec)) => {
-                let (key, _size) = bincode::decode_from_slice::<K, _>(&key_ivec, BINCODE_CONFIG)?;
+    /// Writes the dirty data of the database to disk, returning how many bytes were
+    /// flushed. Like in sled, this flushes every tree, not only this one.
+    pub fn flush(&self) -> Result<usize, Error> {
+        self.inner_tree.flush()
+    }
 
-                let (value, _size) =
-                    bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG)?;
+    /// Asynchronous version of `flush`.
+    pub async fn flush_async(&self) -> Result<usize, Error> {
+        self.inner_tree.flush_async().await
+    }
 
-                Ok(Some((key, value)))
-            }
-            None => Ok(None),
-        }
+    /// The underlying sled tree, to mix typed and raw access without opening it
+    /// again. Writes through it bypass the tree's quota.
+    pub fn inner(&self) -> &sled::Tree {
+        self.inner_tree.inner()
     }
 
-    fn iter<K: Decode, V: Decode>(&self) -> impl DoubleEndedIterator<Item = (K, V)> {
-        self.inner_tree.into_iter().filter_map(|res| match res {
-            Ok((key_ivec, value_ivec)) => {
-                let key = bincode::decode_from_slice::<K, _>(&key_ivec, BINCODE_CONFIG).ok();
+    /// Gets the stored bytes of `key`, an already encoded key, without decoding them.
+    pub fn get_raw<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<IVec>, Error> {
+        self.inner_tree.get_raw(key)
+    }
 
-                let value = bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG).ok();
+    /// Inserts already encoded bytes, returning the bytes they replaced. Nothing
+    /// checks that they decode. The tree's quota still applies.
+    pub fn insert_raw<K: AsRef<[u8]>, V: Into<IVec>>(
+        &self,
+        key: K,
+        value: V,
+    ) -> Result<Option<IVec>, Error> {
+        self.inner_tree.insert_raw(key, value)
+    }
 
-                if let Some((key, _size)) = key {
-                    if let Some((value, _size)) = value {
-                        return Some((key, value));
-                    }
-                }
+    /// Removes `key`, an already encoded key, returning the bytes it held.
+    pub fn remove_raw<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<IVec>, Error> {
+        self.inner_tree.remove_raw(key)
+    }
 
-                return None;
-            }
-            Err(_) => None,
-        })
+    fn encode<T: Encode>(&self, value: &T) -> Result<Vec<u8>, Error> {
+        encode(value, self.inner_tree.config)
     }
 
-    fn range_key_bytes<K: AsRef<[u8]>, R: RangeBounds<K>, V: Decode>(
+    /// Like `iter`, but yields an error for every entry that fails to decode
+    /// instead of skipping it.
+    pub fn try_iter<K: Decode, V: Decode>(
         &self,
-        range: R,
-    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> {
-        self.inner_tree.range(range).filter_map(|res| match res {
-            Ok((key_ivec, value_ivec)) => {
-                let key = key_ivec.to_vec();
-
-                let value = bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG).ok();
-
-                if let Some((value, _)) = value {
-                    Some((key, value))
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        })
+    ) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> {
+        self.inner_tree.try_entries(
+            self.inner_tree.inner_tree.iter(),
+            decode::<K, C>,
+            decode::<V, C>,
+        )
     }
 
-    fn clear(&self) -> Result<(), Error> {
-        Ok(self.inner_tree.clear()?)
+    /// Decodes every entry as `(K, V)`, rejecting trailing bytes, and reports the
+    /// ones that fail with their raw bytes and the error. Unlike `iter`, which skips
+    /// such entries, this is meant to find them.
+    pub fn verify<K: Decode, V: Decode>(&self) -> Result<check::TreeReport, Error> {
+        self.inner_tree.verify_with(decode::<K, C>, decode::<V, C>)
     }
 
-    fn contains_key<K: Encode>(&self, key: &K) -> Result<bool, Error> {
-        let key_bytes = bincode::encode_to_vec(key, BINCODE_CONFIG)?;
+    /// Like `verify`, and moves the entries that fail to decode into the sibling
+    /// `<tree>_quarantine` tree of `db`, with their raw bytes preserved, so that they
+    /// can be inspected or recovered later. `db` must be the database of this tree.
+    pub fn repair<K: Decode, V: Decode>(&self, db: &crate::Db) -> Result<check::TreeReport, Error> {
+        self.inner_tree
+            .repair_with(db, decode::<K, C>, decode::<V, C>)
+    }
 
-        Ok(self.inner_tree.contains_key(key_bytes)?)
+    /// Like `range`, but yields an error for every entry that fails to decode
+    /// instead of skipping it.
+    pub fn try_range<K: Encode + Decode, R: RangeBounds<K>, V: Decode>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = Result<(K, V), Error>>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, encode::<K, C>)?;
+
+        Ok(self.inner_tree.try_entries(
+            self.inner_tree.inner_tree.range(bytes_range),
+            decode::<K, C>,
+            decode::<V, C>,
+        ))
     }
 
-    fn pop_max<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
-        match self.inner_tree.pop_max()? {
-            Some((key_ivec, value_ivec)) => {
-                let (key, _size) = bincode::decode_from_slice::<K, _>(&key_ivec, BINCODE_CONFIG)?;
+    /// Iterates over the entries whose key starts with the encoding of `prefix`,
+    /// such as the first element of a tuple key. Entries that fail to decode are skipped.
+    pub fn scan_prefix<P: Encode, K: Decode, V: Decode>(
+        &self,
+        prefix: &P,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error> {
+        Ok(self.scan_prefix_bytes(self.encode(prefix)?))
+    }
 
-                let (value, _size) =
-                    bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG)?;
+    /// Like [`RelaxedTree::scan_prefix`], with a prefix given as raw bytes.
+    pub fn scan_prefix_bytes<P: AsRef<[u8]>, K: Decode, V: Decode>(
+        &self,
+        prefix: P,
+    ) -> impl DoubleEndedIterator<Item = (K, V)> {
+        self.inner_tree.entries(
+            self.inner_tree.inner_tree.scan_prefix(prefix),
+            decode::<K, C>,
+            decode::<V, C>,
+        )
+    }
 
-                Ok(Some((key, value)))
-            }
-            None => Ok(None),
-        }
+    /// Iterates over the entries whose key starts with the encoding of `prefix`,
+    /// decoding only the rest of the key as `S`. With `(UserId, OrderId)` keys,
+    /// `bucket::<_, OrderId, Order>(&user_id)` yields the orders of a single user.
+    /// Entries that fail to decode are skipped, like with `iter`.
+    pub fn bucket<P: Encode, S: Decode, V: Decode>(
+        &self,
+        prefix: &P,
+    ) -> Result<impl DoubleEndedIterator<Item = (S, V)>, Error> {
+        Ok(self
+            .inner_tree
+            .bucket_with(self.encode(prefix)?, decode::<S, C>, decode::<V, C>))
     }
 
-    fn len(&self) -> usize {
-        self.inner_tree.len()
+    /// Subscribes to changes of the entries whose key starts with the encoding of `prefix`,
+    /// such as the first element of a tuple key. See [`Subscriber`].
+    pub fn watch_prefix<P: Encode, K: Decode + 'static, V: Decode + 'static>(
+        &self,
+        prefix: &P,
+    ) -> Result<Subscriber<K, V>, Error>
+    where
+        C: Send + Sync + 'static,
+    {
+        Ok(self
+            .inner_tree
+            .watch_prefix_with(self.encode(prefix)?, decode::<K, C>, decode::<V, C>))
     }
 
-    fn remove<K: Encode, V: Decode>(&self, key: &K) -> Result<Option<V>, Error> {
-        let bytes = bincode::encode_to_vec(key, BINCODE_CONFIG)?;
+    /// Decodes only the field picked by `selector` out of the value stored at `key`.
+    /// See [`FieldSelector`].
+    pub fn project<K: Encode, V, Before: Decode, F: Decode>(
+        &self,
+        key: &K,
+        _selector: FieldSelector<V, Before, F>,
+    ) -> Result<Option<F>, Error> {
+        let field = self
+            .inner_tree
+            .project_bytes(&self.encode(key)?, decode::<(Before, F), C>)?;
 
-        match self.inner_tree.remove(bytes)? {
-            Some(res_ivec) => {
-                let (deser, _size) = bincode::decode_from_slice::<V, _>(&res_ivec, BINCODE_CONFIG)?;
+        Ok(field.map(|(_, field)| field))
+    }
 
-                Ok(Some(deser))
-            }
-            None => Ok(None),
-        }
+    /// Applies `patch` to the value stored at `key` and returns the updated value,
+    /// or `None` if there is no such value. The update is atomic: if the value changes
+    /// in the meantime, the patch is applied again to the new value.
+    pub fn patch<K: Encode, V: Encode + Decode, P: Patch<V>>(
+        &self,
+        key: &K,
+        patch: &P,
+    ) -> Result<Option<V>, Error> {
+        self.inner_tree
+            .patch_bytes(&self.encode(key)?, patch, encode::<V, C>, decode::<V, C>)
     }
 
-    fn get_or_init<F: FnOnce() -> T, K: Encode, T: Encode + Decode>(
+    /// Inserts `value` at `key` only if `key` has no value yet, atomically, and returns
+    /// whether it was inserted. Unlike checking with `get` first, two concurrent calls
+    /// can't both insert.
+    pub fn insert_if_absent<K: Encode, V: Encode>(
         &self,
-        key: K,
-        init_func: F,
-    ) -> Result<Option<T>, Error> {
-        let res = match self.get(&key)? {
-            Some(v) => Some(v),
-            None => {
-                let value = init_func();
-                let _ = self.insert(&key, &value)?;
-                Some(value)
-            }
-        };
+        key: &K,
+        value: &V,
+    ) -> Result<bool, Error> {
+        self.inner_tree
+            .insert_if_absent_bytes(self.encode(key)?, self.encode(value)?)
+    }
 
-        Ok(res)
+    /// Applies every operation of `batch` atomically, in order: either all of them
+    /// land or none do. Nothing is written if an operation fails to encode.
+    pub fn apply_batch<K: Encode, V: Encode>(&self, batch: Batch<K, V>) -> Result<(), Error> {
+        self.inner_tree
+            .apply_batch_with(batch, encode::<K, C>, encode::<V, C>)
     }
 
-    fn range<K: Encode + Decode, R: RangeBounds<K>, V: Decode>(
+    /// Atomically replaces the value at `key` with the result of `f`, which receives
+    /// the current value, and returns the new value. Returning `None` from `f` removes
+    /// the entry. `f` may be called several times if the value changes concurrently.
+    pub fn update_and_fetch<K: Encode, V: Encode + Decode, F: FnMut(Option<V>) -> Option<V>>(
         &self,
-        range: R,
-    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error> {
-        let start_bound_bytes = match range.start_bound() {
-            Included(r) => Included(bincode::encode_to_vec(r, BINCODE_CONFIG)?),
-            Excluded(r) => Excluded(bincode::encode_to_vec(r, BINCODE_CONFIG)?),
-            Unbounded => Unbounded,
-        };
-        let end_bound_bytes = match range.end_bound() {
-            Included(r) => Included(bincode::encode_to_vec(r, BINCODE_CONFIG)?),
-            Excluded(r) => Excluded(bincode::encode_to_vec(r, BINCODE_CONFIG)?),
-            Unbounded => Unbounded,
-        };
-
+        key: &K,
+        f: F,
+    ) -> Result<Option<V>, Error> {
         Ok(self
             .inner_tree
-            .range((start_bound_bytes, end_bound_bytes))
-            .filter_map(|res| match res {
-                Ok((key_ivec, value_ivec)) => {
-                    let key = bincode::decode_from_slice::<K, _>(&key_ivec, BINCODE_CONFIG).ok();
+            .update_bytes(&self.encode(key)?, f, encode::<V, C>, decode::<V, C>)?
+            .1)
+    }
+
+    /// Like [`RelaxedTree::update_and_fetch`], but returns the value that was replaced.
+    pub fn fetch_and_update<K: Encode, V: Encode + Decode, F: FnMut(Option<V>) -> Option<V>>(
+        &self,
+        key: &K,
+        f: F,
+    ) -> Result<Option<V>, Error> {
+        self.inner_tree.fetch_and_update_bytes(
+            &self.encode(key)?,
+            f,
+            encode::<V, C>,
+            decode::<V, C>,
+        )
+    }
 
-                    let value =
-                        bincode::decode_from_slice::<V, _>(&value_ivec, BINCODE_CONFIG).ok();
+    /// Registers a merge operator called by [`RelaxedTree::merge`] with the key,
+    /// the current value if any and the merged delta, all decoded. Returning `None`
+    /// removes the entry. If the stored bytes fail to decode, the current value is kept.
+    /// Like sled's, the operator is not persisted and must be set again after reopening.
+    pub fn set_merge_operator<K, V, F>(&self, merge_operator: F)
+    where
+        C: 'static,
+        K: Decode + 'static,
+        V: Encode + Decode + 'static,
+        F: Fn(K, Option<V>, V) -> Option<V> + 'static,
+    {
+        self.inner_tree.set_merge_operator_with(
+            merge_operator,
+            decode::<K, C>,
+            encode::<V, C>,
+            decode::<V, C>,
+        )
+    }
 
-                    if let Some((key, _size)) = key {
-                        if let Some((value, _size)) = value {
-                            return Some((key, value));
-                        }
-                    }
+    /// Merges `delta` into the value at `key` with the operator set by
+    /// [`RelaxedTree::set_merge_operator`], and returns the merged value.
+    /// Fails if no merge operator was set.
+    pub fn merge<K: Encode, V: Encode + Decode>(
+        &self,
+        key: &K,
+        delta: &V,
+    ) -> Result<Option<V>, Error> {
+        self.inner_tree
+            .merge_bytes(self.encode(key)?, delta, encode::<V, C>, decode::<V, C>)
+    }
 
-                    return None;
-                }
-                Err(_) => None,
-            }))
+    /// Decodes every entry as `(K, V)` and writes it back encoded with the tree's
+    /// current settings, e.g. after enabling compression. Returns how many entries
+    /// changed.
+    ///
+    /// The tree is rewritten in place, not swapped with a new tree, so the rewrite as
+    /// a whole isn't atomic. Nothing is written if an entry fails to decode. Entries are
+    /// then rewritten [`crate::admin::COPY_BATCH_SIZE`] at a time, each batch in a
+    /// transaction that reads them again, so writes made meanwhile aren't lost and every
+    /// key has either its old or its new encoding, never both. An interrupted rewrite
+    /// finishes when run again. sled reclaims the space of the old entries as it
+    /// compacts its log.
+    pub fn rewrite_in_place<K: Encode + Decode, V: Encode + Decode>(&self) -> Result<usize, Error> {
+        self.inner_tree.rewrite_in_place_with(
+            encode::<K, C>,
+            decode::<K, C>,
+            encode::<V, C>,
+            decode::<V, C>,
+        )
     }
 }
 
-impl<KeyItem, ValueItem> StrictTree<KeyItem, ValueItem> for BincodeTree<KeyItem, ValueItem>
-where
-    KeyItem: Encode + Decode,
-    ValueItem: Encode + Decode,
-{
-    fn new(tree: sled::Tree) -> Self {
+impl<C: Config + Default> RelaxedBincodeTree for RelaxedTree<C> {
+    fn new(sled_tree: sled::Tree) -> Self {
         Self {
-            inner_tree: RelaxedTree::new(tree),
-            key_type: PhantomData,
-            value_type: PhantomData,
+            inner_tree: RawTree::new(sled_tree, C::default(), false),
         }
     }
 
-    fn get(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
-        self.inner_tree.get(key)
+    /// Retrieve value from table.
+    fn get<K: Encode, V: Decode>(&self, key: &K) -> Result<Option<V>, Error> {
+        self.inner_tree
+            .get_bytes(&self.encode(key)?, decode::<V, C>)
     }
 
-    fn get_or_init<F: FnOnce() -> ValueItem>(
+    /// Insert value into table.
+    fn insert<K: Encode, V: Encode + Decode>(
         &self,
-        key: KeyItem,
-        init_func: F,
-    ) -> Result<Option<ValueItem>, Error> {
-        self.inner_tree.get_or_init(key, init_func)
+        key: &K,
+        value: &V,
+    ) -> Result<Option<V>, Error> {
+        self.inner_tree
+            .insert_bytes(self.encode(key)?, value, encode::<V, C>, decode::<V, C>)
     }
 
-    fn insert(&self, key: &KeyItem, value: &ValueItem) -> Result<Option<ValueItem>, Error> {
-        self.inner_tree.insert(key, value)
+    fn first<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.inner_tree.first()?,
+            decode::<K, C>,
+            decode::<V, C>,
+        )
     }
 
-    fn first(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
-        self.inner_tree.first()
+    fn last<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.inner_tree.last()?,
+            decode::<K, C>,
+            decode::<V, C>,
+        )
     }
 
-    fn last(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
-        self.inner_tree.last()
+    fn iter<K: Decode, V: Decode>(&self) -> impl DoubleEndedIterator<Item = (K, V)> {
+        self.inner_tree.entries(
+            self.inner_tree.inner_tree.iter(),
+            decode::<K, C>,
+            decode::<V, C>,
+        )
     }
 
-    fn pop_max(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
-        self.inner_tree.pop_max()
+    fn range_key_bytes<K: AsRef<[u8]>, R: RangeBounds<K>, V: Decode>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> {
+        self.inner_tree
+            .key_bytes_entries(self.inner_tree.inner_tree.range(range), decode::<V, C>)
     }
 
-    fn iter(&self) -> impl DoubleEndedIterator<Item = (KeyItem, ValueItem)> {
-        self.inner_tree.iter()
+    fn clear(&self) -> Result<(), Error> {
+        self.inner_tree.clear()
     }
 
-    fn range_key_bytes<KeyBytes: AsRef<[u8]>, R: RangeBounds<KeyBytes>>(
-        &self,
-        range: R,
-    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, ValueItem)> {
-        self.inner_tree.range_key_bytes(range)
+    fn contains_key<K: Encode>(&self, key: &K) -> Result<bool, Error> {
+        Ok(self.inner_tree.inner_tree.contains_key(self.encode(key)?)?)
     }
 
-    fn range<R: RangeBounds<KeyItem>>(
-        &self,
-        range: R,
-    ) -> Result<impl DoubleEndedIterator<Item = (KeyItem, ValueItem)>, Error> {
-        self.inner_tree.range(range)
+    fn pop_max<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.pop_raw(true)?,
+            decode::<K, C>,
+            decode::<V, C>,
+        )
     }
 
-    fn clear(&self) -> Result<(), Error> {
-        self.inner_tree.clear()
+    fn pop_min<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.pop_raw(false)?,
+            decode::<K, C>,
+            decode::<V, C>,
+        )
+    }
+
+    fn pop_n_min<K: Decode, V: Decode>(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        self.inner_tree
+            .pop_n_with(n, false, decode::<K, C>, decode::<V, C>)
     }
 
-    fn contains_key(&self, key: &KeyItem) -> Result<bool, Error> {
-        self.inner_tree.contains_key(key)
+    fn pop_n_max<K: Decode, V: Decode>(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        self.inner_tree
+            .pop_n_with(n, true, decode::<K, C>, decode::<V, C>)
     }
 
     fn len(&self) -> usize {
-        self.inner_tree.len()
+        self.inner_tree.inner_tree.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner_tree.inner_tree.is_empty()
     }
 
-    fn remove(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
-        self.inner_tree.remove(key)
+    fn remove<K: Encode, V: Decode>(&self, key: &K) -> Result<Option<V>, Error> {
+        self.inner_tree
+            .remove_bytes(&self.encode(key)?, decode::<V, C>)
+    }
+
+    fn get_or_init<F: FnOnce() -> T, K: Encode, T: Encode + Decode>(
+        &self,
+        key: K,
+        init_func: F,
+    ) -> Result<Option<T>, Error> {
+        let res = match self.get(&key)? {
+            Some(v) => Some(v),
+            None => {
+                let value = init_func();
+                let _ = self.insert(&key, &value)?;
+                Some(value)
+            }
+        };
+
+        Ok(res)
+    }
+
+    fn range<K: Encode + Decode, R: RangeBounds<K>, V: Decode>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, encode::<K, C>)?;
+
+        Ok(self.inner_tree.entries(
+            self.inner_tree.inner_tree.range(bytes_range),
+            decode::<K, C>,
+            decode::<V, C>,
+        ))
     }
 }
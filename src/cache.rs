@@ -0,0 +1,240 @@
+use bincode::{Decode, Encode};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+};
+use sled::IVec;
+
+use crate::bincode_tree::BincodeTree;
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::OrderedKey;
+use crate::multi_tree::{self, decode_u64};
+use crate::{error::Error, Db, DefaultConfig, StrictTree};
+
+pub(crate) fn recency_name(tree_name: &str) -> String {
+    format!("{tree_name}_recency")
+}
+
+/// Key of the number of entries in the recency tree.
+const COUNT_KEY: &[u8] = b"c";
+/// Prefix of the keys mapping an entry to the tick of its last access.
+const ACCESS_PREFIX: u8 = b'k';
+/// Prefix of the keys mapping a tick to the entry accessed then.
+const TICK_PREFIX: u8 = b't';
+
+/// A strict tree holding at most `capacity` entries, which evicts its least recently
+/// used entries on insert. Opened with [`Db::open_cache_tree`].
+///
+/// Every `insert` and `get` records the time of the access, as an ID taken from
+/// [`Db::generate_id`], in the `<name>_recency` tree, in the same transaction as the
+/// write. This makes reads cost a write: use [`CacheTree::peek`] to read without
+/// counting as an access. Writes made through other handles to the tree aren't tracked.
+#[derive(Clone)]
+pub struct CacheTree<K, V> {
+    db: Db,
+    tree: BincodeTree<K, V>,
+    recency: sled::Tree,
+    capacity: u64,
+}
+
+impl<K: OrderedKey, V: Encode + Decode> CacheTree<K, V> {
+    /// Tracks the entries already in `tree`, in key order, if it wasn't tracked before.
+    pub(crate) fn new(
+        db: Db,
+        tree: BincodeTree<K, V>,
+        recency: sled::Tree,
+        capacity: u64,
+    ) -> Result<Self, Error> {
+        let cache = Self {
+            db,
+            tree,
+            recency,
+            capacity,
+        };
+
+        if cache.recency.get(COUNT_KEY)?.is_none() {
+            for key in cache.sled_tree().iter().keys() {
+                cache.touch(&key?, None)?;
+            }
+            cache.evict()?;
+        }
+
+        Ok(cache)
+    }
+
+    /// Inserts `value`, marks `key` as the most recently used, and evicts the least
+    /// recently used entries if the cache is over capacity. Returns the previous value.
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        self.tree.check_key(key)?;
+        let key_bytes = self.tree.encode_key(key)?;
+        let value = BincodeCodec::encode(value, self.tree.raw().config)?;
+
+        let old = self.touch(&key_bytes, Some(&value))?;
+        self.evict()?;
+
+        self.decode_old(&key_bytes, old)
+    }
+
+    /// Gets the value of `key`, and marks it as the most recently used.
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+        let tick = self.db.generate_id()?;
+
+        let value = multi_tree::transaction(&self.trees(), |(tree, recency)| {
+            let Some(value) = tree.get(&key_bytes)? else {
+                return Ok(None);
+            };
+            set_tick(recency, &key_bytes, tick)?;
+            Ok(Some(value))
+        })?;
+
+        self.decode_old(&key_bytes, value.ok().flatten())
+    }
+
+    /// Gets the value of `key` without marking it as used.
+    pub fn peek(&self, key: &K) -> Result<Option<V>, Error> {
+        self.tree.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        self.tree.contains_key(key)
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+
+        let old = multi_tree::transaction(&self.trees(), |(tree, recency)| {
+            let old = tree.remove(key_bytes.as_slice())?;
+            if old.is_some() {
+                remove_tick(recency, &key_bytes)?;
+            }
+            Ok(old)
+        })?;
+
+        self.decode_old(&key_bytes, old.ok().flatten())
+    }
+
+    /// Number of entries, read from the stored count.
+    pub fn len(&self) -> Result<u64, Error> {
+        Ok(self
+            .recency
+            .get(COUNT_KEY)?
+            .map_or(0, |count| decode_u64(&count)))
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// The cached tree, to read from it without marking entries as used. Writes
+    /// through it aren't tracked.
+    pub fn tree(&self) -> &BincodeTree<K, V> {
+        &self.tree
+    }
+
+    /// Writes `value`, if any, and marks `key_bytes` as the most recently used.
+    /// Returns the previous value.
+    fn touch(&self, key_bytes: &[u8], value: Option<&[u8]>) -> Result<Option<IVec>, Error> {
+        let tick = self.db.generate_id()?;
+
+        let old = multi_tree::transaction(&self.trees(), |(tree, recency)| {
+            let old = match value {
+                Some(value) => tree.insert(key_bytes, value)?,
+                None => tree.get(key_bytes)?,
+            };
+            set_tick(recency, key_bytes, tick)?;
+            Ok(old)
+        })?;
+
+        Ok(old.ok().flatten())
+    }
+
+    /// Removes the least recently used entries until the cache fits its capacity.
+    fn evict(&self) -> Result<(), Error> {
+        while self.len()? > self.capacity {
+            let Some(oldest) = self.recency.scan_prefix([TICK_PREFIX]).next() else {
+                return Ok(());
+            };
+            let (tick_key, key_bytes) = oldest?;
+
+            // Accessed or evicted concurrently otherwise: check again
+            let _ = multi_tree::transaction(&self.trees(), |(tree, recency)| {
+                if recency.get(&tick_key)?.is_none() {
+                    return Err(ConflictableTransactionError::Abort(()));
+                }
+                tree.remove(&key_bytes)?;
+                remove_tick(recency, &key_bytes)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn sled_tree(&self) -> &sled::Tree {
+        &self.tree.raw().inner_tree
+    }
+
+    fn decode_old(&self, key: &[u8], old: Option<IVec>) -> Result<Option<V>, Error> {
+        old.map(|old| {
+            self.tree
+                .raw()
+                .decode_stored(BincodeCodec::decode::<DefaultConfig>, key, &old)
+        })
+        .transpose()
+    }
+
+    /// The tree and its recency tree, to run transactions on.
+    fn trees(&self) -> (&sled::Tree, &sled::Tree) {
+        (self.sled_tree(), &self.recency)
+    }
+}
+
+fn access_key(key_bytes: &[u8]) -> Vec<u8> {
+    [&[ACCESS_PREFIX], key_bytes].concat()
+}
+
+fn tick_key(tick: &[u8]) -> Vec<u8> {
+    [&[TICK_PREFIX], tick].concat()
+}
+
+/// Records `tick` as the last access to `key_bytes`, counting it if it is new.
+fn set_tick(
+    recency: &TransactionalTree,
+    key_bytes: &[u8],
+    tick: u64,
+) -> ConflictableTransactionResult<(), ()> {
+    match recency.insert(access_key(key_bytes), &tick.to_be_bytes())? {
+        Some(old_tick) => {
+            recency.remove(tick_key(&old_tick))?;
+        }
+        None => add_to_count(recency, 1)?,
+    }
+    recency.insert(tick_key(&tick.to_be_bytes()), key_bytes)?;
+
+    Ok(())
+}
+
+/// Forgets the accesses to `key_bytes`, and uncounts it.
+fn remove_tick(
+    recency: &TransactionalTree,
+    key_bytes: &[u8],
+) -> ConflictableTransactionResult<(), ()> {
+    if let Some(old_tick) = recency.remove(access_key(key_bytes))? {
+        recency.remove(tick_key(&old_tick))?;
+        add_to_count(recency, -1)?;
+    }
+
+    Ok(())
+}
+
+fn add_to_count(recency: &TransactionalTree, delta: i64) -> ConflictableTransactionResult<(), ()> {
+    let count = recency
+        .get(COUNT_KEY)?
+        .map_or(0, |count| decode_u64(&count));
+    recency.insert(COUNT_KEY, &count.saturating_add_signed(delta).to_be_bytes())?;
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use bincode::{Decode, Encode};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+};
+use sled::IVec;
+
+use crate::bincode_tree::BincodeTree;
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::multi_tree;
+use crate::{error::Error, Db, StrictTree, BINCODE_CONFIG};
+
+/// Name of the tree holding the number of entries and bytes of every capped log,
+/// keyed by log name.
+pub const CAPPED_LOGS_TREE: &str = "__ser_sled_capped_logs";
+
+/// How much a capped log holds, as stored in [`CAPPED_LOGS_TREE`].
+#[derive(Encode, Decode, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Usage {
+    entries: u64,
+    bytes: u64,
+}
+
+/// An append-only log that keeps only its latest entries, opened with
+/// [`Db::open_capped_log`].
+///
+/// Entries are stored under increasing IDs taken from [`Db::generate_id`]. Once an
+/// append takes the log over `max_entries`, or over the limit set with
+/// [`CappedLog::with_max_bytes`], the oldest entries are evicted until it fits again.
+/// The latest entry is never evicted, even if it is larger than the byte limit.
+#[derive(Clone)]
+pub struct CappedLog<V> {
+    db: Db,
+    log: BincodeTree<u64, V>,
+    usage: sled::Tree,
+    max_entries: u64,
+    max_bytes: Option<u64>,
+}
+
+impl<V: Encode + Decode> CappedLog<V> {
+    /// Counts the entries of `log` if it wasn't counted before.
+    pub(crate) fn new(
+        db: Db,
+        log: BincodeTree<u64, V>,
+        usage: sled::Tree,
+        max_entries: u64,
+    ) -> Result<Self, Error> {
+        let capped = Self {
+            db,
+            log,
+            usage,
+            max_entries,
+            max_bytes: None,
+        };
+
+        if capped.usage.get(capped.name())?.is_none() {
+            let mut usage = Usage::default();
+            for entry in capped.sled_log().iter() {
+                usage.entries += 1;
+                usage.bytes += entry?.1.len() as u64;
+            }
+            capped.usage.insert(
+                capped.name(),
+                bincode::encode_to_vec(usage, BINCODE_CONFIG)?,
+            )?;
+        }
+
+        Ok(capped)
+    }
+
+    /// Also evicts the oldest entries while the encoded values take more than
+    /// `max_bytes`.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Appends `value`, evicts the oldest entries if the log is over its limits, and
+    /// returns the ID of the new entry.
+    pub fn append(&self, value: &V) -> Result<u64, Error> {
+        let id = self.db.generate_id()?;
+        let key = self.log.encode_key(&id)?;
+        let value = BincodeCodec::encode(value, BINCODE_CONFIG)?;
+
+        // Never aborts
+        let _ = multi_tree::transaction(&self.trees(), |(log, usage)| {
+            log.insert(key.as_slice(), value.as_slice())?;
+            update_usage(usage, self.name(), |mut current| {
+                current.entries += 1;
+                current.bytes += value.len() as u64;
+                current
+            })
+        })?;
+
+        self.evict()?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: u64) -> Result<Option<V>, Error> {
+        self.log.get(&id)
+    }
+
+    /// Iterates over the entries with their IDs, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u64, V)> + '_ {
+        self.log.iter()
+    }
+
+    /// The `n` latest entries, newest first.
+    pub fn latest(&self, n: usize) -> Vec<(u64, V)> {
+        self.log.iter().rev().take(n).collect()
+    }
+
+    /// Number of entries, read from the stored count.
+    pub fn len(&self) -> Result<u64, Error> {
+        Ok(self.usage()?.entries)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Total size of the encoded entries.
+    pub fn size_in_bytes(&self) -> Result<u64, Error> {
+        Ok(self.usage()?.bytes)
+    }
+
+    /// Removes the oldest entries until the log fits in its limits.
+    fn evict(&self) -> Result<(), Error> {
+        loop {
+            let usage = self.usage()?;
+            let over_bytes = self.max_bytes.is_some_and(|max| usage.bytes > max);
+            if usage.entries <= 1 || (usage.entries <= self.max_entries && !over_bytes) {
+                return Ok(());
+            }
+
+            let Some((oldest, _)) = self.sled_log().first()? else {
+                return Ok(());
+            };
+
+            // Evicted by a concurrent append otherwise, which is fine
+            let _ = multi_tree::transaction(&self.trees(), |(log, usage)| {
+                let Some(value) = log.remove(&oldest)? else {
+                    return Err(ConflictableTransactionError::Abort(()));
+                };
+                update_usage(usage, self.name(), |mut current| {
+                    current.entries = current.entries.saturating_sub(1);
+                    current.bytes = current.bytes.saturating_sub(value.len() as u64);
+                    current
+                })
+            })?;
+        }
+    }
+
+    fn usage(&self) -> Result<Usage, Error> {
+        match self.usage.get(self.name())? {
+            Some(bytes) => Ok(bincode::decode_from_slice(&bytes, BINCODE_CONFIG)?.0),
+            None => Ok(Usage::default()),
+        }
+    }
+
+    fn sled_log(&self) -> &sled::Tree {
+        &self.log.raw().inner_tree
+    }
+
+    fn name(&self) -> IVec {
+        self.sled_log().name()
+    }
+
+    /// The log and usage trees, to run transactions on.
+    fn trees(&self) -> (&sled::Tree, &sled::Tree) {
+        (self.sled_log(), &self.usage)
+    }
+}
+
+fn update_usage<F: Fn(Usage) -> Usage>(
+    usage: &TransactionalTree,
+    name: IVec,
+    f: F,
+) -> ConflictableTransactionResult<(), ()> {
+    let current = match usage.get(&name)? {
+        Some(bytes) => bincode::decode_from_slice(&bytes, BINCODE_CONFIG)
+            .map(|(usage, _)| usage)
+            .unwrap_or_default(),
+        None => Usage::default(),
+    };
+    let updated = bincode::encode_to_vec(f(current), BINCODE_CONFIG).expect("usage always encodes");
+    usage.insert(name, updated)?;
+
+    Ok(())
+}
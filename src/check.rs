@@ -0,0 +1,171 @@
+use crate::{error::Error, Db};
+
+/// Checks a raw entry against the key and value types a tree was opened with.
+pub(crate) type TableValidator = fn(&[u8], &[u8]) -> Result<(), Error>;
+
+/// How much [`Db::check`] reports, and whether it repairs anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportLevel {
+    /// Only count entries and failures.
+    Summary,
+    /// Also list every entry that failed, with its raw bytes.
+    Detailed,
+    /// Like [`ReportLevel::Detailed`], and move every failing entry into
+    /// a sibling `<tree>_quarantine` tree with its raw bytes preserved.
+    Repair,
+}
+
+/// An entry that failed to decode.
+#[derive(Debug, Clone)]
+pub struct BadEntry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub error: String,
+}
+
+/// Result of checking a single tree.
+#[derive(Debug, Clone)]
+pub struct TreeReport {
+    pub name: String,
+    /// `false` if no strict tree was opened under this name, in which case
+    /// entries are counted but cannot be checked.
+    pub checked: bool,
+    pub entries: usize,
+    pub failures: usize,
+    /// Filled in for [`ReportLevel::Detailed`] and [`ReportLevel::Repair`].
+    pub bad_entries: Vec<BadEntry>,
+    pub quarantined: usize,
+}
+
+/// Result of [`Db::check`].
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    pub trees: Vec<TreeReport>,
+}
+
+impl CheckReport {
+    /// Returns `true` if no checked entry failed.
+    pub fn is_clean(&self) -> bool {
+        self.trees.iter().all(|tree| tree.failures == 0)
+    }
+
+    pub fn tree(&self, name: &str) -> Option<&TreeReport> {
+        self.trees.iter().find(|tree| tree.name == name)
+    }
+}
+
+pub(crate) fn quarantine_name(tree_name: &str) -> String {
+    format!("{tree_name}_quarantine")
+}
+
+/// Moves a raw entry into the quarantine tree of `tree`, atomically. Returns `false`
+/// without moving anything if the entry no longer holds `value`.
+pub(crate) fn quarantine(
+    tree: &sled::Tree,
+    quarantine: &sled::Tree,
+    key: &[u8],
+    value: &[u8],
+) -> Result<bool, Error> {
+    use sled::transaction::{TransactionError, Transactional};
+
+    (tree, quarantine)
+        .transaction(|(tree, quarantine)| {
+            if tree.get(key)?.as_deref() != Some(value) {
+                return Ok(false);
+            }
+
+            quarantine.insert(key, value)?;
+            tree.remove(key)?;
+            Ok(true)
+        })
+        .map_err(|e: TransactionError<()>| match e {
+            TransactionError::Storage(e) => Error::SledError(e),
+            TransactionError::Abort(()) => Error::IllegalOperation,
+        })
+}
+
+/// Moves the bad entries of `report` into the quarantine tree of `tree`.
+pub(crate) fn quarantine_bad_entries(
+    db: &Db,
+    tree: &sled::Tree,
+    report: &mut TreeReport,
+) -> Result<(), Error> {
+    if report.bad_entries.is_empty() {
+        return Ok(());
+    }
+
+    let quarantine_tree = db.inner_db.open_tree(quarantine_name(&report.name))?;
+
+    for entry in &report.bad_entries {
+        if quarantine(tree, &quarantine_tree, &entry.key, &entry.value)? {
+            report.quarantined += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks every entry of `tree` with `validator`, or only counts them without one.
+pub(crate) fn check_tree<F: Fn(&[u8], &[u8]) -> Result<(), Error>>(
+    tree: &sled::Tree,
+    validator: Option<F>,
+    level: ReportLevel,
+) -> Result<TreeReport, Error> {
+    let mut report = TreeReport {
+        name: String::from_utf8_lossy(&tree.name()).into_owned(),
+        checked: validator.is_some(),
+        entries: 0,
+        failures: 0,
+        bad_entries: Vec::new(),
+        quarantined: 0,
+    };
+
+    for res in tree.iter() {
+        let (key, value) = res?;
+        report.entries += 1;
+
+        let Some(validator) = &validator else {
+            continue;
+        };
+
+        if let Err(e) = validator(&key, &value) {
+            report.failures += 1;
+
+            if level != ReportLevel::Summary {
+                report.bad_entries.push(BadEntry {
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+pub(crate) fn check(db: &Db, level: ReportLevel) -> Result<CheckReport, Error> {
+    let mut trees = Vec::new();
+
+    for name in db.inner_db.tree_names() {
+        let name = String::from_utf8_lossy(&name).into_owned();
+        let validator = db.table_validator(&name);
+        let tree = db.inner_db.open_tree(&name)?;
+
+        let mut report = check_tree(
+            &tree,
+            validator
+                .as_ref()
+                .map(|v| v as &dyn Fn(&[u8], &[u8]) -> Result<(), Error>),
+            level,
+        )?;
+
+        if level == ReportLevel::Repair {
+            quarantine_bad_entries(db, &tree, &mut report)?;
+        }
+
+        trees.push(report);
+    }
+
+    Ok(CheckReport { trees })
+}
@@ -0,0 +1,411 @@
+//! Value encodings for strict trees.
+//!
+//! A [`ValueCodec`] turns values into bytes and back. [`BincodeCodec`] and [`SerdeCodec`]
+//! back [`crate::bincode_tree::BincodeTree`] and [`crate::serde_tree::SerdeTree`]. Other
+//! formats are available behind features, and any format can be plugged into
+//! [`crate::tree::Tree`] by implementing the trait.
+
+use bincode::config::Config;
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+use std::marker::PhantomData;
+
+use crate::error::Error;
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Decodes a value with the tree's configuration, rejecting trailing bytes if the flag is set.
+pub(crate) type Decoder<T, C> = fn(&[u8], C, bool) -> Result<T, Error>;
+/// Encodes a value with the tree's configuration.
+pub(crate) type Encoder<T, C> = fn(&T, C) -> Result<Vec<u8>, Error>;
+
+/// How a strict tree turns its values into bytes and back. Codecs that don't use
+/// bincode can ignore the configuration.
+pub trait ValueCodec<T> {
+    fn encode<C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error>;
+    fn decode<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<T, Error>;
+}
+
+/// Encodes values implementing `bincode::Encode` and `bincode::Decode`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeCodec;
+
+/// Encodes values implementing `serde::Serialize` and `serde::Deserialize` with bincode.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeCodec;
+
+/// Encodes values implementing `serde::Serialize` and `serde::Deserialize` as MessagePack.
+/// Structs are encoded as maps keyed by field name, which is what most other
+/// MessagePack implementations expect.
+#[cfg(feature = "messagepack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+/// Encodes values implementing `serde::Serialize` and `serde::Deserialize` as CBOR.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+/// Encodes values implementing `serde::Serialize` and `serde::Deserialize` with postcard,
+/// which is compact and readable from `no_std` crates.
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostcardCodec;
+
+/// Encodes values implementing `BorshSerialize` and `BorshDeserialize` with borsh.
+#[cfg(feature = "borsh")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BorshCodec;
+
+/// Encodes protobuf messages with prost. Protobuf messages aren't delimited, so
+/// trailing bytes can't be detected and strict decoding has no effect.
+#[cfg(feature = "prost")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProtobufCodec;
+
+/// Wraps the codec `Inner`, compressing encoded values of at least `THRESHOLD` bytes
+/// with `A`. Every value starts with a header byte telling how it is compressed, so the
+/// threshold and algorithm can be changed without rewriting the tree, as long as the
+/// features of the algorithms used before are still enabled.
+///
+/// Values decompressing into more than the tree's limit (see `with_limit`), or
+/// [`MAX_DECOMPRESSED_LEN`] bytes without one, return [`Error::LimitExceeded`].
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressedWith<A, Inner, const THRESHOLD: usize = 256> {
+    codecs: PhantomData<(A, Inner)>,
+}
+
+/// How many bytes [`CompressedWith`] decompresses a value into at most, for trees
+/// without a limit.
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Compresses values of at least `THRESHOLD` bytes with zstd. See [`CompressedWith`].
+#[cfg(feature = "zstd")]
+pub type Compressed<Inner, const THRESHOLD: usize = 256> = CompressedWith<Zstd, Inner, THRESHOLD>;
+
+/// Compresses values of at least `THRESHOLD` bytes with lz4, which is faster than
+/// zstd but compresses less. See [`CompressedWith`].
+#[cfg(feature = "lz4")]
+pub type Lz4Compressed<Inner, const THRESHOLD: usize = 256> = CompressedWith<Lz4, Inner, THRESHOLD>;
+
+/// A compression algorithm for [`CompressedWith`].
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+pub trait Compression {
+    /// Written before every value compressed with this algorithm.
+    const HEADER: u8;
+
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// zstd at its default level.
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zstd;
+
+/// lz4 block compression.
+#[cfg(feature = "lz4")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lz4;
+
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+const UNCOMPRESSED: u8 = 0;
+#[cfg(feature = "zstd")]
+const ZSTD: u8 = 1;
+#[cfg(feature = "lz4")]
+const LZ4: u8 = 2;
+
+#[cfg(feature = "zstd")]
+impl Compression for Zstd {
+    const HEADER: u8 = ZSTD;
+
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        zstd::bulk::compress(bytes, zstd::DEFAULT_COMPRESSION_LEVEL)
+            .map_err(Error::CompressionError)
+    }
+}
+
+#[cfg(feature = "lz4")]
+impl Compression for Lz4 {
+    const HEADER: u8 = LZ4;
+
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(lz4_flex::compress_prepend_size(bytes))
+    }
+}
+
+/// Wraps the codec `Inner`, appending a CRC32 of the encoded value that is checked
+/// on every read. A mismatch returns [`Error::ChecksumMismatch`].
+#[cfg(feature = "checksum")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksummed<Inner> {
+    inner: std::marker::PhantomData<Inner>,
+}
+
+#[cfg(feature = "checksum")]
+const CHECKSUM_LEN: usize = 4;
+
+impl<T: Encode + Decode> ValueCodec<T> for BincodeCodec {
+    fn encode<C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error> {
+        bincode_encode(value, config)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<T, Error> {
+        bincode_decode(bytes, config, strict)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize + DeserializeOwned> ValueCodec<T> for SerdeCodec {
+    fn encode<C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error> {
+        serde_encode(value, config)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<T, Error> {
+        serde_decode(bytes, config, strict)
+    }
+}
+
+#[cfg(feature = "messagepack")]
+impl<T: Serialize + DeserializeOwned> ValueCodec<T> for MessagePackCodec {
+    fn encode<C: Config>(value: &T, _config: C) -> Result<Vec<u8>, Error> {
+        Ok(rmp_serde::to_vec_named(value)?)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<T, Error> {
+        let mut deserializer = rmp_serde::Deserializer::new(bytes);
+        let value = T::deserialize(&mut deserializer)?;
+        check_trailing(bytes.len() - deserializer.get_ref().len(), bytes, strict)?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<T: Serialize + DeserializeOwned> ValueCodec<T> for CborCodec {
+    fn encode<C: Config>(value: &T, _config: C) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<T, Error> {
+        let mut rest = bytes;
+        let value = ciborium::from_reader(&mut rest)?;
+        check_trailing(bytes.len() - rest.len(), bytes, strict)?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<T: Serialize + DeserializeOwned> ValueCodec<T> for PostcardCodec {
+    fn encode<C: Config>(value: &T, _config: C) -> Result<Vec<u8>, Error> {
+        Ok(postcard::to_allocvec(value)?)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<T, Error> {
+        let (value, rest) = postcard::take_from_bytes(bytes)?;
+        check_trailing(bytes.len() - rest.len(), bytes, strict)?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: BorshSerialize + BorshDeserialize> ValueCodec<T> for BorshCodec {
+    fn encode<C: Config>(value: &T, _config: C) -> Result<Vec<u8>, Error> {
+        borsh_encode(value)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<T, Error> {
+        borsh_decode(bytes, strict)
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<T: prost::Message + Default> ValueCodec<T> for ProtobufCodec {
+    fn encode<C: Config>(value: &T, _config: C) -> Result<Vec<u8>, Error> {
+        Ok(value.encode_to_vec())
+    }
+
+    fn decode<C: Config>(bytes: &[u8], _config: C, _strict: bool) -> Result<T, Error> {
+        Ok(T::decode(bytes)?)
+    }
+}
+
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+impl<T, A: Compression, Inner: ValueCodec<T>, const THRESHOLD: usize> ValueCodec<T>
+    for CompressedWith<A, Inner, THRESHOLD>
+{
+    fn encode<C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error> {
+        let encoded = Inner::encode(value, config)?;
+
+        if encoded.len() >= THRESHOLD {
+            let compressed = A::compress(&encoded)?;
+
+            // Incompressible values are stored as they are
+            if compressed.len() < encoded.len() {
+                return Ok([&[A::HEADER], compressed.as_slice()].concat());
+            }
+        }
+
+        Ok([&[UNCOMPRESSED], encoded.as_slice()].concat())
+    }
+
+    fn decode<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<T, Error> {
+        match bytes.split_first() {
+            Some((&UNCOMPRESSED, encoded)) => Inner::decode(encoded, config, strict),
+            Some((&header, compressed)) => {
+                let max_len = C::LIMIT.unwrap_or(MAX_DECOMPRESSED_LEN);
+                Inner::decode(&decompress(header, compressed, max_len)?, config, strict)
+            }
+            None => Err(compression_error("missing compression header")),
+        }
+    }
+}
+
+/// Decompresses a value compressed with the algorithm identified by `header`,
+/// failing with [`Error::LimitExceeded`] rather than producing more than `max_len`
+/// bytes.
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+fn decompress(header: u8, compressed: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+    match header {
+        #[cfg(feature = "zstd")]
+        ZSTD => {
+            use std::io::Read;
+
+            let mut decompressed = Vec::new();
+            zstd::stream::Decoder::new(compressed)
+                .and_then(|decoder| {
+                    decoder
+                        .take(max_len as u64 + 1)
+                        .read_to_end(&mut decompressed)
+                })
+                .map_err(Error::CompressionError)?;
+
+            if decompressed.len() > max_len {
+                return Err(Error::LimitExceeded);
+            }
+
+            Ok(decompressed)
+        }
+        #[cfg(feature = "lz4")]
+        LZ4 => {
+            // The size is prepended, so it is checked before allocating anything
+            let (len, compressed) =
+                lz4_flex::block::uncompressed_size(compressed).map_err(lz4_error)?;
+            if len > max_len {
+                return Err(Error::LimitExceeded);
+            }
+
+            lz4_flex::block::decompress(compressed, len).map_err(lz4_error)
+        }
+        _ => Err(compression_error("unknown compression header")),
+    }
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_error(error: lz4_flex::block::DecompressError) -> Error {
+    Error::CompressionError(std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+fn compression_error(message: &'static str) -> Error {
+    Error::CompressionError(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message,
+    ))
+}
+
+#[cfg(feature = "checksum")]
+impl<T, Inner: ValueCodec<T>> ValueCodec<T> for Checksummed<Inner> {
+    fn encode<C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error> {
+        let mut bytes = Inner::encode(value, config)?;
+        let checksum = crc32fast::hash(&bytes);
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+
+        Ok(bytes)
+    }
+
+    fn decode<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<T, Error> {
+        // The key is added by the tree, which knows it
+        let mismatch = || Error::ChecksumMismatch {
+            key_bytes: Vec::new(),
+        };
+
+        let split = bytes.len().checked_sub(CHECKSUM_LEN).ok_or_else(mismatch)?;
+        let (encoded, checksum) = bytes.split_at(split);
+        if crc32fast::hash(encoded).to_be_bytes() != checksum {
+            return Err(mismatch());
+        }
+
+        Inner::decode(encoded, config, strict)
+    }
+}
+
+/// Returns [`Error::TrailingBytes`] if `strict` is set and only `size` of the bytes were decoded.
+pub(crate) fn check_trailing(size: usize, bytes: &[u8], strict: bool) -> Result<(), Error> {
+    if strict && size != bytes.len() {
+        return Err(Error::TrailingBytes {
+            decoded: size,
+            len: bytes.len(),
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn bincode_encode<T: Encode, C: Config>(value: &T, config: C) -> Result<Vec<u8>, Error> {
+    Ok(bincode::encode_to_vec(value, config)?)
+}
+
+pub(crate) fn bincode_decode<T: Decode, C: Config>(
+    bytes: &[u8],
+    config: C,
+    strict: bool,
+) -> Result<T, Error> {
+    let (value, size) = bincode::decode_from_slice::<T, _>(bytes, config)?;
+    check_trailing(size, bytes, strict)?;
+
+    Ok(value)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn serde_encode<T: Serialize, C: Config>(
+    value: &T,
+    config: C,
+) -> Result<Vec<u8>, Error> {
+    Ok(bincode::serde::encode_to_vec(value, config)?)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn serde_decode<T: DeserializeOwned, C: Config>(
+    bytes: &[u8],
+    config: C,
+    strict: bool,
+) -> Result<T, Error> {
+    let (value, size) = bincode::serde::decode_from_slice::<T, _>(bytes, config)?;
+    check_trailing(size, bytes, strict)?;
+
+    Ok(value)
+}
+
+#[cfg(feature = "borsh")]
+pub(crate) fn borsh_encode<T: BorshSerialize>(value: &T) -> Result<Vec<u8>, Error> {
+    borsh::to_vec(value).map_err(Error::BorshError)
+}
+
+#[cfg(feature = "borsh")]
+pub(crate) fn borsh_decode<T: BorshDeserialize>(bytes: &[u8], strict: bool) -> Result<T, Error> {
+    let mut rest = bytes;
+    let value = T::deserialize(&mut rest).map_err(Error::BorshError)?;
+    check_trailing(bytes.len() - rest.len(), bytes, strict)?;
+
+    Ok(value)
+}
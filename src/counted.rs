@@ -0,0 +1,120 @@
+//! Constant-time `len` for large trees, see [`Tree::counted`].
+
+use bincode::config::Config;
+use sled::transaction::ConflictableTransactionError;
+use sled::IVec;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::multi_tree::{self, decode_u64};
+use crate::tree::Tree;
+use crate::{error::Error, DefaultConfig};
+
+/// Name of the tree holding the entry count of every counted tree, keyed by tree name.
+pub const COUNTS_TREE: &str = "__ser_sled_counts";
+
+/// A strict tree that keeps count of its entries, created with [`Tree::counted`].
+/// `insert` and `remove` update the count in the same transaction as the entry.
+/// Writes made through other handles to the same tree aren't counted: use
+/// [`CountedTree::recount`] after them.
+#[derive(Clone)]
+pub struct CountedTree<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    tree: Tree<K, V, VC, C, KC>,
+    counts: sled::Tree,
+    name: IVec,
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> CountedTree<K, V, VC, C, KC> {
+    /// Counts the entries of `tree` if it wasn't counted before. Fails with
+    /// [`Error::IllegalOperation`] if `tree` has a quota, as the count and the quota
+    /// can't be updated in the same transaction.
+    pub(crate) fn new(tree: Tree<K, V, VC, C, KC>, counts: sled::Tree) -> Result<Self, Error> {
+        if tree.raw().quota.is_some() {
+            return Err(Error::IllegalOperation);
+        }
+
+        let name = tree.raw().inner_tree.name();
+        let counted = Self { tree, counts, name };
+
+        if counted.counts.get(&counted.name)?.is_none() {
+            counted.recount()?;
+        }
+
+        Ok(counted)
+    }
+
+    /// The counted tree, to read from it. Writes through it aren't counted.
+    pub fn tree(&self) -> &Tree<K, V, VC, C, KC> {
+        &self.tree
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        self.tree.check_key(key)?;
+        let key = self.tree.encode_key(key)?;
+        let value = VC::encode(value, self.tree.raw().config)?;
+
+        let old = self.apply(&key, Some(&value))?;
+        self.decode_old(&key, old)
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key = self.tree.encode_key(key)?;
+
+        let old = self.apply(&key, None)?;
+        self.decode_old(&key, old)
+    }
+
+    /// Number of entries, read from the stored count. Unlike [`crate::StrictTree::len`],
+    /// this reads another tree, so it can fail.
+    pub fn len(&self) -> Result<u64, Error> {
+        Ok(self
+            .counts
+            .get(&self.name)?
+            .map_or(0, |count| decode_u64(&count)))
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Counts the entries again, in O(n), and stores the result.
+    pub fn recount(&self) -> Result<u64, Error> {
+        let count = self.tree.raw().inner_tree.len() as u64;
+        self.counts.insert(&self.name, &count.to_be_bytes())?;
+
+        Ok(count)
+    }
+
+    /// Inserts `value` under `key`, or removes `key` if `value` is `None`, and updates
+    /// the count atomically. Returns the value it replaced.
+    fn apply(&self, key: &[u8], value: Option<&[u8]>) -> Result<Option<IVec>, Error> {
+        let old = multi_tree::transaction(
+            &(&self.tree.raw().inner_tree, &self.counts),
+            |(tree, counts)| {
+                let old = match value {
+                    Some(value) => tree.insert(key, value)?,
+                    None => tree.remove(key)?,
+                };
+
+                let count = counts
+                    .get(&self.name)?
+                    .map_or(0, |count| decode_u64(&count));
+                let new_count = match (&old, value) {
+                    (None, Some(_)) => count + 1,
+                    (Some(_), None) => count.saturating_sub(1),
+                    _ => return Ok(old),
+                };
+                counts.insert(&self.name, &new_count.to_be_bytes())?;
+
+                Ok::<_, ConflictableTransactionError<()>>(old)
+            },
+        )?;
+
+        Ok(old.ok().flatten())
+    }
+
+    fn decode_old(&self, key: &[u8], old: Option<IVec>) -> Result<Option<V>, Error> {
+        old.map(|old| self.tree.raw().decode_stored(VC::decode::<C>, key, &old))
+            .transpose()
+    }
+}
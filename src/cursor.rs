@@ -0,0 +1,73 @@
+//! Iterators that can jump forward, for merge joins over sorted trees.
+
+use bincode::config::Config;
+use std::marker::PhantomData;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::tree::RawTree;
+use crate::{error::Error, BytesRange, DefaultConfig};
+
+/// An iterator over the entries of a range of a strict tree, in key order, that can
+/// be repositioned with [`Cursor::seek`]. Created with [`crate::tree::Tree::cursor`]
+/// and [`crate::tree::Tree::cursor_range`].
+pub struct Cursor<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    raw: RawTree<C>,
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    iter: sled::Iter,
+    types: PhantomData<fn() -> (K, V)>,
+    codecs: PhantomData<fn() -> (VC, KC)>,
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> Cursor<K, V, VC, C, KC> {
+    pub(crate) fn new(raw: RawTree<C>, (start, end): BytesRange) -> Self {
+        let iter = raw.inner_tree.range((start.clone(), end.clone()));
+
+        Self {
+            raw,
+            start,
+            end,
+            iter,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+
+    /// Moves the cursor to the first key of its range that is greater than or equal
+    /// to `key`. Seeking to a key before the current position goes back, and seeking
+    /// before the start of the range goes back to its start.
+    pub fn seek(&mut self, key: &K) -> Result<(), Error> {
+        let key_bytes = KC::encode_key(key, self.raw.config)?;
+
+        let after_start = match &self.start {
+            Included(start) => key_bytes >= *start,
+            Excluded(start) => key_bytes > *start,
+            Unbounded => true,
+        };
+        let start = if after_start {
+            Included(key_bytes)
+        } else {
+            self.start.clone()
+        };
+
+        self.iter = self.raw.inner_tree.range((start, self.end.clone()));
+        Ok(())
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> Iterator for Cursor<K, V, VC, C, KC> {
+    type Item = Result<(K, V), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.iter.next()? {
+            Ok(entry) => entry,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        self.raw
+            .decode_entry(Some(entry), KC::decode_key::<C>, VC::decode::<C>)
+            .transpose()
+    }
+}
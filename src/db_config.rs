@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::{error::Error, Db};
+
+/// Builder for a [`Db`], mirroring the most used settings of `sled::Config`.
+///
+/// ```ignore
+/// let db = ser_sled::db_config::DbConfig::new()
+///     .path("data")
+///     .cache_capacity(64 * 1024 * 1024)
+///     .flush_every_ms(Some(1000))
+///     .open()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DbConfig {
+    inner: sled::Config,
+}
+
+impl DbConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory the database is stored in.
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.inner = self.inner.path(path);
+        self
+    }
+
+    /// Deletes the database when it is dropped. Without a path, it is created in a
+    /// temporary directory.
+    pub fn temporary(mut self, temporary: bool) -> Self {
+        self.inner = self.inner.temporary(temporary);
+        self
+    }
+
+    /// Maximum size of the page cache, in bytes.
+    pub fn cache_capacity(mut self, bytes: u64) -> Self {
+        self.inner = self.inner.cache_capacity(bytes);
+        self
+    }
+
+    /// Compresses data on disk with zstd. This needs sled's `compression` feature,
+    /// which this crate doesn't enable: without it, [`DbConfig::open`] fails.
+    pub fn use_compression(mut self, compression: bool) -> Self {
+        self.inner = self.inner.use_compression(compression);
+        self
+    }
+
+    /// zstd compression level used with [`DbConfig::use_compression`], from 1 to 22.
+    pub fn compression_factor(mut self, factor: i32) -> Self {
+        self.inner = self.inner.compression_factor(factor);
+        self
+    }
+
+    /// How often dirty data is flushed to disk in the background, or never with `None`.
+    pub fn flush_every_ms(mut self, every_ms: Option<u64>) -> Self {
+        self.inner = self.inner.flush_every_ms(every_ms);
+        self
+    }
+
+    pub fn open(&self) -> Result<Db, Error> {
+        Ok(self.inner.open()?.into())
+    }
+}
+
+impl From<sled::Config> for DbConfig {
+    fn from(inner: sled::Config) -> Self {
+        Self { inner }
+    }
+}
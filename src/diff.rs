@@ -0,0 +1,141 @@
+use bincode::config::Config;
+use sled::IVec;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+use crate::codec::{Decoder, ValueCodec};
+use crate::error::Error;
+use crate::keys::KeyCodec;
+use crate::tree::{RawTree, Tree};
+
+/// A difference between two trees, yielded by [`diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEntry<K, V> {
+    /// The key is only in the left tree.
+    OnlyLeft(K, V),
+    /// The key is only in the right tree.
+    OnlyRight(K, V),
+    /// The key is in both trees with different values.
+    Different { key: K, left: V, right: V },
+}
+
+/// Picks the value to keep for a key, given the local value and the remote value.
+pub type Resolver<K, V> = Box<dyn Fn(&K, V, V) -> V>;
+
+/// How `sync_from` resolves a key whose value differs between the two trees.
+pub enum ConflictPolicy<K, V> {
+    /// Keeps the value of the tree being synced.
+    KeepLocal,
+    /// Takes the value of the tree synced from.
+    TakeRemote,
+    /// Stores the value returned by the resolver.
+    Resolve(Resolver<K, V>),
+}
+
+/// Walks both trees in key order and yields the entries that differ between them.
+/// Values are compared once decoded, so the trees may use different value codecs,
+/// but they must encode keys the same way. Stops at the first entry that fails to
+/// decode.
+pub fn diff<'a, K, V, VC1, C1, VC2, C2, KC>(
+    left: &'a Tree<K, V, VC1, C1, KC>,
+    right: &'a Tree<K, V, VC2, C2, KC>,
+) -> impl Iterator<Item = Result<DiffEntry<K, V>, Error>> + 'a
+where
+    V: PartialEq,
+    VC1: ValueCodec<V>,
+    C1: Config,
+    VC2: ValueCodec<V>,
+    C2: Config,
+    KC: KeyCodec<K>,
+{
+    Diff {
+        left: left.raw(),
+        right: right.raw(),
+        left_iter: left.raw().inner_tree.iter().peekable(),
+        right_iter: right.raw().inner_tree.iter().peekable(),
+        decode_key: KC::decode_key::<C1>,
+        decode_left: VC1::decode::<C1>,
+        decode_right: VC2::decode::<C2>,
+        failed: false,
+    }
+}
+
+struct Diff<'a, K, V, C1, C2> {
+    left: &'a RawTree<C1>,
+    right: &'a RawTree<C2>,
+    left_iter: Peekable<sled::Iter>,
+    right_iter: Peekable<sled::Iter>,
+    decode_key: Decoder<K, C1>,
+    decode_left: Decoder<V, C1>,
+    decode_right: Decoder<V, C2>,
+    failed: bool,
+}
+
+impl<K, V: PartialEq, C1: Config, C2: Config> Diff<'_, K, V, C1, C2> {
+    fn next_entry(&mut self) -> Result<Option<DiffEntry<K, V>>, Error> {
+        loop {
+            let order = match (self.left_iter.peek(), self.right_iter.peek()) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(Ok((left, _))), Some(Ok((right, _)))) => left.cmp(right),
+                // Let `next` return the error
+                (Some(Err(_)), _) => Ordering::Less,
+                (_, Some(Err(_))) => Ordering::Greater,
+            };
+
+            match order {
+                Ordering::Less => {
+                    let (key, value) = next(&mut self.left_iter)?;
+                    return Ok(Some(DiffEntry::OnlyLeft(
+                        self.decode_key(&key)?,
+                        self.left.decode_stored(self.decode_left, &key, &value)?,
+                    )));
+                }
+                Ordering::Greater => {
+                    let (key, value) = next(&mut self.right_iter)?;
+                    return Ok(Some(DiffEntry::OnlyRight(
+                        self.decode_key(&key)?,
+                        self.right.decode_stored(self.decode_right, &key, &value)?,
+                    )));
+                }
+                Ordering::Equal => {
+                    let (key, left) = next(&mut self.left_iter)?;
+                    let (_, right) = next(&mut self.right_iter)?;
+
+                    let left = self.left.decode_stored(self.decode_left, &key, &left)?;
+                    let right = self.right.decode_stored(self.decode_right, &key, &right)?;
+                    if left != right {
+                        return Ok(Some(DiffEntry::Different {
+                            key: self.decode_key(&key)?,
+                            left,
+                            right,
+                        }));
+                    }
+                }
+            }
+        }
+    }
+
+    fn decode_key(&self, bytes: &[u8]) -> Result<K, Error> {
+        (self.decode_key)(bytes, self.left.config, self.left.strict_decode)
+    }
+}
+
+fn next(iter: &mut Peekable<sled::Iter>) -> Result<(IVec, IVec), Error> {
+    Ok(iter.next().expect("the iterator was peeked")?)
+}
+
+impl<K, V: PartialEq, C1: Config, C2: Config> Iterator for Diff<'_, K, V, C1, C2> {
+    type Item = Result<DiffEntry<K, V>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let res = self.next_entry().transpose();
+        self.failed = matches!(res, Some(Err(_)));
+        res
+    }
+}
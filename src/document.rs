@@ -0,0 +1,187 @@
+use bincode::{Decode, Encode};
+use std::cell::Cell;
+use std::ops::RangeBounds;
+
+use crate::index::{Index, IndexedTree};
+use crate::keys::OrderedKey;
+use crate::{error::Error, Db, StrictTree};
+
+/// The ID of a document, taken from [`Db::generate_id`].
+pub type Id = u64;
+
+/// A document as stored, with the version it got from its last write.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Envelope<V> {
+    version: u64,
+    doc: V,
+}
+
+/// A document read from a [`DocumentStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document<V> {
+    pub id: Id,
+    /// Starts at `1` and increases with every update, so that writers can check that
+    /// the document didn't change since they read it.
+    pub version: u64,
+    pub doc: V,
+}
+
+impl<V> From<(Id, Envelope<V>)> for Document<V> {
+    fn from((id, envelope): (Id, Envelope<V>)) -> Self {
+        Self {
+            id,
+            version: envelope.version,
+            doc: envelope.doc,
+        }
+    }
+}
+
+/// A secondary index of a [`DocumentStore`], returned by [`DocumentStore::add_index`].
+pub struct DocumentIndex<V, I> {
+    index: Index<Id, Envelope<V>, I>,
+}
+
+impl<V, I> Clone for DocumentIndex<V, I> {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index.clone(),
+        }
+    }
+}
+
+/// A collection of documents with IDs taken from [`Db::generate_id`], opened with
+/// [`Db::open_document_store`].
+///
+/// Documents are stored in an [`IndexedTree`] along with their version, so they can be
+/// looked up by the fields indexed with [`DocumentStore::add_index`], and updated only
+/// if they didn't change with [`DocumentStore::update_if_version`].
+#[derive(Clone)]
+pub struct DocumentStore<V> {
+    db: Db,
+    docs: IndexedTree<Id, Envelope<V>>,
+}
+
+impl<V: Encode + Decode + Clone> DocumentStore<V> {
+    pub(crate) fn new(db: Db, docs: IndexedTree<Id, Envelope<V>>) -> Self {
+        Self { db, docs }
+    }
+
+    /// Indexes the documents by the value `extract` returns for them. See
+    /// [`IndexedTree::add_index`].
+    pub fn add_index<I, F>(
+        &mut self,
+        index_name: &str,
+        extract: F,
+    ) -> Result<DocumentIndex<V, I>, Error>
+    where
+        I: OrderedKey,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        let index = self
+            .docs
+            .add_index(index_name, move |envelope: &Envelope<V>| {
+                extract(&envelope.doc)
+            })?;
+
+        Ok(DocumentIndex { index })
+    }
+
+    /// Stores a new document, and returns its ID.
+    pub fn insert(&self, doc: &V) -> Result<Id, Error> {
+        let id = self.db.generate_id()?;
+        self.docs.insert(
+            &id,
+            &Envelope {
+                version: 1,
+                doc: doc.clone(),
+            },
+        )?;
+
+        Ok(id)
+    }
+
+    pub fn get(&self, id: Id) -> Result<Option<V>, Error> {
+        Ok(self.docs.get(&id)?.map(|envelope| envelope.doc))
+    }
+
+    /// The document `id` along with its version.
+    pub fn get_document(&self, id: Id) -> Result<Option<Document<V>>, Error> {
+        Ok(self.docs.get(&id)?.map(|envelope| (id, envelope).into()))
+    }
+
+    /// Replaces the document `id`, and returns its new version. Returns `None` if there
+    /// is no such document.
+    pub fn update(&self, id: Id, doc: &V) -> Result<Option<u64>, Error> {
+        let envelope = self.docs.update_and_fetch(&id, |envelope| {
+            envelope.map(|envelope| Envelope {
+                version: envelope.version + 1,
+                doc: doc.clone(),
+            })
+        })?;
+
+        Ok(envelope.map(|envelope| envelope.version))
+    }
+
+    /// Replaces the document `id` if its version is still `version`. Returns whether it
+    /// was replaced.
+    pub fn update_if_version(&self, id: Id, version: u64, doc: &V) -> Result<bool, Error> {
+        let replaced = Cell::new(false);
+
+        self.docs.update_and_fetch(&id, |envelope| {
+            replaced.set(false);
+            match envelope {
+                Some(envelope) if envelope.version == version => {
+                    replaced.set(true);
+                    Some(Envelope {
+                        version: version + 1,
+                        doc: doc.clone(),
+                    })
+                }
+                envelope => envelope,
+            }
+        })?;
+
+        Ok(replaced.get())
+    }
+
+    /// Removes the document `id`, and returns it.
+    pub fn remove(&self, id: Id) -> Result<Option<V>, Error> {
+        Ok(self.docs.remove(&id)?.map(|envelope| envelope.doc))
+    }
+
+    /// The documents whose value indexed by `index` is `value`, by ID.
+    pub fn find_by<I: OrderedKey>(
+        &self,
+        index: &DocumentIndex<V, I>,
+        value: &I,
+    ) -> Result<Vec<Document<V>>, Error> {
+        self.find_range(index, value..=value)
+    }
+
+    /// The documents whose value indexed by `index` is in `range`, ordered by indexed
+    /// value then ID.
+    pub fn find_range<I: OrderedKey, R: RangeBounds<I>>(
+        &self,
+        index: &DocumentIndex<V, I>,
+        range: R,
+    ) -> Result<Vec<Document<V>>, Error> {
+        index
+            .index
+            .range(range)
+            .map(|entry| entry.map(Document::from))
+            .collect()
+    }
+
+    /// Iterates over the documents by ID, skipping those that fail to decode.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Document<V>> + '_ {
+        self.docs.tree().iter().map(Document::from)
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+}
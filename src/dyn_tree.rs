@@ -0,0 +1,117 @@
+//! An object-safe version of [`StrictTree`].
+//!
+//! [`StrictTree`] returns `impl Trait` iterators and has generic methods, so it can't
+//! be made into a trait object. [`DynTree`] has the same operations with boxed
+//! iterators and ranges of owned bounds, and is implemented for every [`StrictTree`]
+//! of owned keys and values, so trees of different types can be stored together,
+//! e.g. as `Box<dyn DynTree<K, V> + Send + Sync>` in a registry.
+
+use std::ops::Bound;
+
+use crate::{error::Error, StrictTree};
+
+/// A boxed iterator over the entries of a [`DynTree`].
+pub type DynIter<'a, T> = Box<dyn DoubleEndedIterator<Item = T> + 'a>;
+
+/// The object-safe companion of [`StrictTree`], see the [module docs](self).
+pub trait DynTree<K, V> {
+    fn get(&self, key: &K) -> Result<Option<V>, Error>;
+    fn get_or_init(
+        &self,
+        key: K,
+        init_func: Box<dyn FnOnce() -> V + '_>,
+    ) -> Result<Option<V>, Error>;
+    fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error>;
+    fn first(&self) -> Result<Option<(K, V)>, Error>;
+    fn last(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_max(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_min(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_n_min(&self, n: usize) -> Result<Vec<(K, V)>, Error>;
+    fn pop_n_max(&self, n: usize) -> Result<Vec<(K, V)>, Error>;
+    fn iter(&self) -> DynIter<'_, (K, V)>;
+    fn range_key_bytes(&self, range: (Bound<Vec<u8>>, Bound<Vec<u8>>))
+        -> DynIter<'_, (Vec<u8>, V)>;
+    fn range(&self, range: (Bound<K>, Bound<K>)) -> Result<DynIter<'_, (K, V)>, Error>;
+    fn clear(&self) -> Result<(), Error>;
+    fn contains_key(&self, key: &K) -> Result<bool, Error>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    fn remove(&self, key: &K) -> Result<Option<V>, Error>;
+}
+
+impl<K: 'static, V: 'static, T: StrictTree<K, V>> DynTree<K, V> for T {
+    fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        StrictTree::get(self, key)
+    }
+
+    fn get_or_init(
+        &self,
+        key: K,
+        init_func: Box<dyn FnOnce() -> V + '_>,
+    ) -> Result<Option<V>, Error> {
+        StrictTree::get_or_init(self, key, init_func)
+    }
+
+    fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        StrictTree::insert(self, key, value)
+    }
+
+    fn first(&self) -> Result<Option<(K, V)>, Error> {
+        StrictTree::first(self)
+    }
+
+    fn last(&self) -> Result<Option<(K, V)>, Error> {
+        StrictTree::last(self)
+    }
+
+    fn pop_max(&self) -> Result<Option<(K, V)>, Error> {
+        StrictTree::pop_max(self)
+    }
+
+    fn pop_min(&self) -> Result<Option<(K, V)>, Error> {
+        StrictTree::pop_min(self)
+    }
+
+    fn pop_n_min(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        StrictTree::pop_n_min(self, n)
+    }
+
+    fn pop_n_max(&self, n: usize) -> Result<Vec<(K, V)>, Error> {
+        StrictTree::pop_n_max(self, n)
+    }
+
+    fn iter(&self) -> DynIter<'_, (K, V)> {
+        Box::new(StrictTree::iter(self))
+    }
+
+    fn range_key_bytes(
+        &self,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ) -> DynIter<'_, (Vec<u8>, V)> {
+        Box::new(StrictTree::range_key_bytes(self, range))
+    }
+
+    fn range(&self, range: (Bound<K>, Bound<K>)) -> Result<DynIter<'_, (K, V)>, Error> {
+        Ok(Box::new(StrictTree::range(self, range)?))
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        StrictTree::clear(self)
+    }
+
+    fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        StrictTree::contains_key(self, key)
+    }
+
+    fn len(&self) -> usize {
+        StrictTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        StrictTree::is_empty(self)
+    }
+
+    fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        StrictTree::remove(self, key)
+    }
+}
@@ -0,0 +1,320 @@
+//! Encryption at rest for the values of strict trees.
+//!
+//! [`EncryptedTree`], returned by [`crate::tree::Tree::encrypted`], encrypts the
+//! encoded values of a tree with XChaCha20-Poly1305, using keys handed out by the
+//! [`KeyProvider`] it holds, so that keys can be loaded at runtime and two trees can
+//! use different keys. The stored key of every entry is authenticated along with its
+//! value, so a value copied under another key fails to decrypt. Keys are not
+//! encrypted.
+
+use bincode::config::Config;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sled::transaction::ConflictableTransactionError;
+use sled::IVec;
+use std::marker::PhantomData;
+use std::ops::Bound::{Excluded, Unbounded};
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{self, KeyCodec, OrderedKeyCodec};
+use crate::multi_tree;
+use crate::tree::RawTree;
+use crate::{error::Error, DefaultConfig};
+
+/// A 256-bit encryption key.
+pub type Key = [u8; 32];
+
+const KEY_ID_LEN: usize = 4;
+const NONCE_LEN: usize = 24;
+
+/// Hands out the keys used by an [`EncryptedTree`]. Every encrypted value stores the
+/// id of the key it was encrypted with, so older keys must stay available until
+/// every value using them has been re-encrypted.
+pub trait KeyProvider {
+    /// Id of the key used to encrypt new values.
+    fn current_key_id(&self) -> u32;
+
+    /// Returns the key with the given id, or `None` if it isn't known.
+    fn key(&self, id: u32) -> Option<Key>;
+}
+
+impl<P: KeyProvider + ?Sized> KeyProvider for Arc<P> {
+    fn current_key_id(&self) -> u32 {
+        (**self).current_key_id()
+    }
+
+    fn key(&self, id: u32) -> Option<Key> {
+        (**self).key(id)
+    }
+}
+
+/// A strict tree whose values are encoded with `VC`, then encrypted with the current
+/// key of its [`KeyProvider`]. Values are stored as the key id, a random nonce and the
+/// ciphertext. See the [module docs](self).
+pub struct EncryptedTree<K, V, P, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    raw: RawTree<C>,
+    provider: P,
+    validate_keys: bool,
+    types: PhantomData<fn() -> (K, V)>,
+    codecs: PhantomData<fn() -> (VC, KC)>,
+}
+
+impl<K, V, P: Clone, VC, C: Clone, KC> Clone for EncryptedTree<K, V, P, VC, C, KC> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            provider: self.provider.clone(),
+            validate_keys: self.validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+}
+
+impl<K, V, P: KeyProvider, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>>
+    EncryptedTree<K, V, P, VC, C, KC>
+{
+    pub(crate) fn new(raw: RawTree<C>, provider: P, validate_keys: bool) -> Self {
+        Self {
+            raw,
+            provider,
+            validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// The underlying sled tree, whose values are encrypted.
+    pub fn inner(&self) -> &sled::Tree {
+        self.raw.inner()
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        if self.validate_keys {
+            keys::check_round_trip::<K, KC, C>(key, self.raw.config)?;
+        }
+
+        let key_bytes = KC::encode_key(key, self.raw.config)?;
+        let sealed = encrypt(
+            &self.provider,
+            self.provider.current_key_id(),
+            &key_bytes,
+            &VC::encode(value, self.raw.config)?,
+        )?;
+
+        self.raw
+            .insert_raw(&key_bytes, sealed)?
+            .map(|old| self.decode_value(&key_bytes, &old))
+            .transpose()
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = KC::encode_key(key, self.raw.config)?;
+
+        self.raw
+            .get_raw(&key_bytes)?
+            .map(|sealed| self.decode_value(&key_bytes, &sealed))
+            .transpose()
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = KC::encode_key(key, self.raw.config)?;
+
+        self.raw
+            .remove_raw(&key_bytes)?
+            .map(|old| self.decode_value(&key_bytes, &old))
+            .transpose()
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        Ok(self
+            .raw
+            .inner_tree
+            .contains_key(KC::encode_key(key, self.raw.config)?)?)
+    }
+
+    /// Iterates over the entries of the tree, skipping those that fail to decrypt or
+    /// decode.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, V)> + '_ {
+        self.entries(self.raw.inner_tree.iter())
+    }
+
+    /// Iterates over the entries whose key is in `range`.
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)> + '_, Error> {
+        let range = self.raw.encode_range(range, KC::encode_key::<C>)?;
+
+        Ok(self.entries(self.raw.inner_tree.range(range)))
+    }
+
+    pub fn first(&self) -> Result<Option<(K, V)>, Error> {
+        self.decode_entry(self.raw.inner_tree.first()?)
+    }
+
+    pub fn last(&self) -> Result<Option<(K, V)>, Error> {
+        self.decode_entry(self.raw.inner_tree.last()?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.inner_tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.inner_tree.is_empty()
+    }
+
+    /// Re-encrypts every value with the current key of `new`, `batch_size` values at
+    /// a time, and returns how many were re-encrypted. Values are decrypted with the
+    /// provider of this tree, or with `new` if it doesn't know their key. Each batch is
+    /// written atomically, skipping values that changed in the meantime, and values
+    /// already using the new key are skipped, so an interrupted rotation resumes by
+    /// calling this again. Once it returns, open the tree with `new`.
+    pub fn rotate_key<New: KeyProvider>(
+        &self,
+        new: &New,
+        batch_size: usize,
+    ) -> Result<usize, Error> {
+        let sled_tree = &self.raw.inner_tree;
+        let mut rotated = 0;
+        let mut start = Unbounded;
+
+        loop {
+            let mut batch = Vec::with_capacity(batch_size);
+            for res in sled_tree.range::<IVec, _>((start.clone(), Unbounded)) {
+                let (key, value) = res?;
+                start = Excluded(key.clone());
+
+                if let Some(new_value) = reencrypt(&self.provider, new, &key, &value)? {
+                    batch.push((key, value, new_value));
+                    if batch.len() >= batch_size {
+                        break;
+                    }
+                }
+            }
+
+            if batch.is_empty() {
+                return Ok(rotated);
+            }
+
+            let written = multi_tree::transaction(sled_tree, |tx| {
+                let mut written = 0;
+                for (key, old_value, new_value) in &batch {
+                    if tx.get(key)?.as_ref() == Some(old_value) {
+                        tx.insert(key, new_value.as_slice())?;
+                        written += 1;
+                    }
+                }
+
+                Ok::<_, ConflictableTransactionError<()>>(written)
+            })?;
+            rotated += written.unwrap_or_default();
+        }
+    }
+
+    fn decode_value(&self, key_bytes: &[u8], sealed: &[u8]) -> Result<V, Error> {
+        VC::decode(
+            &decrypt(&self.provider, key_bytes, sealed)?,
+            self.raw.config,
+            self.raw.strict_decode,
+        )
+    }
+
+    fn entries(&self, iter: sled::Iter) -> impl DoubleEndedIterator<Item = (K, V)> + '_ {
+        iter.filter_map(move |res| self.decode_entry(Some(res.ok()?)).ok()?)
+    }
+
+    fn decode_entry(&self, entry: Option<(IVec, IVec)>) -> Result<Option<(K, V)>, Error> {
+        let Some((key_ivec, sealed)) = entry else {
+            return Ok(None);
+        };
+
+        Ok(Some((
+            KC::decode_key(&key_ivec, self.raw.config, self.raw.strict_decode)?,
+            self.decode_value(&key_ivec, &sealed)?,
+        )))
+    }
+}
+
+/// Returns the id of the key `bytes` were encrypted with.
+fn key_id(bytes: &[u8]) -> Result<u32, Error> {
+    let id = bytes
+        .get(..KEY_ID_LEN)
+        .ok_or(Error::EncryptionError("truncated value"))?;
+
+    Ok(u32::from_be_bytes(
+        id.try_into().expect("slice has 4 bytes"),
+    ))
+}
+
+/// Re-encrypts `bytes`, stored under `key_bytes`, with the current key of `new`,
+/// decrypting them with `old` or, if `old` doesn't know their key, with `new`.
+/// Returns `None` if they already use the current key of `new`.
+fn reencrypt(
+    old: &impl KeyProvider,
+    new: &impl KeyProvider,
+    key_bytes: &[u8],
+    bytes: &[u8],
+) -> Result<Option<Vec<u8>>, Error> {
+    let id = key_id(bytes)?;
+    if id == new.current_key_id() {
+        return Ok(None);
+    }
+
+    let plaintext = match old.key(id) {
+        Some(_) => decrypt(old, key_bytes, bytes)?,
+        None => decrypt(new, key_bytes, bytes)?,
+    };
+
+    Ok(Some(encrypt(
+        new,
+        new.current_key_id(),
+        key_bytes,
+        &plaintext,
+    )?))
+}
+
+fn encrypt(
+    provider: &impl KeyProvider,
+    key_id: u32,
+    key_bytes: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let key = provider
+        .key(key_id)
+        .ok_or(Error::EncryptionError("unknown key id"))?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let payload = Payload {
+        msg: plaintext,
+        aad: key_bytes,
+    };
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(&nonce, payload)
+        .map_err(|_| Error::EncryptionError("encryption failed"))?;
+
+    Ok([&key_id.to_be_bytes(), nonce.as_slice(), &ciphertext].concat())
+}
+
+fn decrypt(provider: &impl KeyProvider, key_bytes: &[u8], bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let key = provider
+        .key(key_id(bytes)?)
+        .ok_or(Error::EncryptionError("unknown key id"))?;
+    let nonce = bytes
+        .get(KEY_ID_LEN..KEY_ID_LEN + NONCE_LEN)
+        .ok_or(Error::EncryptionError("truncated value"))?;
+    let payload = Payload {
+        msg: &bytes[KEY_ID_LEN + NONCE_LEN..],
+        aad: key_bytes,
+    };
+
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(XNonce::from_slice(nonce), payload)
+        .map_err(|_| Error::EncryptionError("decryption failed"))
+}
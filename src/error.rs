@@ -8,6 +8,65 @@ pub enum Error {
     BincodeError(#[from] BincodeError),
     #[error("This operation is not allowed")]
     IllegalOperation,
+    #[error("Decoding exceeded the configured size limit")]
+    LimitExceeded,
+    #[error("Decoded {decoded} bytes but the stored item is {len} bytes long")]
+    TrailingBytes { decoded: usize, len: usize },
+    #[error("Key does not encode back to the same bytes after decoding")]
+    KeyRoundTrip,
+    #[error("Invalid key: {0}")]
+    InvalidKey(&'static str),
+    #[error("Unknown codec `{0}`")]
+    UnknownCodec(String),
+    #[error("The blocking task was cancelled before completing")]
+    Cancelled,
+    #[error("Tree `{tree}` was opened with `{stored}`, not `{requested}`")]
+    SchemaMismatch {
+        tree: String,
+        stored: String,
+        requested: String,
+    },
+    #[error("Stored value has version {version}, but the current version is {current}")]
+    UnsupportedVersion { version: u8, current: u8 },
+    #[error("The key is already in the tree")]
+    KeyExists,
+    #[error("The write would take the tree over its quota")]
+    QuotaExceeded,
+    #[error("Stored value has tag {stored}, not the tag {expected} of the requested type")]
+    TypeMismatch { expected: u32, stored: u32 },
+    #[error("Type `{0}` isn't registered")]
+    UnregisteredType(&'static str),
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(&'static str),
+    #[cfg(feature = "messagepack")]
+    #[error("MessagePack serialiser error")]
+    MessagePackError(#[from] MessagePackError),
+    #[cfg(feature = "cbor")]
+    #[error("CBOR serialiser error")]
+    CborError(#[from] CborError),
+    #[cfg(feature = "postcard")]
+    #[error("Postcard serialiser error")]
+    PostcardError(#[from] postcard::Error),
+    #[cfg(feature = "borsh")]
+    #[error("Borsh serialiser error")]
+    BorshError(#[source] std::io::Error),
+    #[cfg(feature = "prost")]
+    #[error("Protobuf decode error")]
+    ProtobufError(#[from] prost::DecodeError),
+    #[cfg(any(feature = "zstd", feature = "lz4"))]
+    #[error("Compression error")]
+    CompressionError(#[source] std::io::Error),
+    #[cfg(feature = "encryption")]
+    #[error("Encryption error: {0}")]
+    EncryptionError(&'static str),
+    #[cfg(feature = "checksum")]
+    #[error("Checksum mismatch for the value stored under {key_bytes:?}")]
+    ChecksumMismatch { key_bytes: Vec<u8> },
+    #[cfg(feature = "jsonl")]
+    #[error("JSON error")]
+    JsonError(#[from] serde_json::Error),
 }
 
 #[derive(Error, Debug)]
@@ -18,9 +77,58 @@ pub enum BincodeError {
     DecodeError(#[from] bincode::error::DecodeError),
 }
 
+#[cfg(feature = "messagepack")]
+#[derive(Error, Debug)]
+pub enum MessagePackError {
+    #[error("Encode error")]
+    EncodeError(#[from] rmp_serde::encode::Error),
+    #[error("Decode error")]
+    DecodeError(#[from] rmp_serde::decode::Error),
+}
+
+#[cfg(feature = "messagepack")]
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(value: rmp_serde::encode::Error) -> Self {
+        Self::MessagePackError(MessagePackError::EncodeError(value))
+    }
+}
+
+#[cfg(feature = "messagepack")]
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(value: rmp_serde::decode::Error) -> Self {
+        Self::MessagePackError(MessagePackError::DecodeError(value))
+    }
+}
+
+#[cfg(feature = "cbor")]
+#[derive(Error, Debug)]
+pub enum CborError {
+    #[error("Encode error")]
+    EncodeError(#[from] ciborium::ser::Error<std::io::Error>),
+    #[error("Decode error")]
+    DecodeError(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+#[cfg(feature = "cbor")]
+impl From<ciborium::ser::Error<std::io::Error>> for Error {
+    fn from(value: ciborium::ser::Error<std::io::Error>) -> Self {
+        Self::CborError(CborError::EncodeError(value))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl From<ciborium::de::Error<std::io::Error>> for Error {
+    fn from(value: ciborium::de::Error<std::io::Error>) -> Self {
+        Self::CborError(CborError::DecodeError(value))
+    }
+}
+
 impl From<bincode::error::DecodeError> for Error {
     fn from(value: bincode::error::DecodeError) -> Self {
-        Self::BincodeError(BincodeError::DecodeError(value))
+        match value {
+            bincode::error::DecodeError::LimitExceeded => Self::LimitExceeded,
+            value => Self::BincodeError(BincodeError::DecodeError(value)),
+        }
     }
 }
 
@@ -34,12 +142,66 @@ impl From<Error> for std::io::Error {
     fn from(value: Error) -> Self {
         match value {
             Error::SledError(e) => e.into(),
-            Error::BincodeError(_) => {
+            Error::Io(e) => e,
+            #[cfg(feature = "messagepack")]
+            Error::MessagePackError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "cbor")]
+            Error::CborError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "postcard")]
+            Error::PostcardError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "borsh")]
+            Error::BorshError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "prost")]
+            Error::ProtobufError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(any(feature = "zstd", feature = "lz4"))]
+            Error::CompressionError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "encryption")]
+            Error::EncryptionError(_) => {
                 std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
             }
-            Error::IllegalOperation => {
+            #[cfg(feature = "checksum")]
+            Error::ChecksumMismatch { .. } => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            #[cfg(feature = "jsonl")]
+            Error::JsonError(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            Error::BincodeError(_)
+            | Error::LimitExceeded
+            | Error::TrailingBytes { .. }
+            | Error::InvalidKey(_)
+            | Error::UnsupportedVersion { .. }
+            | Error::TypeMismatch { .. }
+            | Error::InvalidArchive(_) => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::InvalidData, value)
+            }
+            Error::IllegalOperation
+            | Error::KeyRoundTrip
+            | Error::UnknownCodec(_)
+            | Error::SchemaMismatch { .. }
+            | Error::UnregisteredType(_)
+            | Error::KeyExists => {
                 std::io::Error::new::<Error>(std::io::ErrorKind::InvalidInput, value)
             }
+            Error::QuotaExceeded => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::StorageFull, value)
+            }
+            Error::Cancelled => {
+                std::io::Error::new::<Error>(std::io::ErrorKind::Interrupted, value)
+            }
         }
     }
 }
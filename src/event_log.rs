@@ -0,0 +1,82 @@
+use bincode::{Decode, Encode};
+use std::ops::Bound::{Excluded, Unbounded};
+
+use crate::bincode_tree::BincodeTree;
+use crate::{error::Error, Db, StrictTree};
+
+/// The sequence number of an entry of an [`EventLog`].
+pub type Seq = u64;
+
+/// An append-only log of events, opened with [`Db::open_event_log`].
+///
+/// Events are stored under sequence numbers taken from [`Db::generate_id`], encoded as
+/// big-endian `u64`s so that the log iterates in append order. Sequence numbers only
+/// increase, but aren't contiguous as other users of `generate_id` take some too.
+#[derive(Clone)]
+pub struct EventLog<V> {
+    db: Db,
+    events: BincodeTree<Seq, V>,
+}
+
+impl<V: Encode + Decode> EventLog<V> {
+    pub(crate) fn new(db: Db, events: BincodeTree<Seq, V>) -> Self {
+        Self { db, events }
+    }
+
+    /// Appends an event and returns its sequence number.
+    pub fn append(&self, event: &V) -> Result<Seq, Error> {
+        let seq = self.db.generate_id()?;
+        self.events.insert(&seq, event)?;
+
+        Ok(seq)
+    }
+
+    pub fn get(&self, seq: Seq) -> Result<Option<V>, Error> {
+        self.events.get(&seq)
+    }
+
+    /// Iterates over the events from `seq` on, including it, in append order.
+    pub fn read_from(
+        &self,
+        seq: Seq,
+    ) -> Result<impl DoubleEndedIterator<Item = (Seq, V)> + '_, Error> {
+        self.events.range(seq..)
+    }
+
+    /// Iterates over the events appended after `seq`.
+    pub fn read_after(
+        &self,
+        seq: Seq,
+    ) -> Result<impl DoubleEndedIterator<Item = (Seq, V)> + '_, Error> {
+        self.events.range((Excluded(seq), Unbounded))
+    }
+
+    /// Iterates over every event in append order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Seq, V)> + '_ {
+        self.events.iter()
+    }
+
+    /// Sequence number of the first event still in the log.
+    pub fn first_seq(&self) -> Result<Option<Seq>, Error> {
+        Ok(self.events.first()?.map(|(seq, _)| seq))
+    }
+
+    /// Sequence number of the last appended event.
+    pub fn last_seq(&self) -> Result<Option<Seq>, Error> {
+        Ok(self.events.last()?.map(|(seq, _)| seq))
+    }
+
+    /// Removes the events before `seq`, e.g. once they are included in a snapshot or
+    /// replicated, and returns how many were removed.
+    pub fn truncate_before(&self, seq: Seq) -> Result<usize, Error> {
+        self.events.remove_range(..seq)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,121 @@
+//! Backups of a whole database as a single file, see [`Db::export_to`].
+//!
+//! An export starts with [`MAGIC`], the [`FORMAT_VERSION`] and the version of this
+//! crate, followed by every tree: its name, then its raw entries. Schema fingerprints
+//! are stored in [`crate::schema::SCHEMA_TREE`], so they are exported like any tree.
+
+use bincode::{Decode, Encode};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::admin::COPY_BATCH_SIZE;
+use crate::{error::Error, Db, BINCODE_CONFIG};
+
+/// First bytes of every export.
+pub const MAGIC: [u8; 8] = *b"SERSLEDX";
+
+/// Version of the export format, bumped on incompatible changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Name of the tree sled always opens. Its entries are exported too.
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+pub(crate) fn write_export<W: Write>(db: &Db, writer: &mut W) -> Result<usize, Error> {
+    writer.write_all(&MAGIC)?;
+    encode_into(&FORMAT_VERSION, writer)?;
+    encode_into(&env!("CARGO_PKG_VERSION"), writer)?;
+
+    let mut exported = 0;
+    for (_, name, entries) in db.inner_db.export() {
+        encode_into(&Some(name), writer)?;
+
+        for entry in entries {
+            let [key, value]: [Vec<u8>; 2] = entry
+                .try_into()
+                .map_err(|_| Error::InvalidArchive("sled exported an entry without a value"))?;
+            encode_into(&Some((key, value)), writer)?;
+            exported += 1;
+        }
+
+        encode_into(&None::<(Vec<u8>, Vec<u8>)>, writer)?;
+    }
+    encode_into(&None::<Vec<u8>>, writer)?;
+
+    Ok(exported)
+}
+
+pub(crate) fn read_export<R: Read>(db: &Db, reader: &mut R) -> Result<usize, Error> {
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(Error::InvalidArchive("not a ser-sled export"));
+    }
+    if decode_from::<u32, _>(reader)? != FORMAT_VERSION {
+        return Err(Error::InvalidArchive("unsupported export format version"));
+    }
+    // Version of the crate that wrote the export, kept for troubleshooting
+    decode_from::<String, _>(reader)?;
+
+    if !is_empty(db)? {
+        return Err(Error::IllegalOperation);
+    }
+
+    let mut imported = 0;
+    while let Some(name) = decode_from::<Option<Vec<u8>>, _>(reader)? {
+        let tree = if name == DEFAULT_TREE {
+            (*db.inner_db).clone()
+        } else {
+            db.inner_db.open_tree(name)?
+        };
+
+        let mut batch = sled::Batch::default();
+        let mut pending = 0;
+        while let Some((key, value)) = decode_from::<Option<(Vec<u8>, Vec<u8>)>, _>(reader)? {
+            batch.insert(key, value);
+            pending += 1;
+
+            if pending == COPY_BATCH_SIZE {
+                tree.apply_batch(std::mem::take(&mut batch))?;
+                imported += pending;
+                pending = 0;
+            }
+        }
+        tree.apply_batch(batch)?;
+        imported += pending;
+    }
+
+    Ok(imported)
+}
+
+pub(crate) fn export_to(db: &Db, path: &Path) -> Result<usize, Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let exported = write_export(db, &mut writer)?;
+    writer.flush()?;
+
+    Ok(exported)
+}
+
+pub(crate) fn import_from(db: &Db, path: &Path) -> Result<usize, Error> {
+    read_export(db, &mut BufReader::new(File::open(path)?))
+}
+
+/// Returns `true` if no tree of `db` has any entry.
+fn is_empty(db: &Db) -> Result<bool, Error> {
+    for name in db.inner_db.tree_names() {
+        if !db.inner_db.open_tree(name)?.is_empty() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn encode_into<T: Encode, W: Write>(value: &T, writer: &mut W) -> Result<(), Error> {
+    bincode::encode_into_std_write(value, writer, BINCODE_CONFIG)?;
+    Ok(())
+}
+
+fn decode_from<T: Decode, R: Read>(reader: &mut R) -> Result<T, Error> {
+    Ok(bincode::decode_from_std_read(reader, BINCODE_CONFIG)?)
+}
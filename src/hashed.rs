@@ -0,0 +1,101 @@
+//! Trees storing a keyed hash of their keys, see [`HashedTree`].
+
+use bincode::config::Config;
+use hmac::{Mac, SimpleHmac};
+use sha2::Sha256;
+use std::marker::PhantomData;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{self, KeyCodec, OrderedKeyCodec};
+use crate::tree::RawTree;
+use crate::{error::Error, DefaultConfig};
+
+/// A strict tree storing the HMAC-SHA256 of its keys encoded with `Inner`, keyed
+/// with the secret given to [`crate::tree::Tree::hashed`], so that key material such
+/// as email addresses never appears on disk. The tree must be opened with
+/// [`keys::HashedKeyCodec<Inner>`] so that [`crate::Db::check`] expects hashes.
+///
+/// Keys can't be recovered from the hash: `get`, `insert`, `remove` and
+/// `contains_key` work as usual, while iterating only works through `iter_hashed`,
+/// which returns the hashes. Hashes don't follow the order of the keys, so there are
+/// no ranges over keys.
+pub struct HashedTree<K, V, VC = BincodeCodec, C = DefaultConfig, Inner = OrderedKeyCodec> {
+    raw: RawTree<C>,
+    mac: SimpleHmac<Sha256>,
+    validate_keys: bool,
+    types: PhantomData<fn() -> (K, V)>,
+    codecs: PhantomData<fn() -> (VC, Inner)>,
+}
+
+impl<K, V, VC, C: Clone, Inner> Clone for HashedTree<K, V, VC, C, Inner> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            mac: self.mac.clone(),
+            validate_keys: self.validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, Inner: KeyCodec<K>> HashedTree<K, V, VC, C, Inner> {
+    pub(crate) fn new(raw: RawTree<C>, secret: &[u8], validate_keys: bool) -> Self {
+        Self {
+            raw,
+            mac: SimpleHmac::new_from_slice(secret).expect("HMAC accepts keys of any length"),
+            validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+
+    /// The underlying sled tree, whose keys are hashes.
+    pub fn inner(&self) -> &sled::Tree {
+        self.raw.inner()
+    }
+
+    /// The hash `key` is stored as.
+    pub fn hash_key(&self, key: &K) -> Result<Vec<u8>, Error> {
+        let mut mac = self.mac.clone();
+        mac.update(&Inner::encode_key(key, self.raw.config)?);
+
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        if self.validate_keys {
+            keys::check_round_trip::<K, Inner, C>(key, self.raw.config)?;
+        }
+
+        self.raw
+            .insert_bytes(self.hash_key(key)?, value, VC::encode::<C>, VC::decode::<C>)
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        self.raw.get_bytes(&self.hash_key(key)?, VC::decode::<C>)
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        self.raw.remove_bytes(&self.hash_key(key)?, VC::decode::<C>)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        Ok(self.raw.inner_tree.contains_key(self.hash_key(key)?)?)
+    }
+
+    /// Iterates over the entries of the tree with their hashed keys, as the keys
+    /// themselves can't be recovered.
+    pub fn iter_hashed(&self) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> {
+        self.raw
+            .key_bytes_entries(self.raw.inner_tree.iter(), VC::decode::<C>)
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.inner_tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.inner_tree.is_empty()
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use crate::{error::Error, Db};
+
+/// Name of the tree used by [`Db::health_check`]. It is always left empty.
+pub const HEALTH_TREE: &str = "__ser_sled_health";
+
+/// Timing and outcome of a single step of a health check.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub elapsed: Duration,
+    /// `None` if the step succeeded, otherwise a description of what went wrong.
+    pub error: Option<String>,
+}
+
+impl Probe {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Result of [`Db::health_check`].
+/// Steps run in order and stop at the first failure, so a step is `None`
+/// if an earlier one failed.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub open: Option<Probe>,
+    pub write: Option<Probe>,
+    pub read: Option<Probe>,
+    pub delete: Option<Probe>,
+    pub flush: Option<Probe>,
+    pub total: Duration,
+}
+
+impl HealthReport {
+    /// Returns `true` if every step ran and succeeded.
+    pub fn is_healthy(&self) -> bool {
+        [
+            &self.open,
+            &self.write,
+            &self.read,
+            &self.delete,
+            &self.flush,
+        ]
+        .iter()
+        .all(|probe| probe.as_ref().is_some_and(Probe::is_ok))
+    }
+}
+
+fn probe<T, E: ToString>(step: impl FnOnce() -> Result<T, E>) -> (Probe, Option<T>) {
+    let start = Instant::now();
+    let res = step();
+    let elapsed = start.elapsed();
+
+    match res {
+        Ok(value) => (
+            Probe {
+                elapsed,
+                error: None,
+            },
+            Some(value),
+        ),
+        Err(e) => (
+            Probe {
+                elapsed,
+                error: Some(e.to_string()),
+            },
+            None,
+        ),
+    }
+}
+
+pub(crate) fn health_check(db: &Db) -> HealthReport {
+    let start = Instant::now();
+    let mut report = HealthReport {
+        open: None,
+        write: None,
+        read: None,
+        delete: None,
+        flush: None,
+        total: Duration::ZERO,
+    };
+
+    let (open, tree) = probe(|| -> Result<_, Error> {
+        let key = db.generate_id()?.to_be_bytes();
+        Ok((db.inner_db.open_tree(HEALTH_TREE)?, key))
+    });
+    report.open = Some(open);
+
+    if let Some((tree, key)) = tree {
+        let value = start.elapsed().as_nanos().to_be_bytes();
+
+        let (write, written) = probe(|| tree.insert(key, &value));
+        report.write = Some(write);
+
+        if written.is_some() {
+            let (read, _) = probe(|| match tree.get(key) {
+                Ok(Some(read)) if read == value => Ok(()),
+                Ok(_) => Err("read back a different value than written".to_string()),
+                Err(e) => Err(e.to_string()),
+            });
+            let read_ok = read.is_ok();
+            report.read = Some(read);
+
+            // Always try to clean up, even if the read failed
+            let (delete, _) = probe(|| tree.remove(key));
+            let delete_ok = delete.is_ok();
+            report.delete = Some(delete);
+
+            if read_ok && delete_ok {
+                let (flush, _) = probe(|| db.inner_db.flush());
+                report.flush = Some(flush);
+            }
+        }
+    }
+
+    report.total = start.elapsed();
+    report
+}
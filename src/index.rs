@@ -0,0 +1,428 @@
+//! Secondary indexes, maintained by the writes of an [`IndexedTree`].
+//!
+//! An index maps a value extracted from every entry, such as one of its fields, to
+//! the keys of the entries. Extracted values are encoded with [`OrderedKey`], so
+//! tuples make composite indexes whose ranges can be queried like any key: an index
+//! on `(status, created_at)` returns the entries of one status ordered by creation
+//! time, and can be queried for a status within a time range.
+
+use bincode::{Decode, Encode};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use crate::bincode_tree::BincodeTree;
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{prefix_bounds, successor, to_ordered_bytes};
+use crate::keys::{CompositeKey, KeyCodec, OrderedKey, OrderedKeyCodec};
+use crate::multi_tree;
+use crate::query::Query;
+use crate::tree::RawTree;
+use crate::{error::Error, BytesRange, Db, DefaultConfig, StrictTree};
+
+pub(crate) fn index_tree_name(tree_name: &str, index_name: &str) -> String {
+    format!("{tree_name}_index_{index_name}")
+}
+
+/// Returns the encoded values indexing an entry.
+pub(crate) type Extractor<V> = Arc<dyn Fn(&V) -> Vec<Vec<u8>> + Send + Sync>;
+
+/// An index tree, and how to extract the indexed values of an entry.
+pub(crate) struct IndexTree<V> {
+    pub(crate) tree: sled::Tree,
+    extract: Extractor<V>,
+}
+
+impl<V> Clone for IndexTree<V> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            extract: self.extract.clone(),
+        }
+    }
+}
+
+impl<V: Encode + Decode> IndexTree<V> {
+    /// The keys of the index entries of the entry `key_bytes` holding `value`: each
+    /// indexed value followed by the entry's key, so that entries with the same
+    /// indexed value don't overwrite each other.
+    fn entry_keys(&self, value: &V, key_bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut entry_keys = (self.extract)(value);
+        for entry_key in &mut entry_keys {
+            entry_key.extend_from_slice(key_bytes);
+        }
+        entry_keys
+    }
+
+    /// Clears the index and indexes every entry of `raw` again.
+    pub(crate) fn rebuild(&self, raw: &RawTree<DefaultConfig>) -> Result<(), Error> {
+        self.tree.clear()?;
+
+        let mut batch = sled::Batch::default();
+        let mut batch_len = 0;
+        for entry in raw.inner_tree.iter() {
+            let (key_bytes, value_bytes) = entry?;
+            let value = decode_value(raw, &key_bytes, &value_bytes)?;
+            for entry_key in self.entry_keys(&value, &key_bytes) {
+                batch.insert(entry_key, key_bytes.clone());
+                batch_len += 1;
+            }
+
+            if batch_len >= crate::admin::COPY_BATCH_SIZE {
+                self.tree.apply_batch(std::mem::take(&mut batch))?;
+                batch_len = 0;
+            }
+        }
+        self.tree.apply_batch(batch)?;
+
+        Ok(())
+    }
+
+    /// The entry `key_bytes` of `raw`, if its index entries still match `matches`.
+    pub(crate) fn lookup<K: OrderedKey, F>(
+        &self,
+        raw: &RawTree<DefaultConfig>,
+        key_bytes: &[u8],
+        matches: F,
+    ) -> Result<Option<(K, V)>, Error>
+    where
+        F: FnOnce(&[Vec<u8>]) -> bool,
+    {
+        let Some(value_bytes) = raw.inner_tree.get(key_bytes)? else {
+            return Ok(None);
+        };
+        let value = decode_value(raw, key_bytes, &value_bytes)?;
+        if !matches(&self.entry_keys(&value, key_bytes)) {
+            return Ok(None);
+        }
+
+        let key = OrderedKeyCodec::decode_key(key_bytes, raw.config, raw.strict_decode)?;
+        Ok(Some((key, value)))
+    }
+}
+
+/// A strict tree whose writes keep its secondary indexes up to date, opened with
+/// [`Db::open_indexed_tree`]. Indexes are added with [`IndexedTree::add_index`].
+///
+/// Every index is stored in the `<name>_index_<index name>` tree, and updated in the
+/// same transaction as the entry. Writes made through other handles to the tree, or
+/// before the index was added to this handle, aren't indexed: see [`Index::rebuild`].
+#[derive(Clone)]
+pub struct IndexedTree<K, V> {
+    db: Db,
+    name: String,
+    tree: BincodeTree<K, V>,
+    indexes: Vec<IndexTree<V>>,
+}
+
+impl<K: OrderedKey, V: Encode + Decode> IndexedTree<K, V> {
+    pub(crate) fn new(db: Db, name: &str, tree: BincodeTree<K, V>) -> Self {
+        Self {
+            db,
+            name: name.to_string(),
+            tree,
+            indexes: Vec::new(),
+        }
+    }
+
+    /// Indexes the entries by the value `extract` returns for them, and returns the
+    /// handle to query the index. The index is built if it is empty but the tree
+    /// isn't. Handles cloned before aren't given the index.
+    pub fn add_index<I, F>(&mut self, index_name: &str, extract: F) -> Result<Index<K, V, I>, Error>
+    where
+        I: OrderedKey,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        let index = self.register(
+            index_name,
+            Arc::new(move |value| vec![to_ordered_bytes(&extract(value))]),
+        )?;
+
+        Ok(Index {
+            raw: self.tree.raw().clone(),
+            index,
+            types: PhantomData,
+        })
+    }
+
+    /// Inserts an entry and updates the indexes, returning the previous value.
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        self.tree.check_key(key)?;
+        let key_bytes = self.tree.encode_key(key)?;
+        let value_bytes = BincodeCodec::encode(value, self.tree.raw().config)?;
+        let entry_keys = self
+            .indexes
+            .iter()
+            .map(|index| index.entry_keys(value, &key_bytes))
+            .collect::<Vec<_>>();
+
+        self.write(&key_bytes, Some(&value_bytes), &entry_keys)
+    }
+
+    /// Replaces the value of `key` with what `f` returns for it, removing the entry if
+    /// `None`, and updates the indexes. Returns the new value. Like
+    /// [`crate::tree::Tree::update_and_fetch`], `f` may be called several times if the
+    /// entry is written concurrently.
+    pub fn update_and_fetch<F>(&self, key: &K, f: F) -> Result<Option<V>, Error>
+    where
+        F: FnMut(Option<V>) -> Option<V>,
+    {
+        self.tree.check_key(key)?;
+        let key_bytes = self.tree.encode_key(key)?;
+        let f = RefCell::new(f);
+
+        self.transaction(|tree, index_trees| {
+            let old = tree.get(&key_bytes)?;
+            if let Some(old) = &old {
+                self.remove_entries(index_trees, &key_bytes, old)?;
+            }
+            let old = old
+                .map(|old| decode_value(self.tree.raw(), &key_bytes, &old))
+                .transpose()
+                .map_err(ConflictableTransactionError::Abort)?;
+
+            let new = (f.borrow_mut())(old);
+            match &new {
+                Some(new) => {
+                    let value_bytes = BincodeCodec::encode(new, self.tree.raw().config)
+                        .map_err(ConflictableTransactionError::Abort)?;
+                    tree.insert(key_bytes.as_slice(), value_bytes)?;
+                    for (index, index_tree) in self.indexes.iter().zip(index_trees) {
+                        for entry_key in index.entry_keys(new, &key_bytes) {
+                            index_tree.insert(entry_key, key_bytes.as_slice())?;
+                        }
+                    }
+                }
+                None => {
+                    tree.remove(key_bytes.as_slice())?;
+                }
+            }
+
+            Ok(new)
+        })
+    }
+
+    /// Removes an entry and its index entries, returning its value.
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+
+        self.write(&key_bytes, None, &[])
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        self.tree.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        self.tree.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// A [`Query`] over every entry, which can read from an index of this tree
+    /// with [`Query::range_by_index`].
+    pub fn query(&self) -> Query<'_, K, V> {
+        self.tree.query()
+    }
+
+    /// The indexed tree, to read from it. Writes through it aren't indexed.
+    pub fn tree(&self) -> &BincodeTree<K, V> {
+        &self.tree
+    }
+
+    /// Writes `value` to the entry `key_bytes`, or removes it if `None`, in a
+    /// transaction that removes the index entries of its previous value and inserts
+    /// `entry_keys`. Returns the previous value.
+    fn write(
+        &self,
+        key_bytes: &[u8],
+        value: Option<&[u8]>,
+        entry_keys: &[Vec<Vec<u8>>],
+    ) -> Result<Option<V>, Error> {
+        let old = self.transaction(|tree, index_trees| {
+            let old = match value {
+                Some(value) => tree.insert(key_bytes, value)?,
+                None => tree.remove(key_bytes)?,
+            };
+            if let Some(old) = &old {
+                self.remove_entries(index_trees, key_bytes, old)?;
+            }
+            for (entry_keys, index_tree) in entry_keys.iter().zip(index_trees) {
+                for entry_key in entry_keys {
+                    index_tree.insert(entry_key.as_slice(), key_bytes)?;
+                }
+            }
+
+            Ok(old)
+        })?;
+
+        old.map(|old| decode_value(self.tree.raw(), key_bytes, &old))
+            .transpose()
+    }
+
+    /// Removes the index entries of the entry `key_bytes` holding `value_bytes`.
+    fn remove_entries(
+        &self,
+        index_trees: &[TransactionalTree],
+        key_bytes: &[u8],
+        value_bytes: &[u8],
+    ) -> ConflictableTransactionResult<(), Error> {
+        let value = decode_value(self.tree.raw(), key_bytes, value_bytes)
+            .map_err(ConflictableTransactionError::Abort)?;
+        for (index, index_tree) in self.indexes.iter().zip(index_trees) {
+            for entry_key in index.entry_keys(&value, key_bytes) {
+                index_tree.remove(entry_key)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs `f` on the tree and its index trees, in the order of `self.indexes`, in a
+    /// transaction.
+    fn transaction<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&TransactionalTree, &[TransactionalTree]) -> ConflictableTransactionResult<T, Error>,
+    {
+        let trees = std::iter::once(self.sled_tree())
+            .chain(self.indexes.iter().map(|index| &index.tree))
+            .collect::<Vec<_>>();
+
+        multi_tree::transaction(trees.as_slice(), |trees| {
+            let (tree, index_trees) = trees.split_first().expect("the tree is in the transaction");
+            f(tree, index_trees)
+        })?
+    }
+
+    /// Adds the index `index_name` to this handle, and builds it if it is empty but
+    /// the tree isn't.
+    pub(crate) fn register(
+        &mut self,
+        index_name: &str,
+        extract: Extractor<V>,
+    ) -> Result<IndexTree<V>, Error> {
+        let index = IndexTree {
+            tree: self
+                .db
+                .open_sled_tree(&index_tree_name(&self.name, index_name))?,
+            extract,
+        };
+        if index.tree.is_empty() && !self.tree.is_empty() {
+            index.rebuild(self.tree.raw())?;
+        }
+        self.indexes.push(index.clone());
+
+        Ok(index)
+    }
+
+    fn sled_tree(&self) -> &sled::Tree {
+        &self.tree.raw().inner_tree
+    }
+}
+
+/// A secondary index of an [`IndexedTree`], whose entries are found by the value
+/// `I` extracted from them. Returned by [`IndexedTree::add_index`].
+///
+/// Entries are looked up in the indexed tree, and skipped if they were removed or
+/// no longer match since the index was read.
+pub struct Index<K, V, I> {
+    raw: RawTree<DefaultConfig>,
+    index: IndexTree<V>,
+    types: PhantomData<fn() -> (K, I)>,
+}
+
+impl<K, V, I> Clone for Index<K, V, I> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            index: self.index.clone(),
+            types: PhantomData,
+        }
+    }
+}
+
+impl<K: OrderedKey, V: Encode + Decode, I: OrderedKey> Index<K, V, I> {
+    /// The entries whose indexed value is `indexed`, in key order.
+    pub fn get(&self, indexed: &I) -> Result<Vec<(K, V)>, Error> {
+        self.range(indexed..=indexed).collect()
+    }
+
+    /// The entries whose indexed value is in `range`, ordered by indexed value then
+    /// key.
+    pub fn range<R: RangeBounds<I>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> + '_ {
+        let bounds = prefix_bounds(range).unwrap_or((Included(vec![]), Excluded(vec![])));
+        self.entries(bounds)
+    }
+
+    /// The entries whose indexed value starts with the components of `prefix`, such as
+    /// all the entries of one status in an index on `(status, created_at)`.
+    pub fn prefix(
+        &self,
+        prefix: &CompositeKey,
+    ) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> + '_ {
+        let start = prefix.as_bytes().to_vec();
+        let bounds = match successor(start.clone()) {
+            Some(end) => (Included(start), Excluded(end)),
+            None => (Included(start), Unbounded),
+        };
+        self.entries(bounds)
+    }
+
+    /// Number of index entries, which is the number of indexed entries.
+    pub fn len(&self) -> usize {
+        self.index.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.tree.is_empty()
+    }
+
+    /// Clears the index and indexes every entry of the tree again, e.g. after writes
+    /// made without the index.
+    pub fn rebuild(&self) -> Result<(), Error> {
+        self.index.rebuild(&self.raw)
+    }
+
+    fn entries(
+        &self,
+        bounds: BytesRange,
+    ) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> + '_ {
+        self.index.tree.range(bounds).filter_map(|entry| {
+            let (entry_key, key_bytes) = match entry {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.index
+                .lookup(&self.raw, &key_bytes, |entry_keys| {
+                    entry_keys.iter().any(|key| *key == *entry_key)
+                })
+                .transpose()
+        })
+    }
+}
+
+fn decode_value<V: Encode + Decode>(
+    raw: &RawTree<DefaultConfig>,
+    key_bytes: &[u8],
+    value_bytes: &[u8],
+) -> Result<V, Error> {
+    raw.decode_stored(
+        BincodeCodec::decode::<DefaultConfig>,
+        key_bytes,
+        value_bytes,
+    )
+}
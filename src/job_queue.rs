@@ -0,0 +1,286 @@
+use bincode::{Decode, Encode};
+use sled::transaction::ConflictableTransactionError;
+use sled::IVec;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::multi_tree::{self, now_ms};
+use crate::{error::Error, Db, BINCODE_CONFIG};
+
+pub(crate) fn in_flight_name(queue_name: &str) -> String {
+    format!("{queue_name}_in_flight")
+}
+
+pub(crate) fn dead_letters_name(queue_name: &str) -> String {
+    format!("{queue_name}_dead_letters")
+}
+
+/// A job taken from a [`JobQueue`] by [`JobQueue::claim`]. Pass it to
+/// [`JobQueue::ack`] once done, or to [`JobQueue::nack`] to give it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job<V> {
+    pub id: u64,
+    pub value: V,
+    /// Identifies this claim of the job, so that a worker whose lease expired can't
+    /// acknowledge the job once another worker claimed it.
+    claim: u64,
+}
+
+/// A claimed job, as stored in the in-flight tree.
+#[derive(Encode, Decode)]
+struct Lease {
+    worker: String,
+    claim: u64,
+    deadline_ms: u64,
+    job: Vec<u8>,
+}
+
+/// A persistent work queue, opened with [`Db::open_job_queue`].
+///
+/// Jobs wait in the `<name>` tree under increasing IDs taken from [`Db::generate_id`].
+/// Claiming a job moves it, in a transaction, to the `<name>_in_flight` tree along with
+/// the claiming worker and the end of its lease. Jobs whose lease ended without being
+/// acknowledged go back to the queue, so that another worker can claim them: a job is
+/// processed at least once, but possibly more than once.
+///
+/// Jobs and leases that fail to decode are moved as they are to the
+/// `<name>_dead_letters` tree, so that they don't block the jobs after them.
+#[derive(Clone)]
+pub struct JobQueue<V> {
+    db: Db,
+    pending: sled::Tree,
+    in_flight: sled::Tree,
+    dead_letters: sled::Tree,
+    value_type: PhantomData<fn() -> V>,
+}
+
+impl<V: Encode + Decode> JobQueue<V> {
+    pub(crate) fn new(
+        db: Db,
+        pending: sled::Tree,
+        in_flight: sled::Tree,
+        dead_letters: sled::Tree,
+    ) -> Self {
+        Self {
+            db,
+            pending,
+            in_flight,
+            dead_letters,
+            value_type: PhantomData,
+        }
+    }
+
+    /// Adds a job at the end of the queue and returns its ID.
+    pub fn enqueue(&self, job: &V) -> Result<u64, Error> {
+        let id = self.db.generate_id()?;
+        self.pending.insert(
+            id.to_be_bytes(),
+            bincode::encode_to_vec(job, BINCODE_CONFIG)?,
+        )?;
+
+        Ok(id)
+    }
+
+    /// Takes the oldest job of the queue for `worker_id`, which has `lease` to
+    /// acknowledge it before it goes back to the queue. Expired leases are requeued
+    /// first. Returns `None` if the queue is empty.
+    pub fn claim(&self, worker_id: &str, lease: Duration) -> Result<Option<Job<V>>, Error> {
+        self.requeue_expired()?;
+
+        for entry in self.pending.iter() {
+            let (id_bytes, job_bytes) = entry?;
+            let Ok((value, _)) = bincode::decode_from_slice(&job_bytes, BINCODE_CONFIG) else {
+                self.move_to_dead_letters(&self.pending, &id_bytes, &job_bytes)?;
+                continue;
+            };
+
+            let claim = self.db.generate_id()?;
+            let lease = bincode::encode_to_vec(
+                Lease {
+                    worker: worker_id.to_string(),
+                    claim,
+                    deadline_ms: now_ms().saturating_add(lease.as_millis() as u64),
+                    job: job_bytes.to_vec(),
+                },
+                BINCODE_CONFIG,
+            )?;
+
+            let claimed = multi_tree::transaction(&self.trees(), |(pending, in_flight)| {
+                // Claimed by another worker in the meantime
+                if pending.remove(&id_bytes)?.is_none() {
+                    return Err(ConflictableTransactionError::Abort(()));
+                }
+                in_flight.insert(&id_bytes, lease.as_slice())?;
+                Ok(())
+            })?;
+
+            if claimed.is_ok() {
+                return Ok(Some(Job {
+                    id: decode_id(&id_bytes)?,
+                    value,
+                    claim,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Removes a finished job. Returns `false` if its lease expired and the job went
+    /// back to the queue, or was claimed again.
+    pub fn ack(&self, job: &Job<V>) -> Result<bool, Error> {
+        let id_bytes = job.id.to_be_bytes();
+
+        let Some(current) = self.current_lease(job)? else {
+            return Ok(false);
+        };
+
+        Ok(self
+            .in_flight
+            .compare_and_swap(id_bytes, Some(current), None::<&[u8]>)?
+            .is_ok())
+    }
+
+    /// Gives a job back, at its original place in the queue, for another worker to
+    /// claim. Returns `false` if its lease had already expired.
+    pub fn nack(&self, job: &Job<V>) -> Result<bool, Error> {
+        match self.current_lease(job)? {
+            Some(current) => self.requeue(&job.id.to_be_bytes(), &current),
+            None => Ok(false),
+        }
+    }
+
+    /// Puts the jobs whose lease expired back in the queue, and returns how many were
+    /// requeued. [`JobQueue::claim`] calls it, so it only needs to be called to requeue
+    /// jobs while no worker is claiming.
+    pub fn requeue_expired(&self) -> Result<usize, Error> {
+        let now = now_ms();
+        let mut requeued = 0;
+
+        for entry in self.in_flight.iter() {
+            let (id_bytes, lease_bytes) = entry?;
+            let Ok((lease, _)) =
+                bincode::decode_from_slice::<Lease, _>(&lease_bytes, BINCODE_CONFIG)
+            else {
+                self.move_to_dead_letters(&self.in_flight, &id_bytes, &lease_bytes)?;
+                continue;
+            };
+
+            if lease.deadline_ms <= now && self.requeue(&id_bytes, &lease_bytes)? {
+                requeued += 1;
+            }
+        }
+
+        Ok(requeued)
+    }
+
+    /// The ID, value and worker of every claimed job.
+    pub fn in_flight(&self) -> Result<Vec<(u64, V, String)>, Error> {
+        self.in_flight
+            .iter()
+            .map(|entry| {
+                let (id_bytes, lease_bytes) = entry?;
+                let lease: Lease = bincode::decode_from_slice(&lease_bytes, BINCODE_CONFIG)?.0;
+                let value = bincode::decode_from_slice(&lease.job, BINCODE_CONFIG)?.0;
+
+                Ok((decode_id(&id_bytes)?, value, lease.worker))
+            })
+            .collect()
+    }
+
+    /// Number of jobs waiting to be claimed.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of claimed jobs that weren't acknowledged yet.
+    pub fn in_flight_len(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// The ID and stored bytes of every job or lease that failed to decode.
+    pub fn dead_letters(&self) -> Result<Vec<(u64, IVec)>, Error> {
+        self.dead_letters
+            .iter()
+            .map(|entry| {
+                let (id_bytes, bytes) = entry?;
+
+                Ok((decode_id(&id_bytes)?, bytes))
+            })
+            .collect()
+    }
+
+    /// Removes the dead letters, once inspected, and returns how many were removed.
+    pub fn clear_dead_letters(&self) -> Result<usize, Error> {
+        let removed = self.dead_letters.len();
+        self.dead_letters.clear()?;
+
+        Ok(removed)
+    }
+
+    /// The stored lease of `job`, if it is still the one `job` was claimed with.
+    fn current_lease(&self, job: &Job<V>) -> Result<Option<IVec>, Error> {
+        let Some(current) = self.in_flight.get(job.id.to_be_bytes())? else {
+            return Ok(None);
+        };
+        let lease: Lease = bincode::decode_from_slice(&current, BINCODE_CONFIG)?.0;
+
+        Ok((lease.claim == job.claim).then_some(current))
+    }
+
+    /// Moves a job back from the in-flight tree to the queue, unless its lease changed
+    /// since `lease_bytes` was read. Returns whether it was moved.
+    fn requeue(&self, id_bytes: &[u8], lease_bytes: &[u8]) -> Result<bool, Error> {
+        let lease: Lease = bincode::decode_from_slice(lease_bytes, BINCODE_CONFIG)?.0;
+
+        let requeued = multi_tree::transaction(&self.trees(), |(pending, in_flight)| {
+            if in_flight.get(id_bytes)?.as_deref() != Some(lease_bytes) {
+                return Err(ConflictableTransactionError::Abort(()));
+            }
+            in_flight.remove(id_bytes)?;
+            pending.insert(id_bytes, lease.job.as_slice())?;
+            Ok(())
+        })?;
+
+        Ok(requeued.is_ok())
+    }
+
+    /// The queue and in-flight trees, to run transactions on.
+    fn trees(&self) -> (&sled::Tree, &sled::Tree) {
+        (&self.pending, &self.in_flight)
+    }
+
+    /// Moves the entry `id_bytes` of `from` to the dead-letter tree, unless it changed
+    /// since `bytes` was read.
+    fn move_to_dead_letters(
+        &self,
+        from: &sled::Tree,
+        id_bytes: &[u8],
+        bytes: &[u8],
+    ) -> Result<(), Error> {
+        // Never aborts
+        let _: Result<(), ()> =
+            multi_tree::transaction(&(from, &self.dead_letters), |(from, dead_letters)| {
+                if from.get(id_bytes)?.as_deref() == Some(bytes) {
+                    from.remove(id_bytes)?;
+                    dead_letters.insert(id_bytes, bytes)?;
+                }
+
+                Ok(())
+            })?;
+
+        Ok(())
+    }
+}
+
+fn decode_id(bytes: &[u8]) -> Result<u64, Error> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| Error::InvalidKey("job key isn't an ID"))?;
+
+    Ok(u64::from_be_bytes(bytes))
+}
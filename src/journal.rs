@@ -0,0 +1,193 @@
+//! Incremental backups of a tree through a journal of its changes.
+//!
+//! A [`JournaledTree`] writes every change to its tree and, in the same transaction,
+//! to the `<tree>_journal` tree under an increasing sequence number.
+//! [`JournaledTree::export_changes_since`] writes the changes after a sequence number
+//! to a file that [`Db::replay_changes`] applies to another database.
+
+use bincode::config::Config;
+use bincode::{Decode, Encode};
+use sled::transaction::ConflictableTransactionError;
+use sled::IVec;
+use std::io::{Read, Write};
+
+use crate::admin::COPY_BATCH_SIZE;
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::multi_tree;
+use crate::tree::Tree;
+use crate::{error::Error, Db, DefaultConfig, BINCODE_CONFIG};
+
+/// First bytes of every change export.
+pub const CHANGES_MAGIC: [u8; 8] = *b"SERSLEDJ";
+
+pub(crate) fn journal_name(tree_name: &str) -> String {
+    format!("{tree_name}_journal")
+}
+
+/// A change to the raw entries of a tree, as stored in its journal.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Insert { key: Vec<u8>, value: Vec<u8> },
+    Remove { key: Vec<u8> },
+}
+
+/// A strict tree whose changes are journaled, created with [`Tree::journaled`].
+/// Writes made through other handles to the same tree aren't journaled.
+#[derive(Clone)]
+pub struct JournaledTree<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    tree: Tree<K, V, VC, C, KC>,
+    journal: sled::Tree,
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> JournaledTree<K, V, VC, C, KC> {
+    pub(crate) fn new(tree: Tree<K, V, VC, C, KC>, journal: sled::Tree) -> Self {
+        Self { tree, journal }
+    }
+
+    /// The journaled tree, to read from it. Writes through it aren't journaled.
+    pub fn tree(&self) -> &Tree<K, V, VC, C, KC> {
+        &self.tree
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        self.tree.check_key(key)?;
+        let key = self.tree.encode_key(key)?;
+        let value = VC::encode(value, self.tree.raw().config)?;
+
+        self.decode_old(
+            &key,
+            self.apply(Change::Insert {
+                key: key.clone(),
+                value,
+            })?,
+        )
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key = self.tree.encode_key(key)?;
+
+        self.decode_old(&key, self.apply(Change::Remove { key: key.clone() })?)
+    }
+
+    /// Sequence number of the last journaled change, or `0` if there is none.
+    pub fn last_seq(&self) -> Result<u64, Error> {
+        Ok(match self.journal.last()? {
+            Some((seq, _)) => decode_seq(&seq)?,
+            None => 0,
+        })
+    }
+
+    /// Writes the changes with a sequence number greater than `seq` to `writer`, and
+    /// returns the sequence number of the last one written, or `seq` if there was none.
+    /// Pass the returned number to the next call to get the changes made since.
+    pub fn export_changes_since<W: Write>(&self, seq: u64, mut writer: W) -> Result<u64, Error> {
+        writer.write_all(&CHANGES_MAGIC)?;
+        bincode::encode_into_std_write(
+            self.tree.raw().inner_tree.name().to_vec(),
+            &mut writer,
+            BINCODE_CONFIG,
+        )?;
+
+        let mut last = seq;
+        for res in self.journal.range(seq.saturating_add(1).to_be_bytes()..) {
+            let (seq_bytes, change) = res?;
+            last = decode_seq(&seq_bytes)?;
+
+            let change: Change = bincode::decode_from_slice(&change, BINCODE_CONFIG)?.0;
+            bincode::encode_into_std_write(Some((last, change)), &mut writer, BINCODE_CONFIG)?;
+        }
+        bincode::encode_into_std_write(None::<(u64, Change)>, &mut writer, BINCODE_CONFIG)?;
+        writer.flush()?;
+
+        Ok(last)
+    }
+
+    /// Forgets the changes up to and including `seq`, once they have been exported.
+    pub fn prune_changes_until(&self, seq: u64) -> Result<usize, Error> {
+        let mut batch = sled::Batch::default();
+        let mut pruned = 0;
+
+        for res in self.journal.range(..=seq.to_be_bytes()) {
+            batch.remove(res?.0);
+            pruned += 1;
+        }
+        self.journal.apply_batch(batch)?;
+
+        Ok(pruned)
+    }
+
+    /// Applies `change` to the tree and appends it to the journal atomically, returning
+    /// the value it replaced.
+    fn apply(&self, change: Change) -> Result<Option<IVec>, Error> {
+        let encoded = bincode::encode_to_vec(&change, BINCODE_CONFIG)?;
+
+        let old = multi_tree::transaction(
+            &(&self.tree.raw().inner_tree, &self.journal),
+            |(tree, journal)| {
+                let old = match &change {
+                    Change::Insert { key, value } => {
+                        tree.insert(key.as_slice(), value.as_slice())?
+                    }
+                    Change::Remove { key } => tree.remove(key.as_slice())?,
+                };
+
+                let seq = journal.generate_id()? + 1;
+                journal.insert(&seq.to_be_bytes(), encoded.as_slice())?;
+
+                Ok::<_, ConflictableTransactionError<()>>(old)
+            },
+        )?;
+
+        Ok(old.ok().flatten())
+    }
+
+    fn decode_old(&self, key: &[u8], old: Option<IVec>) -> Result<Option<V>, Error> {
+        old.map(|old| self.tree.raw().decode_stored(VC::decode::<C>, key, &old))
+            .transpose()
+    }
+}
+
+fn decode_seq(bytes: &[u8]) -> Result<u64, Error> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| Error::InvalidArchive("journal key isn't a sequence number"))?;
+
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Applies the changes exported by [`JournaledTree::export_changes_since`] to the tree
+/// they were exported from, returning the sequence number of the last one.
+pub(crate) fn replay_changes<R: Read>(db: &Db, mut reader: R) -> Result<Option<u64>, Error> {
+    let mut magic = [0; CHANGES_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != CHANGES_MAGIC {
+        return Err(Error::InvalidArchive("not a ser-sled change export"));
+    }
+
+    let name: Vec<u8> = bincode::decode_from_std_read(&mut reader, BINCODE_CONFIG)?;
+    let tree = db.inner_db.open_tree(name)?;
+
+    // A batch keeps the last change of every key, like applying them in order would
+    let mut batch = sled::Batch::default();
+    let mut pending = 0;
+    let mut last = None;
+    while let Some((seq, change)) =
+        bincode::decode_from_std_read::<Option<(u64, Change)>, _, _>(&mut reader, BINCODE_CONFIG)?
+    {
+        match change {
+            Change::Insert { key, value } => batch.insert(key, value),
+            Change::Remove { key } => batch.remove(key),
+        };
+        last = Some(seq);
+        pending += 1;
+
+        if pending == COPY_BATCH_SIZE {
+            tree.apply_batch(std::mem::take(&mut batch))?;
+            pending = 0;
+        }
+    }
+    tree.apply_batch(batch)?;
+
+    Ok(last)
+}
@@ -0,0 +1,85 @@
+//! Human-readable dumps of a tree as JSON Lines: one `{"key": ..., "value": ...}`
+//! object per line, for debugging or for feeding data into other tools.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+use crate::error::Error;
+
+/// What `import_jsonl` does with a line whose key is already in the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keeps the stored value.
+    Skip,
+    /// Replaces the stored value.
+    Overwrite,
+    /// Stops the import with [`Error::KeyExists`]. Lines before it stay imported.
+    Error,
+}
+
+#[derive(Serialize)]
+struct Line<'a, K, V> {
+    key: &'a K,
+    value: &'a V,
+}
+
+#[derive(Deserialize)]
+struct OwnedLine<K, V> {
+    key: K,
+    value: V,
+}
+
+/// Writes `entries` to `writer` as JSON Lines, stopping at the first entry that
+/// failed to decode, and returns how many were written.
+pub(crate) fn export<K, V, I, W>(entries: I, mut writer: W) -> Result<usize, Error>
+where
+    K: Serialize,
+    V: Serialize,
+    I: Iterator<Item = Result<(K, V), Error>>,
+    W: Write,
+{
+    let mut exported = 0;
+
+    for entry in entries {
+        let (key, value) = entry?;
+        serde_json::to_writer(
+            &mut writer,
+            &Line {
+                key: &key,
+                value: &value,
+            },
+        )?;
+        writer.write_all(b"\n")?;
+        exported += 1;
+    }
+    writer.flush()?;
+
+    Ok(exported)
+}
+
+/// Reads JSON Lines written by `export` from `reader`, skipping blank lines, and
+/// passes every entry to `insert`, which returns whether it was written. Returns
+/// how many entries were written.
+pub(crate) fn import<K, V, R, F>(reader: R, mut insert: F) -> Result<usize, Error>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+    R: BufRead,
+    F: FnMut(K, V) -> Result<bool, Error>,
+{
+    let mut imported = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let OwnedLine { key, value } = serde_json::from_str(&line)?;
+        if insert(key, value)? {
+            imported += 1;
+        }
+    }
+
+    Ok(imported)
+}
@@ -0,0 +1,741 @@
+//! Key encodings for strict trees.
+//!
+//! sled orders entries by their key bytes. bincode's encoding doesn't preserve the
+//! order of most types: signed integers are zigzag-encoded, and strings and byte
+//! vectors are prefixed by their length, so that `"b" < "aa"` once encoded.
+//! [`OrderedKeyCodec`], used by strict trees by default, encodes keys so that the
+//! byte order matches the order of the keys themselves:
+//!
+//! - integers are fixed-width big-endian, with the sign bit flipped for signed ones,
+//! - strings and byte vectors are escaped (`0x00` becomes `0x00 0xFF`) and terminated
+//!   by `0x00 0x00`, so that a key sorts before any longer key it is a prefix of,
+//! - tuples are the concatenation of their elements, see also [`CompositeKey`].
+
+use bincode::config::Config;
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+
+use crate::error::Error;
+use crate::BytesRange;
+
+pub use std::cmp::Reverse;
+
+#[cfg(feature = "derive")]
+pub use ser_sled_derive::OrderedKey;
+
+/// How a strict tree turns its keys into bytes and back.
+pub trait KeyCodec<K> {
+    fn encode_key<C: Config>(key: &K, config: C) -> Result<Vec<u8>, Error>;
+    fn decode_key<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<K, Error>;
+
+    /// Checks that `bytes` are a valid key, for [`crate::Db::check`]. Decodes them
+    /// by default.
+    fn validate_key<C: Config>(bytes: &[u8], config: C) -> Result<(), Error> {
+        Self::decode_key(bytes, config, true).map(|_| ())
+    }
+}
+
+/// Order-preserving encoding for types implementing [`OrderedKey`]. The bincode
+/// configuration of the tree is ignored. This is the default for strict trees.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderedKeyCodec;
+
+/// Stores `String` keys as raw UTF-8, without escaping or terminator, so that
+/// `scan_prefix_bytes("user:")` and ranges follow the plain lexicographic order
+/// of the strings. Unlike [`OrderedKeyCodec`], the key can't be part of a tuple.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrKeyCodec;
+
+/// Encodes keys with bincode, like relaxed trees do. Range queries over such keys
+/// only follow the order of the keys for unsigned integers. Use it to open trees
+/// written before strict trees switched to [`OrderedKeyCodec`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeKeyCodec;
+
+/// Encodes keys with borsh. Like with [`BincodeKeyCodec`], ranges only follow the
+/// order of the keys for some types: borsh integers are little-endian.
+#[cfg(feature = "borsh")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BorshKeyCodec;
+
+/// Encodes keys with bincode's serde support, like relaxed serde trees do.
+/// See [`BincodeKeyCodec`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeKeyCodec;
+
+impl<K: OrderedKey> KeyCodec<K> for OrderedKeyCodec {
+    fn encode_key<C: Config>(key: &K, _config: C) -> Result<Vec<u8>, Error> {
+        Ok(to_ordered_bytes(key))
+    }
+
+    fn decode_key<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<K, Error> {
+        let mut input = bytes;
+        let key = K::decode_ordered(&mut input)?;
+
+        if strict && !input.is_empty() {
+            return Err(Error::TrailingBytes {
+                decoded: bytes.len() - input.len(),
+                len: bytes.len(),
+            });
+        }
+
+        Ok(key)
+    }
+}
+
+impl KeyCodec<String> for StrKeyCodec {
+    fn encode_key<C: Config>(key: &String, _config: C) -> Result<Vec<u8>, Error> {
+        Ok(key.as_bytes().to_vec())
+    }
+
+    fn decode_key<C: Config>(bytes: &[u8], _config: C, _strict: bool) -> Result<String, Error> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::InvalidKey("invalid UTF-8"))
+    }
+}
+
+impl<K: Encode + Decode> KeyCodec<K> for BincodeKeyCodec {
+    fn encode_key<C: Config>(key: &K, config: C) -> Result<Vec<u8>, Error> {
+        crate::codec::bincode_encode(key, config)
+    }
+
+    fn decode_key<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<K, Error> {
+        crate::codec::bincode_decode(bytes, config, strict)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: Serialize + DeserializeOwned> KeyCodec<K> for SerdeKeyCodec {
+    fn encode_key<C: Config>(key: &K, config: C) -> Result<Vec<u8>, Error> {
+        crate::codec::serde_encode(key, config)
+    }
+
+    fn decode_key<C: Config>(bytes: &[u8], config: C, strict: bool) -> Result<K, Error> {
+        crate::codec::serde_decode(bytes, config, strict)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<K: borsh::BorshSerialize + borsh::BorshDeserialize> KeyCodec<K> for BorshKeyCodec {
+    fn encode_key<C: Config>(key: &K, _config: C) -> Result<Vec<u8>, Error> {
+        crate::codec::borsh_encode(key)
+    }
+
+    fn decode_key<C: Config>(bytes: &[u8], _config: C, strict: bool) -> Result<K, Error> {
+        crate::codec::borsh_decode(bytes, strict)
+    }
+}
+
+/// The key codec of trees storing an HMAC of their keys encoded with `Inner`, which
+/// are written and read through a [`crate::hashed::HashedTree`] holding the secret.
+/// Hashes can't be computed without the secret nor decoded, so encoding and decoding
+/// return [`Error::IllegalOperation`]; [`crate::Db::check`] only checks their length.
+#[cfg(feature = "keyed-hashing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashedKeyCodec<Inner = OrderedKeyCodec> {
+    codec: std::marker::PhantomData<Inner>,
+}
+
+/// Length of the keys stored by [`HashedKeyCodec`].
+#[cfg(feature = "keyed-hashing")]
+pub const HASHED_KEY_LEN: usize = 32;
+
+#[cfg(feature = "keyed-hashing")]
+impl<K, Inner: KeyCodec<K>> KeyCodec<K> for HashedKeyCodec<Inner> {
+    fn encode_key<C: Config>(_key: &K, _config: C) -> Result<Vec<u8>, Error> {
+        Err(Error::IllegalOperation)
+    }
+
+    fn decode_key<C: Config>(_bytes: &[u8], _config: C, _strict: bool) -> Result<K, Error> {
+        Err(Error::IllegalOperation)
+    }
+
+    fn validate_key<C: Config>(bytes: &[u8], _config: C) -> Result<(), Error> {
+        if bytes.len() != HASHED_KEY_LEN {
+            return Err(Error::InvalidKey("hashed keys are 32 bytes long"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that `key` decodes back into a key with the same encoding.
+pub(crate) fn check_round_trip<K, KC: KeyCodec<K>, C: Config>(
+    key: &K,
+    config: C,
+) -> Result<(), Error> {
+    let bytes = KC::encode_key(key, config)?;
+    let decoded = KC::decode_key(&bytes, config, false)?;
+
+    if KC::encode_key(&decoded, config)? != bytes {
+        return Err(Error::KeyRoundTrip);
+    }
+
+    Ok(())
+}
+
+/// A type with an encoding whose byte order matches the order of its values.
+pub trait OrderedKey: Sized {
+    /// Appends the encoding of `self` to `out`.
+    fn encode_ordered(&self, out: &mut Vec<u8>);
+    /// Decodes a value from the start of `input`, and advances `input` past it.
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error>;
+}
+
+pub fn to_ordered_bytes<K: OrderedKey>(key: &K) -> Vec<u8> {
+    let mut out = Vec::new();
+    key.encode_ordered(&mut out);
+    out
+}
+
+/// Decodes a key from `bytes`, which must contain nothing else.
+pub fn from_ordered_bytes<K: OrderedKey>(bytes: &[u8]) -> Result<K, Error> {
+    OrderedKeyCodec::decode_key(bytes, crate::BINCODE_CONFIG, true)
+}
+
+/// The bounds of the keys starting with the encoding of a value in `range`, or `None`
+/// if no key can be in it. No encoding of a key is a prefix of the encoding of another
+/// one, so the keys starting with it can be e.g. `(score, member)` composite keys.
+pub(crate) fn prefix_bounds<K: OrderedKey, R: RangeBounds<K>>(range: R) -> Option<BytesRange> {
+    let start = match range.start_bound() {
+        Included(key) => Included(to_ordered_bytes(key)),
+        Excluded(key) => Included(successor(to_ordered_bytes(key))?),
+        Unbounded => Unbounded,
+    };
+    let end = match range.end_bound() {
+        Included(key) => match successor(to_ordered_bytes(key)) {
+            Some(bytes) => Excluded(bytes),
+            None => Unbounded,
+        },
+        Excluded(key) => Excluded(to_ordered_bytes(key)),
+        Unbounded => Unbounded,
+    };
+
+    Some((start, end))
+}
+
+/// The smallest byte string greater than every string starting with `prefix`, or
+/// `None` if there is none.
+pub(crate) fn successor(mut prefix: Vec<u8>) -> Option<Vec<u8>> {
+    while let Some(last) = prefix.pop() {
+        if last < 0xff {
+            prefix.push(last + 1);
+            return Some(prefix);
+        }
+    }
+
+    None
+}
+
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+    if input.len() < n {
+        return Err(Error::InvalidKey("unexpected end of key"));
+    }
+
+    let (head, tail) = input.split_at(n);
+    *input = tail;
+    Ok(head)
+}
+
+macro_rules! ordered_unsigned {
+    ($($ty:ty),+) => {
+        $(
+            impl OrderedKey for $ty {
+                fn encode_ordered(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+                    let bytes = take(input, std::mem::size_of::<$ty>())?;
+                    Ok(<$ty>::from_be_bytes(bytes.try_into().expect("length was checked")))
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! ordered_signed {
+    ($($ty:ty => $unsigned:ty),+) => {
+        $(
+            impl OrderedKey for $ty {
+                fn encode_ordered(&self, out: &mut Vec<u8>) {
+                    // Flipping the sign bit puts negative numbers before positive ones
+                    ((*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1))).encode_ordered(out);
+                }
+
+                fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+                    let flipped = <$unsigned>::decode_ordered(input)?;
+                    Ok((flipped ^ (1 << (<$unsigned>::BITS - 1))) as $ty)
+                }
+            }
+        )+
+    };
+}
+
+ordered_unsigned!(u8, u16, u32, u64, u128);
+ordered_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+impl OrderedKey for usize {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode_ordered(out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        usize::try_from(u64::decode_ordered(input)?)
+            .map_err(|_| Error::InvalidKey("usize out of range"))
+    }
+}
+
+impl OrderedKey for isize {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode_ordered(out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        isize::try_from(i64::decode_ordered(input)?)
+            .map_err(|_| Error::InvalidKey("isize out of range"))
+    }
+}
+
+impl OrderedKey for bool {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode_ordered(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::InvalidKey("invalid bool")),
+        }
+    }
+}
+
+impl OrderedKey for char {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode_ordered(out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        char::from_u32(u32::decode_ordered(input)?).ok_or(Error::InvalidKey("invalid char"))
+    }
+}
+
+impl OrderedKey for () {
+    fn encode_ordered(&self, _out: &mut Vec<u8>) {}
+
+    fn decode_ordered(_input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xFF;
+const TERMINATOR: u8 = 0x00;
+
+fn encode_escaped(bytes: &[u8], out: &mut Vec<u8>) {
+    for &byte in bytes {
+        out.push(byte);
+
+        if byte == ESCAPE {
+            out.push(ESCAPED_ZERO);
+        }
+    }
+
+    out.extend_from_slice(&[ESCAPE, TERMINATOR]);
+}
+
+fn decode_escaped(input: &mut &[u8]) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+
+    loop {
+        match take(input, 1)?[0] {
+            ESCAPE => match take(input, 1)?[0] {
+                ESCAPED_ZERO => bytes.push(0),
+                TERMINATOR => return Ok(bytes),
+                _ => return Err(Error::InvalidKey("invalid escape sequence")),
+            },
+            byte => bytes.push(byte),
+        }
+    }
+}
+
+impl OrderedKey for Vec<u8> {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        encode_escaped(self, out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        decode_escaped(input)
+    }
+}
+
+impl OrderedKey for String {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        encode_escaped(self.as_bytes(), out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        String::from_utf8(decode_escaped(input)?).map_err(|_| Error::InvalidKey("invalid UTF-8"))
+    }
+}
+
+impl<const N: usize> OrderedKey for [u8; N] {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(take(input, N)?.try_into().expect("length was checked"))
+    }
+}
+
+impl<T: OrderedKey> OrderedKey for Option<T> {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                value.encode_ordered(out);
+            }
+        }
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode_ordered(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode_ordered(input)?)),
+            _ => Err(Error::InvalidKey("invalid option tag")),
+        }
+    }
+}
+
+macro_rules! ordered_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: OrderedKey),+> OrderedKey for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode_ordered(&self, out: &mut Vec<u8>) {
+                let ($($name,)+) = self;
+                $($name.encode_ordered(out);)+
+            }
+
+            fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+                Ok(($($name::decode_ordered(input)?,)+))
+            }
+        }
+    };
+}
+
+ordered_tuple!(A);
+ordered_tuple!(A, B);
+ordered_tuple!(A, B, C);
+ordered_tuple!(A, B, C, D);
+ordered_tuple!(A, B, C, D, E);
+ordered_tuple!(A, B, C, D, E, F);
+
+/// Descending order: the encoding of the wrapped key with every bit inverted, so that
+/// `first` and `iter` yield the largest keys first. This relies on no encoding being
+/// a prefix of another one, which holds for every [`OrderedKey`] implementation here.
+impl<K: OrderedKey> OrderedKey for Reverse<K> {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        self.0.encode_ordered(out);
+
+        for byte in &mut out[start..] {
+            *byte = !*byte;
+        }
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        // How many bytes belong to the key is only known once decoded
+        let inverted: Vec<u8> = input.iter().map(|byte| !byte).collect();
+        let mut rest = inverted.as_slice();
+        let key = K::decode_ordered(&mut rest)?;
+
+        *input = &input[inverted.len() - rest.len()..];
+        Ok(Reverse(key))
+    }
+}
+
+/// A float ordered like [`f64::total_cmp`]: `-NaN < -inf < ... < -0.0 < 0.0 < ... < inf < NaN`.
+/// Floats themselves aren't [`OrderedKey`]s, as they are only partially ordered. The
+/// encoding is the big-endian bits with the sign bit flipped for positive floats, and
+/// every bit flipped for negative ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderedFloat<F>(pub F);
+
+macro_rules! ordered_float {
+    ($($ty:ty => $bits:ty),+) => {
+        $(
+            impl PartialEq for OrderedFloat<$ty> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.cmp(other).is_eq()
+                }
+            }
+
+            impl Eq for OrderedFloat<$ty> {}
+
+            impl PartialOrd for OrderedFloat<$ty> {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for OrderedFloat<$ty> {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.total_cmp(&other.0)
+                }
+            }
+
+            impl OrderedKey for OrderedFloat<$ty> {
+                fn encode_ordered(&self, out: &mut Vec<u8>) {
+                    let bits = self.0.to_bits();
+                    let sign = 1 << (<$bits>::BITS - 1);
+                    let ordered = if bits & sign == 0 { bits ^ sign } else { !bits };
+                    ordered.encode_ordered(out);
+                }
+
+                fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+                    let ordered = <$bits>::decode_ordered(input)?;
+                    let sign = 1 << (<$bits>::BITS - 1);
+                    let bits = if ordered & sign != 0 { ordered ^ sign } else { !ordered };
+                    Ok(OrderedFloat(<$ty>::from_bits(bits)))
+                }
+            }
+        )+
+    };
+}
+
+ordered_float!(f32 => u32, f64 => u64);
+
+/// Builds the encoding of a composite key one component at a time, matching the
+/// encoding of the tuple of the same components. A partial key is a prefix of every
+/// key that starts with the same components, so it can be passed to `scan_prefix_bytes`
+/// to select e.g. all the events of one user in `(UserId, Timestamp)` keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompositeKey {
+    bytes: Vec<u8>,
+}
+
+impl CompositeKey {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a component to the key.
+    pub fn push<T: OrderedKey>(mut self, component: &T) -> Self {
+        component.encode_ordered(&mut self.bytes);
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for CompositeKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(any(feature = "ulid", feature = "uuid"))]
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).expect("the system random number generator should work");
+    bytes
+}
+
+/// Milliseconds since the Unix epoch, truncated to the 48 bits ULIDs and UUIDv7s hold.
+#[cfg(any(feature = "ulid", feature = "uuid"))]
+fn unix_millis() -> u64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    now.as_millis() as u64 & ((1 << 48) - 1)
+}
+
+#[cfg(feature = "ulid")]
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A ULID: a 48-bit millisecond timestamp followed by 80 random bits. Keys are stored
+/// as their 16 big-endian bytes, so a tree iterates them by creation time, newest last
+/// (or first with `iter().rev()`). ULIDs created in the same millisecond are in
+/// random order. Displayed and parsed as the usual 26 characters of Crockford base32.
+#[cfg(feature = "ulid")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UlidKey(pub u128);
+
+#[cfg(feature = "ulid")]
+impl UlidKey {
+    /// A new ULID with the current time.
+    pub fn new() -> Self {
+        Self::from_parts(unix_millis(), u128::from_be_bytes(random_bytes()))
+    }
+
+    /// Builds a ULID from a timestamp, of which only the lower 48 bits are kept, and
+    /// random bits, of which only the lower 80 bits are kept.
+    pub fn from_parts(timestamp_ms: u64, random: u128) -> Self {
+        let timestamp = (timestamp_ms as u128 & ((1 << 48) - 1)) << 80;
+
+        Self(timestamp | (random & ((1 << 80) - 1)))
+    }
+
+    /// The smallest ULID with this timestamp, to start a `range` at a point in time.
+    pub fn min_at(timestamp_ms: u64) -> Self {
+        Self::from_parts(timestamp_ms, 0)
+    }
+
+    pub fn timestamp_ms(&self) -> u64 {
+        (self.0 >> 80) as u64
+    }
+}
+
+#[cfg(feature = "ulid")]
+impl Default for UlidKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "ulid")]
+impl std::fmt::Display for UlidKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 26 characters of 5 bits hold 130 bits: the first one only holds 3
+        for i in 0..26 {
+            let digit = (self.0 >> (125 - 5 * i)) & 31;
+            write!(f, "{}", CROCKFORD[digit as usize] as char)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ulid")]
+impl std::str::FromStr for UlidKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if s.len() != 26 || s.as_bytes()[0] > b'7' {
+            return Err(Error::InvalidKey("invalid ULID"));
+        }
+
+        let mut value: u128 = 0;
+        for c in s.bytes() {
+            let digit = CROCKFORD
+                .iter()
+                .position(|&d| d == c.to_ascii_uppercase())
+                .ok_or(Error::InvalidKey("invalid ULID"))?;
+            value = value << 5 | digit as u128;
+        }
+
+        Ok(Self(value))
+    }
+}
+
+#[cfg(feature = "ulid")]
+impl OrderedKey for UlidKey {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        self.0.encode_ordered(out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Self(u128::decode_ordered(input)?))
+    }
+}
+
+/// A UUID, stored as its 16 bytes. Version 7 UUIDs, created with
+/// [`UuidKey::now_v7`], start with a millisecond timestamp, so a tree iterates them
+/// by creation time like [`UlidKey`]s. Displayed and parsed in the hyphenated form.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UuidKey(pub [u8; 16]);
+
+#[cfg(feature = "uuid")]
+impl UuidKey {
+    /// A new random (version 4) UUID.
+    pub fn new_v4() -> Self {
+        Self::with_version(random_bytes(), 4)
+    }
+
+    /// A new time-ordered (version 7) UUID with the current time.
+    pub fn now_v7() -> Self {
+        let mut bytes: [u8; 16] = random_bytes();
+        bytes[..6].copy_from_slice(&unix_millis().to_be_bytes()[2..]);
+
+        Self::with_version(bytes, 7)
+    }
+
+    /// Sets the version and the RFC 4122 variant bits.
+    fn with_version(mut bytes: [u8; 16], version: u8) -> Self {
+        bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Self(bytes)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.0[6] >> 4
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl std::fmt::Display for UuidKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl std::str::FromStr for UuidKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let well_formed = s.len() == 36
+            && s.bytes().enumerate().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => c == b'-',
+                _ => c.is_ascii_hexdigit(),
+            });
+        if !well_formed {
+            return Err(Error::InvalidKey("invalid UUID"));
+        }
+
+        let hex: Vec<u8> = s.bytes().filter(|&c| c != b'-').collect();
+        let mut bytes = [0; 16];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            let pair = std::str::from_utf8(pair).expect("hex digits are ASCII");
+            *byte = u8::from_str_radix(pair, 16).expect("hex digits were checked");
+        }
+
+        Ok(Self(bytes))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl OrderedKey for UuidKey {
+    fn encode_ordered(&self, out: &mut Vec<u8>) {
+        self.0.encode_ordered(out);
+    }
+
+    fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Self(<[u8; 16]>::decode_ordered(input)?))
+    }
+}
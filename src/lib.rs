@@ -18,23 +18,91 @@ use error::Error;
 #[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Serialize};
 
+/// The bincode configuration used by trees unless told otherwise.
+pub type DefaultConfig = bincode::config::Configuration<bincode::config::BigEndian>;
+
 /// Sled is optimised to work with big-endian bytes
 /// See <https://github.com/spacejam/sled?tab=readme-ov-file#a-note-on-lexicographic-ordering-and-endianness>
-pub const BINCODE_CONFIG: bincode::config::Configuration<bincode::config::BigEndian> =
-    bincode::config::standard().with_big_endian();
+pub const BINCODE_CONFIG: DefaultConfig = bincode::config::standard().with_big_endian();
+
+#[cfg(feature = "derive")]
+pub use ser_sled_derive::SerSledSchema;
+
+// Lets the code generated by the derive macros refer to `::ser_sled` in this crate too.
+extern crate self as ser_sled;
 
 use sled::IVec;
+use std::collections::HashMap;
 use std::ops::RangeBounds;
+use std::sync::{Arc, Mutex};
 
+pub mod admin;
+pub mod aggregate;
+#[cfg(feature = "serde")]
+pub mod any_tree;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "tokio")]
+pub mod async_tree;
+pub mod backend;
 pub mod bincode_tree;
+pub mod cache;
+pub mod capped_log;
+pub mod check;
+pub mod codec;
+pub mod counted;
+pub mod cursor;
+pub mod db_config;
+pub mod diff;
+pub mod document;
+pub mod dyn_tree;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
+pub mod event_log;
+pub mod export;
+#[cfg(feature = "keyed-hashing")]
+pub mod hashed;
+pub mod health;
+pub mod index;
+pub mod job_queue;
+pub mod journal;
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+pub mod keys;
+pub mod migration;
+mod multi_tree;
+pub mod patch;
+pub mod projection;
+pub mod query;
+pub mod quota;
+pub mod schema;
+pub mod scoped;
 #[cfg(feature = "serde")]
 pub mod serde_tree;
+pub mod set;
+pub mod snapshot;
+pub mod sorted_set;
+pub mod subscriber;
+pub mod tagged;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tests;
+#[cfg(feature = "text-index")]
+pub mod text_index;
+pub mod time_series;
+pub mod tree;
+pub mod ttl;
+pub mod value_ref;
+pub mod view;
 
 impl From<sled::Db> for Db {
     fn from(value: sled::Db) -> Self {
-        Self { inner_db: value }
+        Self {
+            inner_db: value,
+            recovery_hook: Arc::new(Mutex::new(None)),
+            tables: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 }
 
@@ -59,29 +127,506 @@ impl<T: Encode + Decode> TryInto<IVec> for BincodeItem<T> {
     }
 }
 
+/// A hook run by [`Db::on_recovered`].
+pub type RecoveryHook = Arc<dyn Fn(&Db) -> Result<(), Error> + Send + Sync>;
+
 #[derive(Clone)]
 pub struct Db {
     pub inner_db: sled::Db,
+    recovery_hook: Arc<Mutex<Option<RecoveryHook>>>,
+    tables: Arc<Mutex<HashMap<String, check::TableValidator>>>,
 }
 
 impl Db {
+    /// Opens the database stored at `path`, creating it if needed.
+    /// See [`db_config::DbConfig`] for other settings.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        db_config::DbConfig::new().path(path).open()
+    }
+
+    /// Opens a database in a temporary directory, deleted when it is dropped.
+    pub fn open_temporary() -> Result<Self, Error> {
+        db_config::DbConfig::new().temporary(true).open()
+    }
+
     pub fn generate_id(&self) -> Result<u64, Error> {
         Ok(self.inner_db.generate_id()?)
     }
 
+    /// Writes the dirty data of every tree to disk, returning how many bytes were
+    /// flushed. Call it before exiting if data written since the last automatic
+    /// flush must not be lost.
+    pub fn flush(&self) -> Result<usize, Error> {
+        Ok(self.inner_db.flush()?)
+    }
+
+    /// Asynchronous version of [`Db::flush`].
+    pub async fn flush_async(&self) -> Result<usize, Error> {
+        Ok(self.inner_db.flush_async().await?)
+    }
+
+    /// Size of the database files, in bytes.
+    pub fn size_on_disk(&self) -> Result<u64, Error> {
+        Ok(self.inner_db.size_on_disk()?)
+    }
+
+    /// Returns `true` if the database was opened from existing files rather than
+    /// created, which includes recovering after an unclean shutdown.
+    pub fn was_recovered(&self) -> bool {
+        self.inner_db.was_recovered()
+    }
+
+    /// Registers a hook (journal replay, index rebuild, verification scan...) that runs
+    /// once, before the next `open_*` call returns, if the database [was recovered](Db::was_recovered).
+    /// If the hook fails, that `open_*` call returns its error and the hook runs again
+    /// on the next one. The hook may open trees itself.
+    pub fn on_recovered<F>(&self, hook: F)
+    where
+        F: Fn(&Db) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        if self.was_recovered() {
+            *self.recovery_hook.lock().expect("lock isn't poisoned") = Some(Arc::new(hook));
+        }
+    }
+
+    /// Walks every tree and checks that its entries decode as the key and value types
+    /// of the strict tree last opened under that name. Trees that were never opened as
+    /// a strict tree are only counted. With [`check::ReportLevel::Repair`], failing
+    /// entries are moved to a `<tree>_quarantine` tree.
+    pub fn check(&self, level: check::ReportLevel) -> Result<check::CheckReport, Error> {
+        check::check(self, level)
+    }
+
+    /// Returns the fingerprint of the types the strict tree `tree_name` was first
+    /// opened with, if it was.
+    pub fn schema(&self, tree_name: &str) -> Result<Option<String>, Error> {
+        let schemas = self.inner_db.open_tree(schema::SCHEMA_TREE)?;
+
+        Ok(schemas
+            .get(tree_name)?
+            .map(|stored| String::from_utf8_lossy(&stored).into_owned()))
+    }
+
+    /// Forgets the fingerprint of `tree_name`, so that the next strict tree opened
+    /// under that name records its own. Use it after migrating a tree to new types,
+    /// or when a type was moved or renamed.
+    pub fn reset_schema(&self, tree_name: &str) -> Result<(), Error> {
+        let schemas = self.inner_db.open_tree(schema::SCHEMA_TREE)?;
+        schemas.remove(tree_name)?;
+
+        Ok(())
+    }
+
+    /// Streams every entry of `src` through `map_fn` into `dst`, which can use other
+    /// key and value types and codecs, `batch_size` entries at a time. Returns how many
+    /// entries were copied.
+    ///
+    /// Each batch is written atomically along with the last source key it covers, so
+    /// calling this again after an interruption or a decode error resumes after the last
+    /// written batch. Once every entry has been copied, progress is forgotten and a new
+    /// call starts over. `src` is left untouched.
+    pub fn migrate_tree<Kold, Vold, Knew, Vnew, VC1, C1, KC1, VC2, C2, KC2, F>(
+        &self,
+        src: &tree::Tree<Kold, Vold, VC1, C1, KC1>,
+        dst: &tree::Tree<Knew, Vnew, VC2, C2, KC2>,
+        map_fn: F,
+        batch_size: usize,
+    ) -> Result<usize, Error>
+    where
+        VC1: codec::ValueCodec<Vold>,
+        C1: bincode::config::Config,
+        KC1: keys::KeyCodec<Kold>,
+        VC2: codec::ValueCodec<Vnew>,
+        C2: bincode::config::Config,
+        KC2: keys::KeyCodec<Knew>,
+        F: FnMut(Kold, Vold) -> (Knew, Vnew),
+    {
+        let progress = self.inner_db.open_tree(migration::MIGRATION_TREE)?;
+
+        src.migrate_into(dst, &progress, map_fn, batch_size.max(1))
+    }
+
+    /// Copies every entry of the tree `src` into the tree `dst` as raw bytes, in
+    /// batches of [`admin::COPY_BATCH_SIZE`], and returns how many were copied.
+    /// Entries already in `dst` are kept unless `src` has the same key.
+    pub fn copy_tree(&self, src: &str, dst: &str) -> Result<usize, Error> {
+        admin::copy_entries(
+            &self.inner_db.open_tree(src)?,
+            &self.inner_db.open_tree(dst)?,
+        )
+    }
+
+    /// Moves every entry of the tree `old` to the tree `new` and drops `old`, returning
+    /// how many entries were moved. `new` must be empty, otherwise this returns
+    /// [`Error::IllegalOperation`]. Trees already opened under either name must be
+    /// opened again.
+    pub fn rename_tree(&self, old: &str, new: &str) -> Result<usize, Error> {
+        admin::rename_tree(self, old, new)
+    }
+
+    /// Drops the tree `name` and forgets its schema. Returns `false` if there was no
+    /// such tree. Trees already opened under that name must not be used afterwards.
+    pub fn drop_tree(&self, name: &str) -> Result<bool, Error> {
+        admin::drop_tree(self, name)
+    }
+
+    /// Names of every tree in the database, including the ones used internally by
+    /// this crate such as [`schema::SCHEMA_TREE`], but not sled's default tree.
+    pub fn tree_names(&self) -> Vec<String> {
+        admin::tree_names(self)
+    }
+
+    /// Writes every tree of the database to the file at `path`, along with the schema
+    /// fingerprints of strict trees, and returns how many entries were written.
+    /// See [`export`] for the format.
+    pub fn export_to<P: AsRef<std::path::Path>>(&self, path: P) -> Result<usize, Error> {
+        export::export_to(self, path.as_ref())
+    }
+
+    /// Restores an export written by [`Db::export_to`] and returns how many entries were
+    /// restored. The database must be empty, otherwise this returns
+    /// [`Error::IllegalOperation`].
+    pub fn import_from<P: AsRef<std::path::Path>>(&self, path: P) -> Result<usize, Error> {
+        export::import_from(self, path.as_ref())
+    }
+
+    /// Writes every tree of the database, with the same metadata as [`Db::export_to`],
+    /// to a single zstd-compressed and checksummed file at `path`. Returns how many
+    /// entries were written.
+    #[cfg(feature = "archive")]
+    pub fn backup_to_archive<P: AsRef<std::path::Path>>(&self, path: P) -> Result<usize, Error> {
+        archive::backup(self, path.as_ref())
+    }
+
+    /// Restores an archive written by [`Db::backup_to_archive`] into this database,
+    /// which must be empty, and returns how many entries were restored. The whole
+    /// archive is checked first, so nothing is restored from a corrupted archive.
+    #[cfg(feature = "archive")]
+    pub fn restore_from_archive<P: AsRef<std::path::Path>>(&self, path: P) -> Result<usize, Error> {
+        archive::restore(self, path.as_ref())
+    }
+
+    /// Applies changes exported by [`journal::JournaledTree::export_changes_since`] to
+    /// the tree of this database with the same name. Returns the sequence number of the
+    /// last change applied, or `None` if there was none.
+    pub fn replay_changes<R: std::io::Read>(&self, reader: R) -> Result<Option<u64>, Error> {
+        journal::replay_changes(self, reader)
+    }
+
+    fn register_table(&self, tree_name: &str, validator: check::TableValidator) {
+        self.tables
+            .lock()
+            .expect("lock isn't poisoned")
+            .insert(tree_name.to_string(), validator);
+    }
+
+    fn move_table(&self, old: &str, new: &str) {
+        let mut tables = self.tables.lock().expect("lock isn't poisoned");
+
+        match tables.remove(old) {
+            Some(validator) => tables.insert(new.to_string(), validator),
+            None => tables.remove(new),
+        };
+    }
+
+    fn forget_table(&self, tree_name: &str) {
+        self.tables
+            .lock()
+            .expect("lock isn't poisoned")
+            .remove(tree_name);
+    }
+
+    fn table_validator(&self, tree_name: &str) -> Option<check::TableValidator> {
+        self.tables
+            .lock()
+            .expect("lock isn't poisoned")
+            .get(tree_name)
+            .copied()
+    }
+
+    fn open_sled_tree(&self, tree_name: &str) -> Result<sled::Tree, Error> {
+        // Take the hook out first so that it can open trees without deadlocking
+        let hook = self
+            .recovery_hook
+            .lock()
+            .expect("lock isn't poisoned")
+            .take();
+
+        if let Some(hook) = hook {
+            if let Err(e) = hook(self) {
+                self.recovery_hook
+                    .lock()
+                    .expect("lock isn't poisoned")
+                    .get_or_insert(hook);
+                return Err(e);
+            }
+        }
+
+        Ok(self.inner_db.open_tree(tree_name)?)
+    }
+
+    /// Opens a dedicated tree, writes, reads back and deletes a probe entry, then
+    /// flushes, timing every step. Meant to back readiness endpoints.
+    pub fn health_check(&self) -> health::HealthReport {
+        health::health_check(self)
+    }
+
     pub fn open_relaxed_bincode_tree(&self, tree_name: &str) -> Result<RelaxedTree, Error> {
-        let tree = self.inner_db.open_tree(tree_name)?;
+        let tree = self.open_sled_tree(tree_name)?;
 
         Ok(RelaxedTree::new(tree))
     }
 
-    pub fn open_bincode_tree<K: Encode + Decode, V: Encode + Decode>(
+    pub fn open_bincode_tree<K: keys::OrderedKey, V: Encode + Decode>(
         &self,
         tree_name: &str,
     ) -> Result<BincodeTree<K, V>, Error> {
-        let tree = self.inner_db.open_tree(tree_name)?;
+        self.open_bincode_tree_with_key_codec(tree_name)
+    }
+
+    /// Opens a [`BincodeTree`] whose keys are encoded with `KC`, such as
+    /// [`keys::BincodeKeyCodec`] for trees written by earlier versions.
+    pub fn open_bincode_tree_with_key_codec<K, V: Encode + Decode, KC: keys::KeyCodec<K>>(
+        &self,
+        tree_name: &str,
+    ) -> Result<BincodeTree<K, V, DefaultConfig, KC>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::Tree`] whose values are encoded with `VC` and keys with `KC`,
+    /// for formats other than bincode and serde.
+    pub fn open_tree<K, V, VC: codec::ValueCodec<V>, KC: keys::KeyCodec<K>>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::Tree<K, V, VC, DefaultConfig, KC>, Error> {
+        self.open_tree_with_config(tree_name, BINCODE_CONFIG)
+    }
+
+    /// Opens a [`BincodeTree`] whose values are encoded with the bincode configuration
+    /// `config` instead of [`BINCODE_CONFIG`].
+    pub fn open_bincode_tree_with_config<K: keys::OrderedKey, V: Encode + Decode, C>(
+        &self,
+        tree_name: &str,
+        config: C,
+    ) -> Result<BincodeTree<K, V, C>, Error>
+    where
+        C: bincode::config::Config + Default,
+    {
+        self.open_tree_with_config(tree_name, config)
+    }
+
+    /// Like [`Db::open_tree`], with the bincode configuration `config`. Value codecs
+    /// that don't use bincode ignore it, as does [`keys::OrderedKeyCodec`]: use
+    /// [`keys::BincodeKeyCodec`] to encode keys with it, e.g. as fixed-size integers.
+    pub fn open_tree_with_config<K, V, VC, KC, C>(
+        &self,
+        tree_name: &str,
+        config: C,
+    ) -> Result<tree::Tree<K, V, VC, C, KC>, Error>
+    where
+        VC: codec::ValueCodec<V>,
+        KC: keys::KeyCodec<K>,
+        C: bincode::config::Config + Default,
+    {
+        let tree = self.open_sled_tree(tree_name)?;
+        schema::check_fingerprint(self, tree_name, schema::fingerprint::<K, V>())?;
+        self.register_table(tree_name, tree::validate_entry::<K, V, VC, C, KC>);
+
+        Ok(tree::Tree::<K, V, VC, DefaultConfig, KC>::new(tree).with_config(config))
+    }
+
+    /// Opens a [`BincodeTree`] whose methods run on tokio's blocking pool.
+    #[cfg(feature = "tokio")]
+    pub fn open_async_bincode_tree<K, V>(
+        &self,
+        tree_name: &str,
+    ) -> Result<async_tree::AsyncBincodeTree<K, V>, Error>
+    where
+        K: keys::OrderedKey + Clone + Send + Sync + 'static,
+        V: Encode + Decode + Clone + Send + Sync + 'static,
+    {
+        Ok(self.open_bincode_tree(tree_name)?.into())
+    }
+
+    /// Opens an event log and its snapshots, stored in the `tree_name` and
+    /// `<tree_name>_snapshots` trees.
+    pub fn open_aggregate_store<E: Encode + Decode, S: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<aggregate::AggregateStore<E, S>, Error> {
+        let events = self.open_bincode_tree(tree_name)?;
+        let snapshots = self.open_bincode_tree(&aggregate::snapshots_name(tree_name))?;
+
+        Ok(aggregate::AggregateStore::new(
+            self.clone(),
+            events,
+            snapshots,
+        ))
+    }
+
+    /// Opens a [`cache::CacheTree`] holding at most `capacity` entries in the
+    /// `tree_name` tree, and their access times in the `<tree_name>_recency` tree.
+    pub fn open_cache_tree<K: keys::OrderedKey, V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+        capacity: u64,
+    ) -> Result<cache::CacheTree<K, V>, Error> {
+        let tree = self.open_bincode_tree(tree_name)?;
+        let recency = self.open_sled_tree(&cache::recency_name(tree_name))?;
+
+        cache::CacheTree::new(self.clone(), tree, recency, capacity)
+    }
+
+    /// Opens a [`capped_log::CappedLog`] storing at most `max_entries` entries in the
+    /// `tree_name` tree.
+    pub fn open_capped_log<V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+        max_entries: u64,
+    ) -> Result<capped_log::CappedLog<V>, Error> {
+        let log = self.open_bincode_tree(tree_name)?;
+        let usage = self.inner_db.open_tree(capped_log::CAPPED_LOGS_TREE)?;
+
+        capped_log::CappedLog::new(self.clone(), log, usage, max_entries)
+    }
+
+    /// Opens an [`event_log::EventLog`] storing its events in the `tree_name` tree.
+    pub fn open_event_log<V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<event_log::EventLog<V>, Error> {
+        Ok(event_log::EventLog::new(
+            self.clone(),
+            self.open_bincode_tree(tree_name)?,
+        ))
+    }
+
+    /// Opens a [`set::Set`] storing its members as the keys of the `tree_name` tree.
+    pub fn open_set<T: keys::OrderedKey>(&self, tree_name: &str) -> Result<set::Set<T>, Error> {
+        self.open_set_with_key_codec(tree_name)
+    }
+
+    /// Opens a [`set::Set`] whose members are encoded with `KC`, such as
+    /// [`keys::BincodeKeyCodec`] for types that only implement `Encode` and `Decode`.
+    pub fn open_set_with_key_codec<T, KC: keys::KeyCodec<T>>(
+        &self,
+        tree_name: &str,
+    ) -> Result<set::Set<T, KC>, Error> {
+        Ok(set::Set::new(self.open_tree(tree_name)?))
+    }
+
+    /// Opens a [`sorted_set::SortedSet`], storing the score of every member in the
+    /// `tree_name` tree and the members by score in the `<tree_name>_by_score` tree.
+    pub fn open_sorted_set<M: keys::OrderedKey + Clone, S: keys::OrderedKey + Clone>(
+        &self,
+        tree_name: &str,
+    ) -> Result<sorted_set::SortedSet<M, S>, Error> {
+        let members = self.open_sled_tree(tree_name)?;
+        let by_score = self.open_sled_tree(&sorted_set::by_score_name(tree_name))?;
+
+        Ok(sorted_set::SortedSet::new(members, by_score))
+    }
+
+    /// Opens a [`snapshot::SnapshotStore`] storing its snapshots in the `tree_name` tree.
+    pub fn open_snapshot_store<S: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<snapshot::SnapshotStore<S>, Error> {
+        Ok(snapshot::SnapshotStore::new(
+            self.open_bincode_tree(tree_name)?,
+        ))
+    }
+
+    /// Opens a [`tagged::TaggedTree`] storing values of different types in the
+    /// `tree_name` tree.
+    pub fn open_tagged_tree<K: keys::OrderedKey>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tagged::TaggedTree<K>, Error> {
+        Ok(tagged::TaggedTree::new(self.open_sled_tree(tree_name)?))
+    }
 
-        Ok(BincodeTree::new(tree))
+    /// Opens a [`ttl::TtlTree`] storing its entries in the `tree_name` tree and their
+    /// deadlines in the `<tree_name>_expiries` tree.
+    pub fn open_ttl_tree<K: keys::OrderedKey, V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<ttl::TtlTree<K, V>, Error> {
+        let tree = self.open_bincode_tree(tree_name)?;
+        let expiries = self.open_sled_tree(&ttl::expiries_name(tree_name))?;
+
+        Ok(ttl::TtlTree::new(tree, expiries))
+    }
+
+    /// Opens a [`document::DocumentStore`] storing its documents in the `tree_name`
+    /// tree, and its indexes like an [`index::IndexedTree`].
+    pub fn open_document_store<V: Encode + Decode + Clone>(
+        &self,
+        tree_name: &str,
+    ) -> Result<document::DocumentStore<V>, Error> {
+        Ok(document::DocumentStore::new(
+            self.clone(),
+            self.open_indexed_tree(tree_name)?,
+        ))
+    }
+
+    /// Opens an [`index::IndexedTree`] storing its entries in the `tree_name` tree and
+    /// each of its indexes in a `<tree_name>_index_<index name>` tree.
+    pub fn open_indexed_tree<K: keys::OrderedKey, V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<index::IndexedTree<K, V>, Error> {
+        Ok(index::IndexedTree::new(
+            self.clone(),
+            tree_name,
+            self.open_bincode_tree(tree_name)?,
+        ))
+    }
+
+    /// Opens a [`job_queue::JobQueue`], whose jobs wait in the `tree_name` tree and
+    /// are moved to the `<tree_name>_in_flight` tree while claimed, or to the
+    /// `<tree_name>_dead_letters` tree if they fail to decode.
+    pub fn open_job_queue<V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<job_queue::JobQueue<V>, Error> {
+        let pending = self.open_sled_tree(tree_name)?;
+        let in_flight = self.open_sled_tree(&job_queue::in_flight_name(tree_name))?;
+        let dead_letters = self.open_sled_tree(&job_queue::dead_letters_name(tree_name))?;
+
+        Ok(job_queue::JobQueue::new(
+            self.clone(),
+            pending,
+            in_flight,
+            dead_letters,
+        ))
+    }
+
+    /// Opens a [`time_series::TimeSeriesTree`] storing its points in the `tree_name` tree.
+    pub fn open_time_series_tree<V: Encode + Decode>(
+        &self,
+        tree_name: &str,
+    ) -> Result<time_series::TimeSeriesTree<V>, Error> {
+        Ok(time_series::TimeSeriesTree::new(
+            self.open_bincode_tree(tree_name)?,
+        ))
+    }
+
+    /// Opens a [`migration::VersionedTree`], whose values are upgraded with `registry`.
+    /// Its value type changes as new versions are registered, so unlike other strict
+    /// trees it doesn't record a schema fingerprint.
+    pub fn open_versioned_tree<K, V, VC, KC>(
+        &self,
+        tree_name: &str,
+        registry: migration::MigrationRegistry<V, VC>,
+    ) -> Result<migration::VersionedTree<K, V, VC, KC>, Error>
+    where
+        VC: codec::ValueCodec<V>,
+        KC: keys::KeyCodec<K>,
+    {
+        let tree = self.open_sled_tree(tree_name)?;
+
+        Ok(migration::VersionedTree::new(tree, registry))
     }
 
     #[cfg(feature = "serde")]
@@ -89,19 +634,171 @@ impl Db {
         &self,
         tree_name: &str,
     ) -> Result<serde_tree::RelaxedTree, Error> {
-        let tree = self.inner_db.open_tree(tree_name)?;
+        let tree = self.open_sled_tree(tree_name)?;
 
         Ok(serde_tree::RelaxedTree::new(tree))
     }
 
     #[cfg(feature = "serde")]
-    pub fn open_serde_tree<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(
+    pub fn open_serde_tree<K: keys::OrderedKey, V: Serialize + DeserializeOwned>(
         &self,
         tree_name: &str,
     ) -> Result<serde_tree::SerdeTree<K, V>, Error> {
-        let tree = self.inner_db.open_tree(tree_name)?;
+        self.open_serde_tree_with_key_codec(tree_name)
+    }
+
+    /// Opens a [`serde_tree::SerdeTree`] whose keys are encoded with `KC`, such as
+    /// [`keys::SerdeKeyCodec`] for trees written by earlier versions.
+    #[cfg(feature = "serde")]
+    pub fn open_serde_tree_with_key_codec<
+        K,
+        V: Serialize + DeserializeOwned,
+        KC: keys::KeyCodec<K>,
+    >(
+        &self,
+        tree_name: &str,
+    ) -> Result<serde_tree::SerdeTree<K, V, DefaultConfig, KC>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`serde_tree::SerdeTree`] whose values are encoded with the bincode
+    /// configuration `config` instead of [`BINCODE_CONFIG`].
+    #[cfg(feature = "serde")]
+    pub fn open_serde_tree_with_config<K: keys::OrderedKey, V: Serialize + DeserializeOwned, C>(
+        &self,
+        tree_name: &str,
+        config: C,
+    ) -> Result<serde_tree::SerdeTree<K, V, C>, Error>
+    where
+        C: bincode::config::Config + Default,
+    {
+        self.open_tree_with_config(tree_name, config)
+    }
+
+    /// Opens a [`tree::MessagePackTree`], whose values are stored as MessagePack.
+    #[cfg(feature = "messagepack")]
+    pub fn open_messagepack_tree<K: keys::OrderedKey, V: Serialize + DeserializeOwned>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::MessagePackTree<K, V>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::CborTree`], whose values are stored as CBOR.
+    #[cfg(feature = "cbor")]
+    pub fn open_cbor_tree<K: keys::OrderedKey, V: Serialize + DeserializeOwned>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::CborTree<K, V>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::PostcardTree`], whose values are stored with postcard.
+    #[cfg(feature = "postcard")]
+    pub fn open_postcard_tree<K: keys::OrderedKey, V: Serialize + DeserializeOwned>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::PostcardTree<K, V>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::BorshTree`], whose keys and values are stored with borsh.
+    /// Range queries follow the order of the encoded keys, see [`keys::BorshKeyCodec`].
+    #[cfg(feature = "borsh")]
+    pub fn open_borsh_tree<K, V>(&self, tree_name: &str) -> Result<tree::BorshTree<K, V>, Error>
+    where
+        K: borsh::BorshSerialize + borsh::BorshDeserialize,
+        V: borsh::BorshSerialize + borsh::BorshDeserialize,
+    {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::ProtoTree`], whose values are stored as protobuf.
+    #[cfg(feature = "prost")]
+    pub fn open_proto_tree<K: keys::OrderedKey, V: prost::Message + Default>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::ProtoTree<K, V>, Error> {
+        self.open_tree(tree_name)
+    }
+
+    /// Opens a [`tree::Bincode1Tree`], for trees written with bincode 1.x and serde.
+    #[cfg(feature = "bincode1-compat")]
+    pub fn open_bincode1_tree<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(
+        &self,
+        tree_name: &str,
+    ) -> Result<tree::Bincode1Tree<K, V>, Error> {
+        self.open_tree_with_config(tree_name, bincode::config::legacy())
+    }
+
+    /// Opens a relaxed tree reading and writing the bincode 1.x format.
+    #[cfg(feature = "bincode1-compat")]
+    pub fn open_relaxed_bincode1_tree(
+        &self,
+        tree_name: &str,
+    ) -> Result<serde_tree::RelaxedTree<tree::LegacyConfig>, Error> {
+        Ok(self
+            .open_relaxed_serde_tree(tree_name)?
+            .with_config(bincode::config::legacy()))
+    }
 
-        Ok(serde_tree::SerdeTree::new(tree))
+    /// Opens a strict tree using the codec picked at runtime, e.g. from configuration.
+    #[cfg(feature = "serde")]
+    pub fn open_any_tree<K: keys::OrderedKey, V: any_tree::AnyItem>(
+        &self,
+        tree_name: &str,
+        codec: any_tree::Codec,
+    ) -> Result<any_tree::AnyTree<K, V>, Error> {
+        Ok(match codec {
+            any_tree::Codec::Bincode => {
+                any_tree::AnyTree::Bincode(self.open_bincode_tree(tree_name)?)
+            }
+            any_tree::Codec::Serde => any_tree::AnyTree::Serde(self.open_serde_tree(tree_name)?),
+        })
+    }
+}
+
+/// Range over encoded keys.
+pub(crate) type BytesRange = (std::ops::Bound<Vec<u8>>, std::ops::Bound<Vec<u8>>);
+
+/// Atomically removes up to `n` entries from one end of `tree`, in iteration order.
+/// If another writer removes one of the candidates first, the pop starts over.
+pub(crate) fn pop_n(
+    tree: &sled::Tree,
+    n: usize,
+    from_max: bool,
+) -> Result<Vec<(IVec, IVec)>, Error> {
+    use sled::transaction::{ConflictableTransactionError, TransactionError};
+
+    loop {
+        let keys = if from_max {
+            tree.iter()
+                .keys()
+                .rev()
+                .take(n)
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            tree.iter().keys().take(n).collect::<Result<Vec<_>, _>>()?
+        };
+
+        let res = tree.transaction(|tx| {
+            let mut popped = Vec::with_capacity(keys.len());
+
+            for key in &keys {
+                match tx.remove(key.clone())? {
+                    Some(value) => popped.push((key.clone(), value)),
+                    None => return Err(ConflictableTransactionError::Abort(())),
+                }
+            }
+
+            Ok(popped)
+        });
+
+        match res {
+            Ok(popped) => return Ok(popped),
+            Err(TransactionError::Abort(())) => continue,
+            Err(TransactionError::Storage(e)) => return Err(e.into()),
+        }
     }
 }
 
@@ -118,6 +815,11 @@ pub trait StrictTree<Key, Value> {
     fn first(&self) -> Result<Option<(Key, Value)>, Error>;
     fn last(&self) -> Result<Option<(Key, Value)>, Error>;
     fn pop_max(&self) -> Result<Option<(Key, Value)>, Error>;
+    fn pop_min(&self) -> Result<Option<(Key, Value)>, Error>;
+    /// Atomically removes and returns up to `n` entries with the smallest keys, in order.
+    fn pop_n_min(&self, n: usize) -> Result<Vec<(Key, Value)>, Error>;
+    /// Atomically removes and returns up to `n` entries with the largest keys, largest first.
+    fn pop_n_max(&self, n: usize) -> Result<Vec<(Key, Value)>, Error>;
     fn iter(&self) -> impl DoubleEndedIterator<Item = (Key, Value)>;
     fn range_key_bytes<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
@@ -130,7 +832,29 @@ pub trait StrictTree<Key, Value> {
     fn clear(&self) -> Result<(), Error>;
     fn contains_key(&self, key: &Key) -> Result<bool, Error>;
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn remove(&self, key: &Key) -> Result<Option<Value>, Error>;
+
+    /// Like [`StrictTree::get_or_init`], but returns the value itself.
+    fn get_or_insert_with<F: FnOnce() -> Value>(
+        &self,
+        key: Key,
+        init_func: F,
+    ) -> Result<Value, Error> {
+        Ok(self
+            .get_or_init(key, init_func)?
+            .expect("get_or_init always returns a value"))
+    }
+
+    /// Turns the tree into a read-only [`view::MappedView`] whose values go through `map`.
+    fn map_view<W, F: Fn(Value) -> W>(self, map: F) -> view::MappedView<Self, Key, Value, F>
+    where
+        Self: Sized,
+    {
+        view::MappedView::new(self, map)
+    }
 }
 
 /// A relaxed tree structure that allows any serde key or value type
@@ -153,6 +877,15 @@ pub trait RelaxedSerdeTree {
     fn first<K: DeserializeOwned, V: DeserializeOwned>(&self) -> Result<Option<(K, V)>, Error>;
     fn last<K: DeserializeOwned, V: DeserializeOwned>(&self) -> Result<Option<(K, V)>, Error>;
     fn pop_max<K: DeserializeOwned, V: DeserializeOwned>(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_min<K: DeserializeOwned, V: DeserializeOwned>(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_n_min<K: DeserializeOwned, V: DeserializeOwned>(
+        &self,
+        n: usize,
+    ) -> Result<Vec<(K, V)>, Error>;
+    fn pop_n_max<K: DeserializeOwned, V: DeserializeOwned>(
+        &self,
+        n: usize,
+    ) -> Result<Vec<(K, V)>, Error>;
     fn iter<K: DeserializeOwned, V: DeserializeOwned>(
         &self,
     ) -> impl DoubleEndedIterator<Item = (K, V)>;
@@ -167,7 +900,21 @@ pub trait RelaxedSerdeTree {
     fn clear(&self) -> Result<(), Error>;
     fn contains_key<K: Serialize>(&self, key: &K) -> Result<bool, Error>;
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn remove<K: Serialize, V: DeserializeOwned>(&self, key: &K) -> Result<Option<V>, Error>;
+
+    /// Like [`RelaxedSerdeTree::get_or_init`], but returns the value itself.
+    fn get_or_insert_with<F: FnOnce() -> T, K: Serialize, T: Serialize + DeserializeOwned>(
+        &self,
+        key: K,
+        init_func: F,
+    ) -> Result<T, Error> {
+        Ok(self
+            .get_or_init(key, init_func)?
+            .expect("get_or_init always returns a value"))
+    }
 }
 
 /// A relaxed tree structure that allows any bincode key or value type
@@ -186,6 +933,9 @@ pub trait RelaxedBincodeTree {
     fn first<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error>;
     fn last<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error>;
     fn pop_max<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_min<K: Decode, V: Decode>(&self) -> Result<Option<(K, V)>, Error>;
+    fn pop_n_min<K: Decode, V: Decode>(&self, n: usize) -> Result<Vec<(K, V)>, Error>;
+    fn pop_n_max<K: Decode, V: Decode>(&self, n: usize) -> Result<Vec<(K, V)>, Error>;
     fn iter<K: Decode, V: Decode>(&self) -> impl DoubleEndedIterator<Item = (K, V)>;
     fn range_key_bytes<K: AsRef<[u8]>, R: RangeBounds<K>, V: Decode>(
         &self,
@@ -198,5 +948,19 @@ pub trait RelaxedBincodeTree {
     fn clear(&self) -> Result<(), Error>;
     fn contains_key<K: Encode>(&self, key: &K) -> Result<bool, Error>;
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn remove<K: Encode, V: Decode>(&self, key: &K) -> Result<Option<V>, Error>;
+
+    /// Like [`RelaxedBincodeTree::get_or_init`], but returns the value itself.
+    fn get_or_insert_with<F: FnOnce() -> T, K: Encode, T: Encode + Decode>(
+        &self,
+        key: K,
+        init_func: F,
+    ) -> Result<T, Error> {
+        Ok(self
+            .get_or_init(key, init_func)?
+            .expect("get_or_init always returns a value"))
+    }
 }
@@ -0,0 +1,236 @@
+//! Values that carry the version of their schema, upgraded when they are read.
+//!
+//! A [`VersionedTree`] stores every value behind a version byte. Its
+//! [`MigrationRegistry`] holds the steps turning a value of one version into the
+//! next one, so that values written by older versions of a program are upgraded
+//! transparently by [`VersionedTree::get`].
+
+use sled::IVec;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::error::Error;
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::{DefaultConfig, BINCODE_CONFIG};
+
+/// Name of the tree recording how far each [`crate::Db::migrate_tree`] got, keyed by
+/// the names of the source and destination trees.
+pub const MIGRATION_TREE: &str = "__ser_sled_migrations";
+
+type Step = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, Error> + Send + Sync>;
+
+/// The steps upgrading values of a [`VersionedTree`] to the current version, `V`.
+///
+/// Values written before any step was registered have version `0`, and the first
+/// registered step upgrades them to version `1`, and so on: the current version is
+/// the number of registered steps. Steps must be registered in order, and the
+/// output of the last one must be `V`.
+pub struct MigrationRegistry<V, VC = BincodeCodec> {
+    steps: Vec<Step>,
+    codecs: PhantomData<fn() -> (V, VC)>,
+}
+
+impl<V, VC> Default for MigrationRegistry<V, VC> {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            codecs: PhantomData,
+        }
+    }
+}
+
+impl<V, VC: ValueCodec<V>> MigrationRegistry<V, VC> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the step upgrading values of the current version, stored as `Old`,
+    /// to the next version, stored as `New`.
+    pub fn register<Old, New, F>(mut self, step: F) -> Self
+    where
+        VC: ValueCodec<Old> + ValueCodec<New>,
+        F: Fn(Old) -> New + Send + Sync + 'static,
+    {
+        self.steps.push(Box::new(move |bytes| {
+            let old = <VC as ValueCodec<Old>>::decode(bytes, BINCODE_CONFIG, true)?;
+            <VC as ValueCodec<New>>::encode(&step(old), BINCODE_CONFIG)
+        }));
+        self
+    }
+
+    /// The version new values are written with.
+    pub fn current_version(&self) -> u8 {
+        self.steps.len() as u8
+    }
+
+    /// Decodes a stored value, upgrading it if needed. Returns whether it was upgraded.
+    fn decode(&self, stored: &[u8]) -> Result<(V, bool), Error> {
+        let (&version, bytes) = stored.split_first().ok_or(Error::UnsupportedVersion {
+            version: 0,
+            current: self.current_version(),
+        })?;
+
+        let steps = self
+            .steps
+            .get(version as usize..)
+            .ok_or(Error::UnsupportedVersion {
+                version,
+                current: self.current_version(),
+            })?;
+
+        let mut bytes = bytes.to_vec();
+        for step in steps {
+            bytes = step(&bytes)?;
+        }
+
+        Ok((VC::decode(&bytes, BINCODE_CONFIG, true)?, !steps.is_empty()))
+    }
+
+    fn encode(&self, value: &V) -> Result<Vec<u8>, Error> {
+        let mut stored = vec![self.current_version()];
+        stored.extend(VC::encode(value, BINCODE_CONFIG)?);
+        Ok(stored)
+    }
+}
+
+/// A tree whose values are stored with the version of their schema, opened with
+/// [`crate::Db::open_versioned_tree`]. Values of older versions are upgraded with the
+/// tree's [`MigrationRegistry`] when they are read, and written back upgraded if
+/// [`VersionedTree::with_write_back`] is set.
+pub struct VersionedTree<K, V, VC = BincodeCodec, KC = OrderedKeyCodec> {
+    inner_tree: sled::Tree,
+    registry: Arc<MigrationRegistry<V, VC>>,
+    write_back: bool,
+    key_codec: PhantomData<fn() -> (K, KC)>,
+}
+
+impl<K, V, VC, KC> Clone for VersionedTree<K, V, VC, KC> {
+    fn clone(&self) -> Self {
+        Self {
+            inner_tree: self.inner_tree.clone(),
+            registry: self.registry.clone(),
+            write_back: self.write_back,
+            key_codec: PhantomData,
+        }
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, KC: KeyCodec<K>> VersionedTree<K, V, VC, KC> {
+    pub(crate) fn new(inner_tree: sled::Tree, registry: MigrationRegistry<V, VC>) -> Self {
+        Self {
+            inner_tree,
+            registry: Arc::new(registry),
+            write_back: false,
+            key_codec: PhantomData,
+        }
+    }
+
+    /// Stores values upgraded by [`VersionedTree::get`] back into the tree, so that
+    /// they only need to be upgraded once. Values changed in the meantime are left alone.
+    pub fn with_write_back(mut self, write_back: bool) -> Self {
+        self.write_back = write_back;
+        self
+    }
+
+    pub fn current_version(&self) -> u8 {
+        self.registry.current_version()
+    }
+
+    /// The underlying sled tree. Its values start with the version byte.
+    pub fn inner(&self) -> &sled::Tree {
+        &self.inner_tree
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = KC::encode_key(key, BINCODE_CONFIG)?;
+
+        match self.inner_tree.get(&key_bytes)? {
+            Some(stored) => self.decode_stored(&key_bytes, stored).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<(), Error> {
+        self.inner_tree.insert(
+            KC::encode_key(key, BINCODE_CONFIG)?,
+            self.registry.encode(value)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        match self
+            .inner_tree
+            .remove(KC::encode_key(key, BINCODE_CONFIG)?)?
+        {
+            Some(stored) => Ok(Some(self.registry.decode(&stored)?.0)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        Ok(self
+            .inner_tree
+            .contains_key(KC::encode_key(key, BINCODE_CONFIG)?)?)
+    }
+
+    /// Iterates over the entries, upgrading values as needed. Entries that fail to
+    /// decode or upgrade are skipped. Nothing is written back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, V)> + '_ {
+        self.inner_tree.iter().filter_map(|entry| {
+            let (key_bytes, stored) = entry.ok()?;
+            let key = KC::decode_key::<DefaultConfig>(&key_bytes, BINCODE_CONFIG, true).ok()?;
+            let (value, _) = self.registry.decode(&stored).ok()?;
+            Some((key, value))
+        })
+    }
+
+    /// Upgrades every value of an older version and writes it back, returning how
+    /// many were upgraded. Stops at the first value that fails to upgrade.
+    pub fn migrate_all(&self) -> Result<usize, Error> {
+        let current = self.current_version();
+        let mut upgraded = 0;
+
+        for entry in self.inner_tree.iter() {
+            let (key_bytes, stored) = entry?;
+            if stored.first() == Some(&current) {
+                continue;
+            }
+
+            let (value, _) = self.registry.decode(&stored)?;
+            if self.write_upgraded(&key_bytes, stored, &value)? {
+                upgraded += 1;
+            }
+        }
+
+        Ok(upgraded)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner_tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner_tree.is_empty()
+    }
+
+    fn decode_stored(&self, key_bytes: &[u8], stored: IVec) -> Result<V, Error> {
+        let (value, upgraded) = self.registry.decode(&stored)?;
+
+        if upgraded && self.write_back {
+            self.write_upgraded(key_bytes, stored, &value)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Replaces `stored` with the upgraded `value`, unless the entry changed since it
+    /// was read. Returns whether it was replaced.
+    fn write_upgraded(&self, key_bytes: &[u8], stored: IVec, value: &V) -> Result<bool, Error> {
+        Ok(self
+            .inner_tree
+            .compare_and_swap(key_bytes, Some(stored), Some(self.registry.encode(value)?))?
+            .is_ok())
+    }
+}
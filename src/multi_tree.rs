@@ -0,0 +1,33 @@
+//! Helpers shared by the structures built on several trees, such as
+//! [`crate::job_queue::JobQueue`] or [`crate::cache::CacheTree`].
+
+use sled::transaction::{ConflictableTransactionResult, TransactionError, Transactional};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::Error;
+
+/// Runs `f` on `trees` in a transaction. Returns `Ok(Err(e))` if `f` aborted with `e`.
+pub(crate) fn transaction<Trees, T, E, F>(trees: &Trees, f: F) -> Result<Result<T, E>, Error>
+where
+    Trees: Transactional<E> + ?Sized,
+    F: Fn(&Trees::View) -> ConflictableTransactionResult<T, E>,
+{
+    match trees.transaction(f) {
+        Ok(res) => Ok(Ok(res)),
+        Err(TransactionError::Abort(e)) => Ok(Err(e)),
+        Err(TransactionError::Storage(e)) => Err(e.into()),
+    }
+}
+
+/// Decodes a big-endian counter, or returns 0 if `bytes` aren't 8 bytes long.
+pub(crate) fn decode_u64(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_be_bytes).unwrap_or_default()
+}
+
+/// Milliseconds since the Unix epoch.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,54 @@
+/// An update to some of the fields of a `V`, applied by `patch` on trees.
+///
+/// `apply` may be called several times if the value changes concurrently,
+/// so it must not consume the patch. Closures `Fn(&mut V)` are patches too.
+pub trait Patch<V> {
+    fn apply(&self, value: &mut V);
+}
+
+impl<V, F: Fn(&mut V)> Patch<V> for F {
+    fn apply(&self, value: &mut V) {
+        self(value)
+    }
+}
+
+/// Generates a patch struct for a struct `V`, with one `Option` per listed field:
+/// fields left to `None` are kept as they are.
+///
+/// ```ignore
+/// ser_sled::patch_struct! {
+///     #[derive(Debug)]
+///     pub struct UserPatch for User {
+///         name: String,
+///         bio: String,
+///     }
+/// }
+///
+/// let patch = UserPatch { name: Some("angel".to_string()), ..Default::default() };
+/// tree.patch(&key, &patch)?;
+/// ```
+#[macro_export]
+macro_rules! patch_struct {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident for $target:ty {
+            $($field:ident: $field_ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Default, Clone)]
+        $vis struct $name {
+            $(pub $field: Option<$field_ty>,)*
+        }
+
+        impl $crate::patch::Patch<$target> for $name {
+            fn apply(&self, value: &mut $target) {
+                $(
+                    if let Some(field) = &self.$field {
+                        value.$field = field.clone();
+                    }
+                )*
+            }
+        }
+    };
+}
@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+/// Selects a field `F` of a stored value `V` by naming the types of the fields
+/// stored before it, in declaration order, as the tuple `Before`.
+///
+/// bincode writes struct fields one after the other without any framing, so decoding
+/// `(Before, F)` from the start of a value yields the field without decoding the rest
+/// of the struct. This works with any integer encoding, but the selector is only
+/// checked against `V` nominally: listing the wrong fields decodes garbage or fails.
+///
+/// ```ignore
+/// #[derive(Encode, Decode)]
+/// struct User { id: u64, name: String, bio: String }
+///
+/// impl User {
+///     fn name_field() -> FieldSelector<User, (u64,), String> {
+///         FieldSelector::new()
+///     }
+/// }
+///
+/// let name: Option<String> = tree.project(&key, User::name_field())?;
+/// ```
+pub struct FieldSelector<V, Before, F> {
+    value: PhantomData<fn() -> V>,
+    before: PhantomData<fn() -> Before>,
+    field: PhantomData<fn() -> F>,
+}
+
+impl<V, Before, F> FieldSelector<V, Before, F> {
+    pub const fn new() -> Self {
+        Self {
+            value: PhantomData,
+            before: PhantomData,
+            field: PhantomData,
+        }
+    }
+}
+
+impl<V, Before, F> Default for FieldSelector<V, Before, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, Before, F> Clone for FieldSelector<V, Before, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V, Before, F> Copy for FieldSelector<V, Before, F> {}
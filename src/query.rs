@@ -0,0 +1,111 @@
+//! A builder for queries filtering the values of a tree.
+
+use std::ops::RangeBounds;
+
+use bincode::{Decode, Encode};
+
+use crate::error::Error;
+use crate::index::Index;
+use crate::keys::OrderedKey;
+
+type Entries<'a, K, V> = Box<dyn Iterator<Item = Result<(K, V), Error>> + 'a>;
+type Filter<'a, V> = Box<dyn Fn(&V) -> bool + 'a>;
+
+/// Where a [`Query`] reads its entries from, as chosen by [`Query::plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    /// Every entry of the tree is read.
+    FullScan,
+    /// The entries of a range of keys are read.
+    KeyRange,
+    /// The entries of a range of an index are read.
+    IndexRange,
+}
+
+/// A query over the entries of a tree, built with `Tree::query` or
+/// [`crate::index::IndexedTree::query`].
+///
+/// The entries are read from the narrowest source given: a range of an index set with
+/// [`Query::range_by_index`], then a range of keys, then the whole tree. Filters are
+/// applied to the entries read, in the order they were added. Entries come in the
+/// order of their source: by key, or by indexed value then key.
+pub struct Query<'a, K, V> {
+    entries: Entries<'a, K, V>,
+    plan: Plan,
+    index_entries: Option<Entries<'a, K, V>>,
+    filters: Vec<Filter<'a, V>>,
+    limit: Option<usize>,
+}
+
+impl<'a, K: 'a, V: 'a> Query<'a, K, V> {
+    pub(crate) fn new<I>(entries: I, plan: Plan) -> Self
+    where
+        I: Iterator<Item = Result<(K, V), Error>> + 'a,
+    {
+        Self {
+            entries: Box::new(entries),
+            plan,
+            index_entries: None,
+            filters: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// Keeps the entries whose value matches `filter`.
+    pub fn filter<F: Fn(&V) -> bool + 'a>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Reads the entries whose value indexed by `index` is in `range`, instead of
+    /// reading the tree. `index` must index the queried tree.
+    pub fn range_by_index<I, R>(mut self, index: &'a Index<K, V, I>, range: R) -> Self
+    where
+        K: OrderedKey,
+        V: Encode + Decode,
+        I: OrderedKey,
+        R: RangeBounds<I> + 'a,
+    {
+        self.index_entries = Some(Box::new(index.range(range)));
+        self
+    }
+
+    /// Returns at most `limit` entries.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Where the entries will be read from.
+    pub fn plan(&self) -> Plan {
+        match self.index_entries {
+            Some(_) => Plan::IndexRange,
+            None => self.plan,
+        }
+    }
+
+    /// Runs the query, stopping at the first entry that fails to decode.
+    pub fn collect(self) -> Result<Vec<(K, V)>, Error> {
+        self.into_iter().collect()
+    }
+}
+
+impl<'a, K: 'a, V: 'a> IntoIterator for Query<'a, K, V> {
+    type Item = Result<(K, V), Error>;
+    type IntoIter = Entries<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let entries = self.index_entries.unwrap_or(self.entries);
+        let filters = self.filters;
+
+        let matching = entries.filter(move |entry| match entry {
+            Ok((_, value)) => filters.iter().all(|filter| filter(value)),
+            Err(_) => true,
+        });
+
+        match self.limit {
+            Some(limit) => Box::new(matching.take(limit)),
+            None => Box::new(matching),
+        }
+    }
+}
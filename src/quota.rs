@@ -0,0 +1,347 @@
+use sled::IVec;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::backend::KvBackend;
+use crate::error::Error;
+use crate::tree::Batch;
+
+/// What a write does when it would take a tree over its [`Quota`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPolicy {
+    /// The write fails with [`Error::QuotaExceeded`], and the tree is left unchanged.
+    RejectWrites,
+    /// The write goes through, then the entries with the smallest keys are removed
+    /// until the tree fits again. These are the oldest entries of trees keyed by
+    /// increasing IDs, such as those written with `insert_auto`.
+    EvictOldest,
+}
+
+/// A limit on the number of entries and bytes of a tree, set with `with_quota`.
+///
+/// A tree's size is the sum of the lengths of its encoded keys and values. It is
+/// counted once, by the first write that adds to the tree, then kept up to date by
+/// every write of the handle and its clones, which are checked against the quota.
+/// Batch writes are checked as a whole, and `rewrite_in_place` has the next write count
+/// the size again. Writes made through `inner` or another handle aren't seen by the
+/// quota until the tree is given a quota again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub max_entries: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub policy: QuotaPolicy,
+}
+
+impl Quota {
+    /// A quota without limits, to set with [`Quota::with_max_entries`] and
+    /// [`Quota::with_max_bytes`].
+    pub fn new(policy: QuotaPolicy) -> Self {
+        Self {
+            max_entries: None,
+            max_bytes: None,
+            policy,
+        }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: u64) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    fn is_exceeded_by(&self, usage: Usage) -> bool {
+        self.max_entries.is_some_and(|max| usage.entries > max)
+            || self.max_bytes.is_some_and(|max| usage.bytes > max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    entries: u64,
+    bytes: u64,
+}
+
+impl Usage {
+    fn count(tree: &impl KvBackend) -> Result<Self, Error> {
+        let mut usage = Self::default();
+        for entry in KvBackend::iter(tree) {
+            let (key, value) = entry?;
+            usage.add(key.len() + value.len());
+        }
+
+        Ok(usage)
+    }
+
+    fn add(&mut self, size: usize) {
+        self.entries += 1;
+        self.bytes += size as u64;
+    }
+
+    fn remove(&mut self, size: usize) {
+        self.entries = self.entries.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(size as u64);
+    }
+}
+
+/// A quota and the usage of the tree it applies to, shared by the clones of a handle.
+#[derive(Debug)]
+pub(crate) struct QuotaState {
+    quota: Quota,
+    /// Counted by the first write that adds to the tree. The lock also serializes the
+    /// writes of the handle, so that they all see each other's usage.
+    usage: Mutex<Option<Usage>>,
+}
+
+impl QuotaState {
+    pub(crate) fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            usage: Mutex::new(None),
+        }
+    }
+
+    /// Inserts raw bytes into `tree` as told by the quota's policy. Returns the
+    /// previous value.
+    pub(crate) fn insert(
+        &self,
+        tree: &impl KvBackend,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> Result<Option<IVec>, Error> {
+        self.write(tree, |usage| {
+            let size = key.len() + value.len();
+            self.check_size(size)?;
+
+            let mut projected = *usage;
+            if let Some(old) = tree.get(&key)? {
+                projected.remove(key.len() + old.len());
+            }
+            projected.add(size);
+            self.check_usage(projected)?;
+
+            let old = tree.insert(&key, &value)?;
+            if let Some(old) = &old {
+                usage.remove(key.len() + old.len());
+            }
+            usage.add(size);
+            self.evict(tree, usage)?;
+
+            Ok(old)
+        })
+    }
+
+    /// Removes `key` from `tree`, and returns its value.
+    pub(crate) fn remove(&self, tree: &impl KvBackend, key: &[u8]) -> Result<Option<IVec>, Error> {
+        let removed = self.remove_entries(|| {
+            Ok(tree
+                .remove(key)?
+                .map(|value| (IVec::from(key), value))
+                .into_iter()
+                .collect())
+        })?;
+
+        Ok(removed.into_iter().next().map(|(_, value)| value))
+    }
+
+    /// Replaces the value of `key` in `tree` with `new` if it is still `old`, as told
+    /// by the quota's policy. Returns whether it was replaced.
+    pub(crate) fn compare_and_swap(
+        &self,
+        tree: &impl KvBackend,
+        key: &[u8],
+        old: Option<IVec>,
+        new: Option<Vec<u8>>,
+    ) -> Result<bool, Error> {
+        self.write(tree, |usage| {
+            let mut projected = *usage;
+            if let Some(old) = &old {
+                projected.remove(key.len() + old.len());
+            }
+            if let Some(new) = &new {
+                self.check_size(key.len() + new.len())?;
+                projected.add(key.len() + new.len());
+                self.check_usage(projected)?;
+            }
+
+            if !tree.compare_and_swap(key, old.as_deref(), new.as_deref())? {
+                return Ok(false);
+            }
+            *usage = projected;
+            self.evict(tree, usage)?;
+
+            Ok(true)
+        })
+    }
+
+    /// Merges `delta` into the value of `key` in `tree`, as told by the quota's policy.
+    /// Returns the merged value.
+    pub(crate) fn merge(
+        &self,
+        tree: &sled::Tree,
+        key: &[u8],
+        delta: Vec<u8>,
+    ) -> Result<Option<IVec>, Error> {
+        self.write(tree, |usage| {
+            let old = tree.get(key)?;
+            let new = tree.merge(key, delta)?;
+
+            let mut projected = *usage;
+            if let Some(old) = &old {
+                projected.remove(key.len() + old.len());
+            }
+            if let Some(new) = &new {
+                projected.add(key.len() + new.len());
+
+                // The merged value is only known once written, so a rejected merge is
+                // undone, unless the value changed again in the meantime
+                let checked = self
+                    .check_size(key.len() + new.len())
+                    .and_then(|()| self.check_usage(projected));
+                if let Err(e) = checked {
+                    if tree.compare_and_swap(key, Some(new), old)?.is_err() {
+                        *usage = Usage::count(tree)?;
+                    }
+                    return Err(e);
+                }
+            }
+
+            *usage = projected;
+            self.evict(tree, usage)?;
+
+            Ok(new)
+        })
+    }
+
+    /// Writes `batch` to `tree` atomically, as told by the quota's policy.
+    pub(crate) fn apply_batch(
+        &self,
+        tree: &impl KvBackend,
+        batch: Batch<Vec<u8>, Vec<u8>>,
+    ) -> Result<(), Error> {
+        // Like in sled, the last write of a key wins
+        let ops: BTreeMap<_, _> = batch.into_iter().collect();
+
+        self.write(tree, |usage| {
+            let mut projected = *usage;
+            let mut deduped = Batch::new();
+
+            for (key, value) in &ops {
+                if let Some(old) = tree.get(key)? {
+                    projected.remove(key.len() + old.len());
+                }
+
+                match value {
+                    Some(value) => {
+                        self.check_size(key.len() + value.len())?;
+                        projected.add(key.len() + value.len());
+                        deduped.insert(key.clone(), value.clone());
+                    }
+                    None => deduped.remove(key.clone()),
+                }
+            }
+            if ops.values().any(Option::is_some) {
+                self.check_usage(projected)?;
+            }
+
+            tree.apply_batch(deduped)?;
+            *usage = projected;
+            self.evict(tree, usage)
+        })
+    }
+
+    /// Runs `remove`, which removes the entries it returns from the tree, and takes
+    /// them off the usage.
+    pub(crate) fn remove_entries(
+        &self,
+        remove: impl FnOnce() -> Result<Vec<(IVec, IVec)>, Error>,
+    ) -> Result<Vec<(IVec, IVec)>, Error> {
+        let mut usage = self.usage.lock().expect("lock isn't poisoned");
+        let removed = remove()?;
+
+        if let Some(usage) = usage.as_mut() {
+            for (key, value) in &removed {
+                usage.remove(key.len() + value.len());
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes every entry of `tree`.
+    pub(crate) fn clear(&self, tree: &impl KvBackend) -> Result<(), Error> {
+        let mut usage = self.usage.lock().expect("lock isn't poisoned");
+        tree.clear()?;
+        *usage = Some(Usage::default());
+
+        Ok(())
+    }
+
+    /// Runs `write`, which changes `tree` in ways that aren't tracked one entry at a
+    /// time, then has the next write count the usage again.
+    pub(crate) fn recounting<T>(
+        &self,
+        write: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut usage = self.usage.lock().expect("lock isn't poisoned");
+        let res = write();
+        *usage = None;
+
+        res
+    }
+
+    /// Runs `write` with the usage of `tree`, counting it first if needed.
+    fn write<T>(
+        &self,
+        tree: &impl KvBackend,
+        write: impl FnOnce(&mut Usage) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut usage = self.usage.lock().expect("lock isn't poisoned");
+        let mut current = match *usage {
+            Some(current) => current,
+            None => Usage::count(tree)?,
+        };
+
+        let res = write(&mut current);
+        *usage = Some(current);
+
+        res
+    }
+
+    /// Fails if a single entry of `size` bytes is over the quota.
+    fn check_size(&self, size: usize) -> Result<(), Error> {
+        match self.quota.max_bytes {
+            Some(max) if size as u64 > max => Err(Error::QuotaExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if the quota rejects writes and `projected` is over it.
+    fn check_usage(&self, projected: Usage) -> Result<(), Error> {
+        if self.quota.policy == QuotaPolicy::RejectWrites && self.quota.is_exceeded_by(projected) {
+            return Err(Error::QuotaExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the entries with the smallest keys until `usage` fits the quota, if it
+    /// evicts the oldest entries.
+    fn evict(&self, tree: &impl KvBackend, usage: &mut Usage) -> Result<(), Error> {
+        if self.quota.policy != QuotaPolicy::EvictOldest {
+            return Ok(());
+        }
+
+        while self.quota.is_exceeded_by(*usage) {
+            match tree.pop_min()? {
+                Some((key, value)) => usage.remove(key.len() + value.len()),
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{error::Error, Db};
+
+/// Name of the tree holding the fingerprint of every strict tree, keyed by tree name.
+pub const SCHEMA_TREE: &str = "__ser_sled_schemas";
+
+/// Describes the key and value types a strict tree was opened with. Codecs aren't
+/// part of it, so that a tree can switch to a compatible codec, e.g. to start
+/// compressing values. It is built from type names, so moving or renaming a type
+/// changes it too; see [`Db::reset_schema`].
+pub(crate) fn fingerprint<K, V>() -> String {
+    use std::any::type_name;
+
+    format!("key={};value={}", type_name::<K>(), type_name::<V>())
+}
+
+/// Stores `fingerprint` for `tree_name` if it has none yet, and otherwise checks
+/// that it matches the stored one.
+pub(crate) fn check_fingerprint(
+    db: &Db,
+    tree_name: &str,
+    fingerprint: String,
+) -> Result<(), Error> {
+    let schemas = db.inner_db.open_tree(SCHEMA_TREE)?;
+
+    match schemas.compare_and_swap(tree_name, None::<&[u8]>, Some(fingerprint.as_bytes()))? {
+        Ok(()) => Ok(()),
+        Err(e) if e.current.as_deref() == Some(fingerprint.as_bytes()) => Ok(()),
+        Err(e) => Err(Error::SchemaMismatch {
+            tree: tree_name.to_string(),
+            stored: String::from_utf8_lossy(e.current.as_deref().unwrap_or_default()).into_owned(),
+            requested: fingerprint,
+        }),
+    }
+}
+
+/// A tree that can be opened by name, checking its fingerprint. Implemented by
+/// strict trees, so that `#[derive(SerSledSchema)]` (with the `derive` feature) can
+/// open every field of a struct.
+pub trait SchemaTree: Sized {
+    fn open_by_name(db: &Db, tree_name: &str) -> Result<Self, Error>;
+}
+
+impl<K, V, VC, KC> SchemaTree for crate::tree::Tree<K, V, VC, crate::DefaultConfig, KC>
+where
+    VC: crate::codec::ValueCodec<V>,
+    KC: crate::keys::KeyCodec<K>,
+{
+    fn open_by_name(db: &Db, tree_name: &str) -> Result<Self, Error> {
+        db.open_tree(tree_name)
+    }
+}
@@ -0,0 +1,192 @@
+use bincode::config::Config;
+use std::marker::PhantomData;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{self, successor, KeyCodec, OrderedKeyCodec};
+use crate::tree::RawTree;
+use crate::{error::Error, BytesRange, DefaultConfig};
+
+/// The part of a strict tree whose keys start with a prefix, returned by
+/// [`crate::tree::Tree::scoped`].
+///
+/// Keys are given and returned without the prefix, which every write adds and every
+/// read removes, so that e.g. every tenant of a multi-tenant app can have its own
+/// scope in one tree. Iterators and ranges only see the keys of the scope, in the order
+/// of their key codec. Prefixes are encoded with bincode, and no encoded prefix is a
+/// prefix of another one, so scopes don't overlap.
+pub struct ScopedTree<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    raw: RawTree<C>,
+    prefix: Vec<u8>,
+    validate_keys: bool,
+    types: PhantomData<fn() -> (K, V)>,
+    codecs: PhantomData<fn() -> (VC, KC)>,
+}
+
+impl<K, V, VC, C: Clone, KC> Clone for ScopedTree<K, V, VC, C, KC> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            prefix: self.prefix.clone(),
+            validate_keys: self.validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> ScopedTree<K, V, VC, C, KC> {
+    pub(crate) fn new(raw: RawTree<C>, prefix: Vec<u8>, validate_keys: bool) -> Self {
+        Self {
+            raw,
+            prefix,
+            validate_keys,
+            types: PhantomData,
+            codecs: PhantomData,
+        }
+    }
+
+    /// The scope of this scope whose keys start with `prefix`. The entries of nested
+    /// scopes are entries of this scope too, so a scope shouldn't mix keys and
+    /// nested scopes.
+    pub fn scoped<P: bincode::Encode>(&self, prefix: &P) -> Result<Self, Error> {
+        let mut scoped = self.clone();
+        scoped
+            .prefix
+            .extend(bincode::encode_to_vec(prefix, self.raw.config)?);
+
+        Ok(scoped)
+    }
+
+    /// The encoded prefix of the keys of the scope.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// The underlying sled tree, holding the entries of every scope. Its keys start
+    /// with the [`ScopedTree::prefix`] of their scope.
+    pub fn inner(&self) -> &sled::Tree {
+        self.raw.inner()
+    }
+
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        if self.validate_keys {
+            keys::check_round_trip::<K, KC, C>(key, self.raw.config)?;
+        }
+
+        self.raw.insert_bytes(
+            self.encode_key(key)?,
+            value,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        self.raw.get_bytes(&self.encode_key(key)?, VC::decode::<C>)
+    }
+
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        self.raw
+            .remove_bytes(&self.encode_key(key)?, VC::decode::<C>)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        Ok(self.raw.inner_tree.contains_key(self.encode_key(key)?)?)
+    }
+
+    /// Iterates over the entries of the scope, skipping those that fail to decode.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, V)> {
+        self.entries(self.raw.inner_tree.scan_prefix(&self.prefix))
+    }
+
+    /// Iterates over the entries of the scope whose key is in `range`.
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error> {
+        let (start, end) = self.raw.encode_range(range, KC::encode_key::<C>)?;
+        let bounds: BytesRange = (
+            match start {
+                Included(key) => Included(self.prefixed(&key)),
+                Excluded(key) => Excluded(self.prefixed(&key)),
+                Unbounded => Included(self.prefix.clone()),
+            },
+            match end {
+                Included(key) => Included(self.prefixed(&key)),
+                Excluded(key) => Excluded(self.prefixed(&key)),
+                Unbounded => successor(self.prefix.clone()).map_or(Unbounded, Excluded),
+            },
+        );
+
+        Ok(self.entries(self.raw.inner_tree.range(bounds)))
+    }
+
+    pub fn first(&self) -> Result<Option<(K, V)>, Error> {
+        self.decode_entry(self.raw.inner_tree.scan_prefix(&self.prefix).next())
+    }
+
+    pub fn last(&self) -> Result<Option<(K, V)>, Error> {
+        self.decode_entry(self.raw.inner_tree.scan_prefix(&self.prefix).next_back())
+    }
+
+    /// Number of entries of the scope. O(n) in the size of the scope.
+    pub fn len(&self) -> usize {
+        self.raw.inner_tree.scan_prefix(&self.prefix).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw
+            .inner_tree
+            .scan_prefix(&self.prefix)
+            .next()
+            .is_none()
+    }
+
+    /// Removes every entry of the scope, and returns how many were removed.
+    pub fn clear(&self) -> Result<usize, Error> {
+        let end = successor(self.prefix.clone()).map_or(Unbounded, Excluded);
+        self.raw
+            .remove_range_bytes((Included(self.prefix.clone()), end))
+    }
+
+    fn encode_key(&self, key: &K) -> Result<Vec<u8>, Error> {
+        Ok(self.prefixed(&KC::encode_key(key, self.raw.config)?))
+    }
+
+    fn prefixed(&self, key_bytes: &[u8]) -> Vec<u8> {
+        [self.prefix.as_slice(), key_bytes].concat()
+    }
+
+    fn entries(&self, iter: sled::Iter) -> impl DoubleEndedIterator<Item = (K, V)> {
+        let (config, strict) = (self.raw.config, self.raw.strict_decode);
+        let prefix_len = self.prefix.len();
+
+        iter.filter_map(move |res| {
+            let (key_ivec, value_ivec) = res.ok()?;
+            let key = KC::decode_key(key_ivec.get(prefix_len..)?, config, strict).ok()?;
+            let value = VC::decode(&value_ivec, config, strict).ok()?;
+
+            Some((key, value))
+        })
+    }
+
+    fn decode_entry(
+        &self,
+        entry: Option<sled::Result<(sled::IVec, sled::IVec)>>,
+    ) -> Result<Option<(K, V)>, Error> {
+        let Some((key_ivec, value_ivec)) = entry.transpose()? else {
+            return Ok(None);
+        };
+        let key_bytes = key_ivec
+            .get(self.prefix.len()..)
+            .ok_or(Error::InvalidKey("key is shorter than its scope's prefix"))?;
+
+        Ok(Some((
+            KC::decode_key(key_bytes, self.raw.config, self.raw.strict_decode)?,
+            self.raw
+                .decode_stored(VC::decode::<C>, &key_ivec, &value_ivec)?,
+        )))
+    }
+}
@@ -0,0 +1,129 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+use crate::bincode_tree::BincodeTree;
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::{error::Error, DefaultConfig, StrictTree, BINCODE_CONFIG};
+
+/// A persistent set, opened with [`crate::Db::open_set`]. Members are stored as keys
+/// with empty values, encoded with `KC`, and iterate in the order of their encoding.
+#[derive(Clone)]
+pub struct Set<T, KC = OrderedKeyCodec> {
+    tree: BincodeTree<T, (), DefaultConfig, KC>,
+}
+
+impl<T, KC: KeyCodec<T>> Set<T, KC> {
+    pub(crate) fn new(tree: BincodeTree<T, (), DefaultConfig, KC>) -> Self {
+        Self { tree }
+    }
+
+    /// Adds `member`, and returns whether it wasn't in the set yet.
+    pub fn insert(&self, member: &T) -> Result<bool, Error> {
+        self.tree.insert_if_absent(member, &())
+    }
+
+    /// Removes `member`, and returns whether it was in the set.
+    pub fn remove(&self, member: &T) -> Result<bool, Error> {
+        Ok(self
+            .tree
+            .raw()
+            .inner_tree
+            .remove(self.tree.encode_key(member)?)?
+            .is_some())
+    }
+
+    pub fn contains(&self, member: &T) -> Result<bool, Error> {
+        self.tree.contains_key(member)
+    }
+
+    /// Iterates over the members. Members that fail to decode are skipped.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = T> {
+        self.tree
+            .raw()
+            .inner_tree
+            .iter()
+            .keys()
+            .filter_map(decode_member::<T, KC>)
+    }
+
+    /// Iterates over the members of this set or `other`, without duplicates.
+    pub fn union<'a>(&'a self, other: &'a Set<T, KC>) -> impl Iterator<Item = T> + 'a {
+        self.merge(other, |_, _| true)
+    }
+
+    /// Iterates over the members of both this set and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a Set<T, KC>) -> impl Iterator<Item = T> + 'a {
+        self.merge(other, |in_self, in_other| in_self && in_other)
+    }
+
+    /// Iterates over the members of this set that aren't in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Set<T, KC>) -> impl Iterator<Item = T> + 'a {
+        self.merge(other, |in_self, in_other| in_self && !in_other)
+    }
+
+    pub fn clear(&self) -> Result<(), Error> {
+        self.tree.clear()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Walks both sets in key order, keeping the members for which `keep`, given
+    /// whether the member is in this set and in `other`, returns `true`.
+    fn merge<'a, F>(&'a self, other: &'a Set<T, KC>, keep: F) -> impl Iterator<Item = T> + 'a
+    where
+        F: Fn(bool, bool) -> bool + 'a,
+    {
+        let mut left = self.tree.raw().inner_tree.iter().keys().peekable();
+        let mut right = other.tree.raw().inner_tree.iter().keys().peekable();
+
+        let keys = std::iter::from_fn(move || loop {
+            let (in_left, in_right) = match (peek_key(&mut left), peek_key(&mut right)) {
+                (None, None) => return None,
+                (Some(_), None) => (true, false),
+                (None, Some(_)) => (false, true),
+                (Some(l), Some(r)) => match l.cmp(&r) {
+                    Ordering::Less => (true, false),
+                    Ordering::Greater => (false, true),
+                    Ordering::Equal => (true, true),
+                },
+            };
+
+            let key = match (in_left, in_right) {
+                (true, true) => {
+                    right.next();
+                    left.next()
+                }
+                (true, false) => left.next(),
+                _ => right.next(),
+            };
+
+            if keep(in_left, in_right) {
+                return key;
+            }
+        });
+
+        keys.filter_map(decode_member::<T, KC>)
+    }
+}
+
+fn decode_member<T, KC: KeyCodec<T>>(key: sled::Result<sled::IVec>) -> Option<T> {
+    KC::decode_key(&key.ok()?, BINCODE_CONFIG, true).ok()
+}
+
+/// The next key of `keys`, skipping errors.
+fn peek_key<I: Iterator<Item = sled::Result<sled::IVec>>>(
+    keys: &mut Peekable<I>,
+) -> Option<sled::IVec> {
+    while let Some(Err(_)) = keys.peek() {
+        keys.next();
+    }
+
+    keys.peek()
+        .map(|key| key.as_ref().expect("errors were skipped").clone())
+}
@@ -0,0 +1,84 @@
+use bincode::{Decode, Encode};
+
+use crate::bincode_tree::BincodeTree;
+use crate::event_log::{EventLog, Seq};
+use crate::{error::Error, StrictTree};
+
+/// Snapshots of a state folded from an [`EventLog`], opened with
+/// [`crate::Db::open_snapshot_store`]. Every snapshot is stored under the sequence
+/// number of the last event it includes, so that [`SnapshotStore::replay`] only has to
+/// fold the events appended after the latest one.
+#[derive(Clone)]
+pub struct SnapshotStore<S> {
+    snapshots: BincodeTree<Seq, S>,
+}
+
+impl<S: Encode + Decode> SnapshotStore<S> {
+    pub(crate) fn new(snapshots: BincodeTree<Seq, S>) -> Self {
+        Self { snapshots }
+    }
+
+    /// Stores `state` as the result of folding every event up to and including `seq`.
+    pub fn save(&self, seq: Seq, state: &S) -> Result<(), Error> {
+        self.snapshots.insert(&seq, state)?;
+        Ok(())
+    }
+
+    /// The latest snapshot, with the sequence number of the last event it includes.
+    pub fn latest(&self) -> Result<Option<(Seq, S)>, Error> {
+        self.snapshots.last()
+    }
+
+    /// The latest snapshot including no event after `seq`.
+    pub fn latest_until(&self, seq: Seq) -> Result<Option<(Seq, S)>, Error> {
+        Ok(self.snapshots.range(..=seq)?.next_back())
+    }
+
+    /// Rebuilds the current state: starts from the latest snapshot, or `init` if there is
+    /// none, and folds the events of `log` appended after it with `apply`. Returns the
+    /// state and the sequence number of the last event it includes, to pass to
+    /// [`SnapshotStore::save`].
+    pub fn replay<E, F>(
+        &self,
+        log: &EventLog<E>,
+        init: S,
+        mut apply: F,
+    ) -> Result<(S, Option<Seq>), Error>
+    where
+        E: Encode + Decode,
+        F: FnMut(S, E) -> S,
+    {
+        let (mut state, mut last) = match self.latest()? {
+            Some((seq, snapshot)) => (snapshot, Some(seq)),
+            None => (init, None),
+        };
+
+        let events = match last {
+            Some(seq) => log.read_after(seq)?.collect::<Vec<_>>(),
+            None => log.iter().collect(),
+        };
+        for (seq, event) in events {
+            state = apply(state, event);
+            last = Some(seq);
+        }
+
+        Ok((state, last))
+    }
+
+    /// Removes every snapshot but the `keep` latest ones, and returns how many were
+    /// removed.
+    pub fn prune(&self, keep: usize) -> Result<usize, Error> {
+        match self.snapshots.iter().rev().nth(keep) {
+            Some((seq, _)) => self.snapshots.remove_range(..=seq),
+            None => Ok(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
@@ -0,0 +1,130 @@
+use sled::transaction::ConflictableTransactionError;
+use std::marker::PhantomData;
+use std::ops::Bound::{Excluded, Included};
+use std::ops::RangeBounds;
+
+use crate::error::Error;
+use crate::keys::{from_ordered_bytes, prefix_bounds, to_ordered_bytes, OrderedKey};
+use crate::multi_tree;
+
+pub(crate) fn by_score_name(set_name: &str) -> String {
+    format!("{set_name}_by_score")
+}
+
+/// A set of members ranked by a score, like a Redis sorted set, opened with
+/// [`crate::Db::open_sorted_set`].
+///
+/// The score of every member is stored in the `<name>` tree, and the `<name>_by_score`
+/// tree holds `(score, member)` keys to iterate by score. Both are updated in the same
+/// transaction. Members with the same score are ordered by member.
+#[derive(Clone)]
+pub struct SortedSet<M, S = i64> {
+    members: sled::Tree,
+    by_score: sled::Tree,
+    types: PhantomData<fn() -> (M, S)>,
+}
+
+impl<M: OrderedKey + Clone, S: OrderedKey + Clone> SortedSet<M, S> {
+    pub(crate) fn new(members: sled::Tree, by_score: sled::Tree) -> Self {
+        Self {
+            members,
+            by_score,
+            types: PhantomData,
+        }
+    }
+
+    /// Adds `member` with `score`, or updates its score, and returns its previous score.
+    pub fn add(&self, member: &M, score: &S) -> Result<Option<S>, Error> {
+        self.write(member, Some(score))
+    }
+
+    /// Removes `member`, and returns its score.
+    pub fn remove(&self, member: &M) -> Result<Option<S>, Error> {
+        self.write(member, None)
+    }
+
+    pub fn score(&self, member: &M) -> Result<Option<S>, Error> {
+        self.members
+            .get(to_ordered_bytes(member))?
+            .map(|score| from_ordered_bytes(&score))
+            .transpose()
+    }
+
+    /// The position of `member` by increasing score, starting at `0`. This counts the
+    /// members before it, so it is O(rank).
+    pub fn rank(&self, member: &M) -> Result<Option<usize>, Error> {
+        let Some(score) = self.score(member)? else {
+            return Ok(None);
+        };
+
+        let mut rank = 0;
+        for key in self
+            .by_score
+            .range(..to_ordered_bytes(&(score, member.clone())))
+            .keys()
+        {
+            key?;
+            rank += 1;
+        }
+
+        Ok(Some(rank))
+    }
+
+    /// Iterates over the members whose score is in `range`, by increasing score. Use
+    /// `rev` to get the highest scores first. Entries that fail to decode are skipped.
+    pub fn range_by_score<R: RangeBounds<S>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (M, S)> {
+        // Nothing is greater than the largest score
+        let bounds = prefix_bounds(range).unwrap_or((Included(vec![]), Excluded(vec![])));
+
+        self.by_score.range(bounds).keys().filter_map(|key| {
+            let (score, member) = from_ordered_bytes::<(S, M)>(&key.ok()?).ok()?;
+            Some((member, score))
+        })
+    }
+
+    /// The `n` members with the highest scores, highest first.
+    pub fn top(&self, n: usize) -> Vec<(M, S)> {
+        self.range_by_score(..).rev().take(n).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Sets or removes the score of `member` in both trees, returning its previous score.
+    fn write(&self, member: &M, score: Option<&S>) -> Result<Option<S>, Error> {
+        let member_bytes = to_ordered_bytes(member);
+        let score_bytes = score.map(to_ordered_bytes);
+
+        let old =
+            multi_tree::transaction(&(&self.members, &self.by_score), |(members, by_score)| {
+                let old = match &score_bytes {
+                    Some(score_bytes) => {
+                        members.insert(member_bytes.as_slice(), score_bytes.as_slice())?
+                    }
+                    None => members.remove(member_bytes.as_slice())?,
+                };
+
+                if let Some(old) = &old {
+                    by_score.remove([old.as_ref(), &member_bytes].concat())?;
+                }
+                if let Some(score_bytes) = &score_bytes {
+                    by_score.insert([score_bytes.as_slice(), &member_bytes].concat(), &[])?;
+                }
+
+                Ok::<_, ConflictableTransactionError<()>>(old)
+            })?;
+
+        old.ok()
+            .flatten()
+            .map(|old| from_ordered_bytes(&old))
+            .transpose()
+    }
+}
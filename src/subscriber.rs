@@ -0,0 +1,112 @@
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::mpsc::RecvTimeoutError;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+pub(crate) type Decoder<T> = Box<dyn Fn(&[u8]) -> Result<T, Error> + Send + Sync>;
+
+/// A decoded change to a watched tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<K, V> {
+    Insert { key: K, value: V },
+    Remove { key: K },
+}
+
+impl<K, V> Event<K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Event::Insert { key, .. } | Event::Remove { key } => key,
+        }
+    }
+}
+
+/// Typed wrapper around a `sled::Subscriber`, returned by `watch_prefix`.
+/// Iterating blocks until the next event; with the `async` feature it is also
+/// a `futures_core::Stream` that can be awaited instead. Events that fail to
+/// decode are skipped, like entries are when iterating over a tree.
+pub struct Subscriber<K, V> {
+    inner: sled::Subscriber,
+    decode_key: Decoder<K>,
+    decode_value: Decoder<V>,
+}
+
+impl<K, V> Subscriber<K, V> {
+    pub(crate) fn new(
+        inner: sled::Subscriber,
+        decode_key: Decoder<K>,
+        decode_value: Decoder<V>,
+    ) -> Self {
+        Self {
+            inner,
+            decode_key,
+            decode_value,
+        }
+    }
+
+    pub(crate) fn decode_event(&self, event: sled::Event) -> Option<Event<K, V>> {
+        match event {
+            sled::Event::Insert { key, value } => Some(Event::Insert {
+                key: (self.decode_key)(&key).ok()?,
+                value: (self.decode_value)(&value).ok()?,
+            }),
+            sled::Event::Remove { key } => Some(Event::Remove {
+                key: (self.decode_key)(&key).ok()?,
+            }),
+        }
+    }
+
+    /// Waits at most `timeout` for the next event that decodes.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Event<K, V>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let event = self.inner.next_timeout(remaining)?;
+
+            if let Some(event) = self.decode_event(event) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl<K, V> Iterator for Subscriber<K, V> {
+    type Item = Event<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = self.inner.next()?;
+
+            if let Some(event) = self.decode_event(event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V> futures_core::Stream for Subscriber<K, V> {
+    type Item = Event<K, V>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match Pin::new(&mut this.inner).poll(cx) {
+                Poll::Ready(Some(event)) => {
+                    if let Some(event) = this.decode_event(event) {
+                        return Poll::Ready(Some(event));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
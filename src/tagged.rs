@@ -0,0 +1,148 @@
+use bincode::{Decode, Encode};
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::keys::{from_ordered_bytes, to_ordered_bytes, OrderedKey};
+use crate::{error::Error, BINCODE_CONFIG};
+
+/// The tag of a type stored in a [`TaggedTree`].
+pub type Tag = u32;
+
+/// A tree holding values of different types, opened with [`crate::Db::open_tagged_tree`].
+///
+/// Every value is stored behind the tag its type was registered with, and is only
+/// decoded as the type of its tag: reading it as another type returns
+/// [`Error::TypeMismatch`] instead of decoding garbage, as a relaxed tree would. Tags
+/// are part of the stored data, so a type must keep its tag.
+pub struct TaggedTree<K> {
+    inner_tree: sled::Tree,
+    tags: Arc<HashMap<TypeId, Tag>>,
+    key_type: PhantomData<fn() -> K>,
+}
+
+impl<K> Clone for TaggedTree<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner_tree: self.inner_tree.clone(),
+            tags: self.tags.clone(),
+            key_type: PhantomData,
+        }
+    }
+}
+
+impl<K: OrderedKey> TaggedTree<K> {
+    pub(crate) fn new(inner_tree: sled::Tree) -> Self {
+        Self {
+            inner_tree,
+            tags: Arc::default(),
+            key_type: PhantomData,
+        }
+    }
+
+    /// Registers `T` to be stored with `tag`. Handles cloned before don't know it.
+    ///
+    /// # Panics
+    ///
+    /// If `T` or `tag` is already registered.
+    pub fn register<T: 'static>(mut self, tag: Tag) -> Self {
+        assert!(
+            !self.tags.values().any(|registered| *registered == tag),
+            "tag {tag} is already registered"
+        );
+        let previous = Arc::make_mut(&mut self.tags).insert(TypeId::of::<T>(), tag);
+        assert!(
+            previous.is_none(),
+            "{} is already registered",
+            type_name::<T>()
+        );
+
+        self
+    }
+
+    /// The tag `T` was registered with.
+    pub fn tag_of<T: 'static>(&self) -> Result<Tag, Error> {
+        self.tags
+            .get(&TypeId::of::<T>())
+            .copied()
+            .ok_or(Error::UnregisteredType(type_name::<T>()))
+    }
+
+    /// Stores `value` with the tag of `T`, replacing the value of `key` whatever its
+    /// type.
+    pub fn insert_as<T: Encode + 'static>(&self, key: &K, value: &T) -> Result<(), Error> {
+        let mut stored = bincode::encode_to_vec(self.tag_of::<T>()?, BINCODE_CONFIG)?;
+        bincode::encode_into_std_write(value, &mut stored, BINCODE_CONFIG)?;
+
+        self.inner_tree.insert(to_ordered_bytes(key), stored)?;
+        Ok(())
+    }
+
+    /// Gets the value of `key`, or [`Error::TypeMismatch`] if it isn't a `T`.
+    pub fn get_as<T: Decode + 'static>(&self, key: &K) -> Result<Option<T>, Error> {
+        let expected = self.tag_of::<T>()?;
+
+        match self.inner_tree.get(to_ordered_bytes(key))? {
+            Some(stored) => decode_tagged(&stored, expected).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The tag of the value of `key`.
+    pub fn stored_tag(&self, key: &K) -> Result<Option<Tag>, Error> {
+        match self.inner_tree.get(to_ordered_bytes(key))? {
+            Some(stored) => Ok(Some(decode_tag(&stored)?.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes `key`, and returns whether it had a value.
+    pub fn remove(&self, key: &K) -> Result<bool, Error> {
+        Ok(self.inner_tree.remove(to_ordered_bytes(key))?.is_some())
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        Ok(self.inner_tree.contains_key(to_ordered_bytes(key))?)
+    }
+
+    /// Iterates over the entries whose value is a `T`, skipping the others and those
+    /// that fail to decode.
+    pub fn iter_as<T: Decode + 'static>(
+        &self,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, T)>, Error> {
+        let expected = self.tag_of::<T>()?;
+
+        Ok(self.inner_tree.iter().filter_map(move |entry| {
+            let (key_bytes, stored) = entry.ok()?;
+            let value = decode_tagged(&stored, expected).ok()?;
+            Some((from_ordered_bytes(&key_bytes).ok()?, value))
+        }))
+    }
+
+    /// Number of entries, of every type.
+    pub fn len(&self) -> usize {
+        self.inner_tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner_tree.is_empty()
+    }
+}
+
+/// Decodes the tag at the start of `stored`, and returns it with its length.
+fn decode_tag(stored: &[u8]) -> Result<(Tag, usize), Error> {
+    Ok(bincode::decode_from_slice(stored, BINCODE_CONFIG)?)
+}
+
+fn decode_tagged<T: Decode>(stored: &[u8], expected: Tag) -> Result<T, Error> {
+    let (tag, tag_len) = decode_tag(stored)?;
+    if tag != expected {
+        return Err(Error::TypeMismatch {
+            expected,
+            stored: tag,
+        });
+    }
+
+    Ok(bincode::decode_from_slice(&stored[tag_len..], BINCODE_CONFIG)?.0)
+}
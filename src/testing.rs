@@ -0,0 +1,377 @@
+//! Helpers to property-test storage logic built on top of ser-sled.
+//!
+//! [`Gen`] is a small deterministic random generator, [`Arbitrary`] lets types
+//! be generated from it, and [`model_check`] runs a random sequence of operations
+//! against a [`StrictTree`] and a `BTreeMap` model, reporting the first divergence.
+//! [`TempDb`] opens a temporary database for integration tests, with trees prefilled
+//! by [`TempDb::bincode_tree_with`].
+use bincode::{Decode, Encode};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Deref;
+
+use crate::bincode_tree::BincodeTree;
+use crate::keys::OrderedKey;
+use crate::{Db, StrictTree};
+
+/// A [`Db`] in a temporary directory, deleted when it is dropped. It derefs to the
+/// [`Db`], and panics instead of returning errors, as tests would.
+#[derive(Clone)]
+pub struct TempDb {
+    db: Db,
+}
+
+impl TempDb {
+    pub fn new() -> Self {
+        Self {
+            db: Db::open_temporary().expect("temporary database should open"),
+        }
+    }
+
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    pub fn sled_db(&self) -> &sled::Db {
+        &self.db.inner_db
+    }
+
+    /// Opens the tree `tree_name` and inserts `entries` into it.
+    pub fn bincode_tree_with<K, V, I>(&self, tree_name: &str, entries: I) -> BincodeTree<K, V>
+    where
+        K: OrderedKey,
+        V: Encode + Decode,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let tree = self
+            .db
+            .open_bincode_tree(tree_name)
+            .expect("tree should open");
+        for (key, value) in entries {
+            tree.insert(&key, &value).expect("entry should be inserted");
+        }
+
+        tree
+    }
+
+    /// Like [`TempDb::bincode_tree_with`], for a [`crate::serde_tree::SerdeTree`].
+    #[cfg(feature = "serde")]
+    pub fn serde_tree_with<K, V, I>(
+        &self,
+        tree_name: &str,
+        entries: I,
+    ) -> crate::serde_tree::SerdeTree<K, V>
+    where
+        K: OrderedKey,
+        V: serde::Serialize + serde::de::DeserializeOwned,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let tree = self
+            .db
+            .open_serde_tree(tree_name)
+            .expect("tree should open");
+        for (key, value) in entries {
+            tree.insert(&key, &value).expect("entry should be inserted");
+        }
+
+        tree
+    }
+}
+
+impl Default for TempDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TempDb {
+    type Target = Db;
+
+    fn deref(&self) -> &Db {
+        &self.db
+    }
+}
+
+/// Deterministic pseudo-random generator (xorshift64*).
+/// The same seed always produces the same sequence, so failures can be replayed.
+#[derive(Debug, Clone)]
+pub struct Gen {
+    state: u64,
+}
+
+impl Gen {
+    pub fn new(seed: u64) -> Self {
+        // xorshift must never have a zero state
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+
+        Self {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in `0..bound`. `bound` must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    pub fn gen_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Picks a reference to one of the items of a non-empty slice.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Types that can be randomly generated from a [`Gen`].
+pub trait Arbitrary: Sized {
+    fn arbitrary(g: &mut Gen) -> Self;
+}
+
+macro_rules! arbitrary_int {
+    ($($t:ty),*) => {
+        $(
+            impl Arbitrary for $t {
+                fn arbitrary(g: &mut Gen) -> Self {
+                    g.next_u64() as $t
+                }
+            }
+        )*
+    };
+}
+
+arbitrary_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Arbitrary for bool {
+    fn arbitrary(g: &mut Gen) -> Self {
+        g.gen_bool()
+    }
+}
+
+impl Arbitrary for char {
+    fn arbitrary(g: &mut Gen) -> Self {
+        // Mostly printable ASCII, sometimes any valid scalar value
+        if g.below(4) == 0 {
+            char::from_u32(g.below(0x11_0000) as u32).unwrap_or('\u{FFFD}')
+        } else {
+            (b' ' + g.below(95) as u8) as char
+        }
+    }
+}
+
+impl Arbitrary for String {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let len = g.below(16);
+        (0..len).map(|_| char::arbitrary(g)).collect()
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Vec<T> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let len = g.below(16);
+        (0..len).map(|_| T::arbitrary(g)).collect()
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if g.gen_bool() {
+            Some(T::arbitrary(g))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Arbitrary, const N: usize> Arbitrary for [T; N] {
+    fn arbitrary(g: &mut Gen) -> Self {
+        std::array::from_fn(|_| T::arbitrary(g))
+    }
+}
+
+macro_rules! arbitrary_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Arbitrary),+> Arbitrary for ($($name,)+) {
+            fn arbitrary(g: &mut Gen) -> Self {
+                ($($name::arbitrary(g),)+)
+            }
+        }
+    };
+}
+
+arbitrary_tuple!(A);
+arbitrary_tuple!(A, B);
+arbitrary_tuple!(A, B, C);
+arbitrary_tuple!(A, B, C, D);
+
+/// An operation applied to both the tree and the model by [`model_check`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op<K, V> {
+    Insert(K, V),
+    Remove(K),
+    Get(K),
+    ContainsKey(K),
+    First,
+    Last,
+    PopMax,
+    PopMin,
+    Len,
+    Iter,
+    Clear,
+}
+
+impl<K: Arbitrary + Clone, V: Arbitrary> Op<K, V> {
+    /// Generates an operation whose key is picked from `keys` most of the time,
+    /// so that operations actually hit existing entries.
+    pub fn generate(g: &mut Gen, keys: &[K]) -> Self {
+        let key = |g: &mut Gen| {
+            if keys.is_empty() || g.below(8) == 0 {
+                K::arbitrary(g)
+            } else {
+                g.choose(keys).clone()
+            }
+        };
+
+        match g.below(100) {
+            0..=34 => Op::Insert(key(g), V::arbitrary(g)),
+            35..=49 => Op::Remove(key(g)),
+            50..=64 => Op::Get(key(g)),
+            65..=74 => Op::ContainsKey(key(g)),
+            75..=79 => Op::First,
+            80..=84 => Op::Last,
+            85..=87 => Op::PopMax,
+            88..=89 => Op::PopMin,
+            90..=94 => Op::Len,
+            95..=98 => Op::Iter,
+            _ => Op::Clear,
+        }
+    }
+}
+
+/// The first operation for which the tree and the model disagreed.
+#[derive(Debug)]
+pub struct ModelCheckFailure<K, V> {
+    pub seed: u64,
+    pub step: usize,
+    pub op: Op<K, V>,
+    pub reason: String,
+}
+
+/// Clears `tree`, then applies `steps` random operations generated from `seed`
+/// to both the tree and a `BTreeMap` model, comparing every result.
+///
+/// The tree orders entries by their encoded bytes, which does not always match
+/// `K`'s `Ord` (e.g. length-prefixed strings), so `first`, `last`, `pop_max` and
+/// `pop_min` are only checked to return an entry of the model, and `iter` is compared
+/// after sorting.
+pub fn model_check<K, V, T>(
+    tree: &T,
+    seed: u64,
+    steps: usize,
+) -> Result<(), ModelCheckFailure<K, V>>
+where
+    T: StrictTree<K, V>,
+    K: Arbitrary + Ord + Clone + Debug,
+    V: Arbitrary + PartialEq + Clone + Debug,
+{
+    let mut g = Gen::new(seed);
+    let mut model: BTreeMap<K, V> = BTreeMap::new();
+    let keys: Vec<K> = (0..1 + g.below(16)).map(|_| K::arbitrary(&mut g)).collect();
+
+    let fail = |step: usize, op: &Op<K, V>, reason: String| ModelCheckFailure {
+        seed,
+        step,
+        op: op.clone(),
+        reason,
+    };
+
+    if let Err(e) = tree.clear() {
+        return Err(fail(0, &Op::Clear, format!("tree error: {e:?}")));
+    }
+
+    for step in 0..steps {
+        let op = Op::generate(&mut g, &keys);
+
+        let outcome: Result<Result<(), String>, crate::error::Error> = (|| {
+            Ok(match &op {
+                Op::Insert(k, v) => {
+                    expect_eq(tree.insert(k, v)?, model.insert(k.clone(), v.clone()))
+                }
+                Op::Remove(k) => expect_eq(tree.remove(k)?, model.remove(k)),
+                Op::Get(k) => expect_eq(tree.get(k)?, model.get(k).cloned()),
+                Op::ContainsKey(k) => expect_eq(tree.contains_key(k)?, model.contains_key(k)),
+                Op::First => expect_member(tree.first()?, &model),
+                Op::Last => expect_member(tree.last()?, &model),
+                Op::PopMax | Op::PopMin => {
+                    let popped = match op {
+                        Op::PopMax => tree.pop_max()?,
+                        _ => tree.pop_min()?,
+                    };
+                    let res = expect_member(popped.clone(), &model);
+                    if let Some((k, _)) = popped {
+                        model.remove(&k);
+                    }
+                    res
+                }
+                Op::Len => expect_eq(tree.len(), model.len()),
+                Op::Iter => {
+                    let mut entries: Vec<(K, V)> = tree.iter().collect();
+                    entries.sort_by(|a, b| a.0.cmp(&b.0));
+                    let expected: Vec<(K, V)> =
+                        model.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                    expect_eq(entries, expected)
+                }
+                Op::Clear => {
+                    tree.clear()?;
+                    model.clear();
+                    Ok(())
+                }
+            })
+        })();
+
+        match outcome {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => return Err(fail(step, &op, reason)),
+            Err(e) => return Err(fail(step, &op, format!("tree error: {e:?}"))),
+        }
+    }
+
+    Ok(())
+}
+
+fn expect_eq<T: PartialEq + Debug>(got: T, expected: T) -> Result<(), String> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "tree returned {got:?}, model expected {expected:?}"
+        ))
+    }
+}
+
+fn expect_member<K: Ord + Debug, V: PartialEq + Debug>(
+    got: Option<(K, V)>,
+    model: &BTreeMap<K, V>,
+) -> Result<(), String> {
+    match got {
+        None if model.is_empty() => Ok(()),
+        None => Err(format!(
+            "tree returned None, model has {} entries",
+            model.len()
+        )),
+        Some((k, v)) => match model.get(&k) {
+            Some(expected) if *expected == v => Ok(()),
+            expected => Err(format!(
+                "tree returned ({k:?}, {v:?}), model has {expected:?} for that key"
+            )),
+        },
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod aggregate_store_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::Db;
+
+    #[derive(Encode, Decode)]
+    enum Event {
+        Deposit(u64),
+        Withdraw(u64),
+    }
+
+    fn fold(balance: u64, event: Event) -> u64 {
+        match event {
+            Event::Deposit(amount) => balance + amount,
+            Event::Withdraw(amount) => balance - amount,
+        }
+    }
+
+    #[test]
+    fn load_replays_events() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let store = ser_db
+            .open_aggregate_store::<Event, u64>("account")
+            .expect("store should open");
+
+        assert_eq!(store.load(0, fold).unwrap(), 0);
+
+        store.append(&Event::Deposit(100)).unwrap();
+        store.append(&Event::Withdraw(30)).unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.load(0, fold).unwrap(), 70);
+        // Not enough events were replayed to take a snapshot
+        assert!(store.latest_snapshot().unwrap().is_none());
+    }
+
+    #[test]
+    fn load_resumes_from_snapshot() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let store = ser_db
+            .open_aggregate_store::<Event, u64>("account")
+            .expect("store should open")
+            .with_snapshot_every(2);
+
+        store.append(&Event::Deposit(100)).unwrap();
+        let second = store.append(&Event::Withdraw(30)).unwrap();
+
+        assert_eq!(store.load(0, fold).unwrap(), 70);
+        assert_eq!(store.latest_snapshot().unwrap(), Some((second, 70)));
+
+        store.append(&Event::Deposit(5)).unwrap();
+        assert_eq!(store.events_since(second).unwrap().count(), 1);
+
+        // Only the last event is replayed, on top of the snapshot
+        let mut replayed = 0;
+        let balance = store
+            .load(0, |balance, event| {
+                replayed += 1;
+                fold(balance, event)
+            })
+            .unwrap();
+
+        assert_eq!(balance, 75);
+        assert_eq!(replayed, 1);
+    }
+
+    #[test]
+    fn snapshot_replaces_older_ones() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let store = ser_db
+            .open_aggregate_store::<Event, u64>("account")
+            .expect("store should open");
+
+        let first = store.append(&Event::Deposit(1)).unwrap();
+        let second = store.append(&Event::Deposit(2)).unwrap();
+
+        store.snapshot(first, &1).unwrap();
+        store.snapshot(second, &3).unwrap();
+
+        assert_eq!(store.latest_snapshot().unwrap(), Some((second, 3)));
+        assert_eq!(store.load(0, fold).unwrap(), 3);
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod any_tree_tests {
+    use crate::any_tree::Codec;
+    use crate::{error::Error, Db, RelaxedBincodeTree, StrictTree};
+
+    #[test]
+    fn codec_from_config() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        for name in ["bincode", "serde"] {
+            let codec: Codec = name.parse().unwrap();
+            let tree = ser_db
+                .open_any_tree::<u64, String>(name, codec)
+                .expect("tree should open");
+            assert_eq!(tree.codec(), codec);
+
+            tree.insert(&2, &"two".to_string()).unwrap();
+            tree.insert(&1, &"one".to_string()).unwrap();
+            assert_eq!(tree.get(&1).unwrap(), Some("one".to_string()));
+
+            let keys: Vec<u64> = tree.iter().rev().map(|(k, _)| k).collect();
+            assert_eq!(keys, vec![2, 1]);
+            assert_eq!(tree.range(2..).unwrap().count(), 1);
+        }
+
+        assert!("json".parse::<Codec>().is_err());
+    }
+
+    #[test]
+    fn strict_decode() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_any_tree::<u8, u8>("strict", Codec::Bincode)
+            .expect("tree should open");
+
+        // Write a `u16` where a `u8` is expected
+        let relaxed = ser_db.open_relaxed_bincode_tree("strict").unwrap();
+        relaxed.insert(&1u8, &300u16).unwrap();
+
+        assert!(matches!(tree.get(&1), Err(Error::TrailingBytes { .. })));
+        assert_eq!(tree.with_strict_decode(false).get(&1).unwrap(), Some(251));
+    }
+
+    #[test]
+    fn with_limit() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        for name in ["bincode", "serde"] {
+            let tree = ser_db
+                .open_any_tree::<u64, Vec<u8>>(name, name.parse().unwrap())
+                .expect("tree should open")
+                .with_limit::<64>();
+
+            tree.insert(&1, &vec![7u8; 8]).unwrap();
+            assert_eq!(tree.get(&1).unwrap(), Some(vec![7u8; 8]));
+
+            tree.insert(&2, &vec![7u8; 128]).unwrap();
+            assert!(matches!(tree.get(&2), Err(Error::LimitExceeded)));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod archive_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    fn archive_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ser_sled_{name}_{}.zst", std::process::id()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("logs").unwrap();
+        for i in 0..2000 {
+            tree.insert(&i, &"the same line, over and over".to_string())
+                .unwrap();
+        }
+
+        let path = archive_path("round_trip");
+        // Plus the schema of `logs`
+        assert_eq!(ser_db.backup_to_archive(&path).unwrap(), 2001);
+        assert!(std::fs::metadata(&path).unwrap().len() < 2000 * 10);
+
+        let restored = Db::open_temporary().unwrap();
+        assert_eq!(restored.restore_from_archive(&path).unwrap(), 2001);
+        std::fs::remove_file(&path).unwrap();
+
+        let tree = restored.open_bincode_tree::<u32, String>("logs").unwrap();
+        assert_eq!(tree.len(), 2000);
+        assert_eq!(
+            tree.get(&1999).unwrap().as_deref(),
+            Some("the same line, over and over")
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u64>("tree").unwrap();
+        for i in 0..100 {
+            tree.insert(&i, &(i as u64)).unwrap();
+        }
+
+        let path = archive_path("corrupted");
+        ser_db.backup_to_archive(&path).unwrap();
+
+        // Flipping a bit of the decompressed content changes the checksum. Rebuild the
+        // archive around the corrupted content, as zstd would catch corrupted frames.
+        let archive = std::fs::read(&path).unwrap();
+        let mut content = zstd::decode_all(&archive[8..]).unwrap();
+        let middle = content.len() / 2;
+        content[middle] ^= 1;
+        let mut corrupted = archive[..8].to_vec();
+        corrupted.extend(zstd::encode_all(content.as_slice(), 0).unwrap());
+        std::fs::write(&path, corrupted).unwrap();
+
+        let restored = Db::open_temporary().unwrap();
+        assert!(matches!(
+            restored.restore_from_archive(&path),
+            Err(Error::InvalidArchive("checksum mismatch"))
+        ));
+        assert!(restored.tree_names().is_empty());
+
+        std::fs::write(&path, b"SERSLEDX").unwrap();
+        assert!(matches!(
+            restored.restore_from_archive(&path),
+            Err(Error::InvalidArchive(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
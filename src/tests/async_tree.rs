@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod async_tree_tests {
+    use crate::{error::Error, Db};
+
+    #[test]
+    fn async_bincode_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_async_bincode_tree::<u64, String>("async")
+            .expect("tree should open");
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            assert_eq!(tree.insert(&1, &"one".to_string()).await.unwrap(), None);
+            assert_eq!(tree.insert(&2, &"two".to_string()).await.unwrap(), None);
+
+            assert_eq!(tree.get(&1).await.unwrap(), Some("one".to_string()));
+            assert_eq!(tree.len().await.unwrap(), 2);
+            assert_eq!(tree.range(2..).await.unwrap(), vec![(2, "two".to_string())]);
+            assert_eq!(tree.remove(&1).await.unwrap(), Some("one".to_string()));
+            assert!(!tree.contains_key(&1).await.unwrap());
+            assert_eq!(tree.pop_max().await.unwrap(), Some((2, "two".to_string())));
+            assert!(tree.is_empty().await.unwrap());
+        });
+    }
+
+    #[test]
+    fn with_limit() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_async_bincode_tree::<u64, Vec<u8>>("with_limit")
+            .expect("tree should open")
+            .with_limit::<64>();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            tree.insert(&1, &vec![7u8; 8]).await.unwrap();
+            assert_eq!(tree.get(&1).await.unwrap(), Some(vec![7u8; 8]));
+
+            tree.insert(&2, &vec![7u8; 128]).await.unwrap();
+            assert!(matches!(tree.get(&2).await, Err(Error::LimitExceeded)));
+        });
+    }
+}
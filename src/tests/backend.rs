@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod backend_tests {
+    use crate::backend::{KvBackend, MemoryBackend, MemoryTree, MEMORY_ITER_CHUNK};
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::tree::Batch;
+    use crate::{bincode_tree::BincodeTree, error::Error, StrictTree};
+    use std::ops::Bound::{Excluded, Included};
+
+    fn exercise<T: StrictTree<i32, String>>(tree: T) {
+        for key in [3, -1, 2, 10] {
+            assert_eq!(tree.insert(&key, &key.to_string()).unwrap(), None);
+        }
+        assert_eq!(
+            tree.insert(&2, &"two".to_string()).unwrap().as_deref(),
+            Some("2")
+        );
+
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.get(&2).unwrap().as_deref(), Some("two"));
+        assert!(tree.contains_key(&-1).unwrap());
+        assert_eq!(
+            tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![-1, 2, 3, 10]
+        );
+        assert_eq!(
+            tree.range(0..=3)
+                .unwrap()
+                .rev()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+        assert_eq!(tree.range((Included(5), Excluded(1))).unwrap().count(), 0);
+        assert_eq!(tree.first().unwrap(), Some((-1, "-1".to_string())));
+        assert_eq!(tree.pop_max().unwrap(), Some((10, "10".to_string())));
+        assert_eq!(
+            tree.pop_n_min(2).unwrap(),
+            vec![(-1, "-1".to_string()), (2, "two".to_string())]
+        );
+        assert_eq!(tree.remove(&3).unwrap().as_deref(), Some("3"));
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn sled_backend() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        exercise(BincodeTree::<i32, String>::new(
+            db.open_tree("backend").unwrap(),
+        ));
+    }
+
+    // Constructors written against `StrictTree`, as in a downstream crate
+    fn open<T: StrictTree<i32, String>>(tree: sled::Tree) -> T {
+        T::new(tree)
+    }
+
+    #[test]
+    fn strict_tree_new() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        exercise(open::<BincodeTree<i32, String>>(
+            db.open_tree("new").unwrap(),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be opened from a sled tree")]
+    fn memory_tree_from_sled() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        open::<MemoryTree<i32, String>>(db.open_tree("new").unwrap());
+    }
+
+    #[test]
+    fn memory_backend() {
+        let backend = MemoryBackend::new();
+        exercise(MemoryTree::<i32, String>::with_backend(backend.clone()));
+
+        let tree = MemoryTree::<i32, String>::with_backend(backend.clone());
+        tree.insert(&1, &"one".to_string()).unwrap();
+        assert_eq!(backend.len(), 1);
+    }
+
+    #[test]
+    fn memory_iter_chunks() {
+        let backend = MemoryBackend::new();
+        let len = MEMORY_ITER_CHUNK as u32 * 2 + 1;
+        for i in 0..len {
+            KvBackend::insert(&backend, &i.to_be_bytes(), &[]).unwrap();
+        }
+        let key = |i: u32| i.to_be_bytes().to_vec();
+
+        assert_eq!(KvBackend::iter(&backend).count(), len as usize);
+        assert_eq!(KvBackend::iter(&backend).rev().count(), len as usize);
+        assert_eq!(backend.first().unwrap().unwrap().0.to_vec(), key(0));
+        assert_eq!(backend.last().unwrap().unwrap().0.to_vec(), key(len - 1));
+
+        // Both ends meet without skipping or repeating entries
+        let mut iter = KvBackend::iter(&backend);
+        let mut keys = Vec::new();
+        while let Some(entry) = iter.next() {
+            keys.push(entry.unwrap().0.to_vec());
+            if let Some(entry) = iter.next_back() {
+                keys.push(entry.unwrap().0.to_vec());
+            }
+        }
+        keys.sort();
+        assert_eq!(keys, (0..len).map(key).collect::<Vec<_>>());
+
+        // Entries are read as the iterator advances
+        let mut iter = KvBackend::iter(&backend);
+        iter.next();
+        backend.clear().unwrap();
+        assert_eq!(iter.count(), MEMORY_ITER_CHUNK - 1);
+    }
+
+    #[test]
+    fn same_encoding_as_sled_trees() {
+        let ser_db = crate::Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<i32, String>("shared").unwrap();
+        tree.insert(&7, &"seven".to_string()).unwrap();
+
+        // The raw entries of the sled tree decode the same in memory
+        let backend = MemoryBackend::new();
+        for entry in KvBackend::iter(tree.inner()) {
+            let (key, value) = entry.unwrap();
+            KvBackend::insert(&backend, &key, &value).unwrap();
+        }
+        let memory = MemoryTree::<i32, String>::with_backend(backend);
+        assert_eq!(memory.get(&7).unwrap().as_deref(), Some("seven"));
+    }
+
+    #[test]
+    fn memory_quota() {
+        let tree = MemoryTree::<u64, u64>::default()
+            .with_quota(Quota::new(QuotaPolicy::EvictOldest).with_max_entries(2));
+
+        let mut batch = Batch::new();
+        for i in 0..3 {
+            batch.insert(i, i);
+        }
+        tree.apply_batch(batch).unwrap();
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+
+        tree.insert(&3, &3).unwrap();
+        assert_eq!(tree.first().unwrap(), Some((2, 2)));
+        assert_eq!(tree.len(), 2);
+    }
+
+    // Storage logic written against `StrictTree`, as in a downstream crate
+    fn rename<T: StrictTree<u64, String>>(tree: &T, id: u64, name: &str) -> Result<bool, Error> {
+        if !tree.contains_key(&id)? {
+            return Ok(false);
+        }
+
+        tree.insert(&id, &name.to_string())?;
+        Ok(true)
+    }
+
+    #[test]
+    fn memory_tree() {
+        let tree = MemoryTree::<u64, String>::from_entries([(1, "a".to_string())]).unwrap();
+        let shared = tree.clone();
+
+        assert!(rename(&tree, 1, "b").unwrap());
+        assert!(!rename(&tree, 2, "c").unwrap());
+        assert_eq!(shared.get(&1).unwrap().as_deref(), Some("b"));
+        assert_eq!(shared.len(), 1);
+
+        let empty = MemoryTree::<u64, String>::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.first().unwrap(), None);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod relaxed_serde_tests {
-    use crate::{Db, RelaxedBincodeTree};
+    use crate::{error::Error, Db, RelaxedBincodeTree};
 
     #[test]
     fn insert_and_get() {
@@ -229,11 +229,32 @@ mod relaxed_serde_tests {
         assert_eq!(iter.next(), Some(([4u8], [4u8])));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn with_limit() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_relaxed_bincode_tree("with_limit")
+            .expect("tree should open")
+            .with_limit::<64>();
+
+        tree.insert(&1u8, &vec![7u8; 8]).unwrap();
+        assert_eq!(tree.get(&1u8).unwrap(), Some(vec![7u8; 8]));
+
+        // A huge length prefix must not be trusted
+        tree.insert(&2u8, &u64::MAX).unwrap();
+        assert!(matches!(
+            tree.get::<_, Vec<u8>>(&2u8),
+            Err(Error::LimitExceeded)
+        ));
+    }
 }
 
 #[cfg(test)]
 mod strict_serde_tests {
-    use crate::{Db, StrictTree};
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::{error::Error, Db, RelaxedBincodeTree, StrictTree};
 
     #[test]
     fn insert_and_get() {
@@ -274,6 +295,82 @@ mod strict_serde_tests {
         );
     }
 
+    #[test]
+    fn get_or_insert_with() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u32, String>("get_or_insert_with")
+            .expect("tree should open");
+
+        assert_eq!(
+            tree.get_or_insert_with(1, || "angel".to_string()).unwrap(),
+            "angel"
+        );
+        // The stored value wins over the new one
+        assert_eq!(
+            tree.get_or_insert_with(1, || "chip".to_string()).unwrap(),
+            "angel"
+        );
+    }
+
+    #[test]
+    fn insert_if_absent() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u32, String>("insert_if_absent")
+            .expect("tree should open");
+
+        assert!(tree.insert_if_absent(&1, &"angel".to_string()).unwrap());
+        assert!(!tree.insert_if_absent(&1, &"chip".to_string()).unwrap());
+        assert_eq!(tree.get(&1).unwrap(), Some("angel".to_string()));
+
+        let relaxed = ser_db
+            .open_relaxed_bincode_tree("relaxed_insert_if_absent")
+            .expect("tree should open");
+        assert!(relaxed
+            .insert_if_absent(&1u32, &"chip".to_string())
+            .unwrap());
+        assert!(!relaxed.insert_if_absent(&1u32, &"nyx".to_string()).unwrap());
+    }
+
+    #[test]
+    fn insert_auto() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("insert_auto")
+            .expect("tree should open");
+
+        let first = tree.insert_auto(&ser_db, &"angel".to_string()).unwrap();
+        ser_db.generate_id().unwrap();
+        let second = tree.insert_auto(&ser_db, &"chip".to_string()).unwrap();
+        assert!(second > first);
+
+        assert_eq!(tree.get(&first).unwrap(), Some("angel".to_string()));
+        assert_eq!(
+            tree.iter().map(|(_, value)| value).collect::<Vec<_>>(),
+            vec!["angel".to_string(), "chip".to_string()]
+        );
+    }
+
+    #[test]
+    fn insert_auto_quota() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("insert_auto_quota")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(1));
+
+        tree.insert_auto(&ser_db, &"angel".to_string()).unwrap();
+        assert!(matches!(
+            tree.insert_auto(&ser_db, &"chip".to_string()),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(tree.len(), 1);
+    }
+
     #[test]
     fn first_and_last() {
         let db = sled::Config::new().temporary(true).open().unwrap();
@@ -465,4 +562,691 @@ mod strict_serde_tests {
         assert_eq!(iter.next(), Some(([4u8], [4u8])));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn strict_decode() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u8, u64>("strict_decode")
+            .expect("tree should open");
+        tree.insert(&1, &300).unwrap();
+
+        // Decoding a `u64` as a `u8` leaves trailing bytes behind
+        ser_db.reset_schema("strict_decode").unwrap();
+        let wrong_tree = ser_db
+            .open_bincode_tree::<u8, u8>("strict_decode")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_tree.get(&1),
+            Err(Error::TrailingBytes { decoded: 1, len: 3 })
+        ));
+        assert!(wrong_tree.iter().next().is_none());
+
+        let lenient_tree = wrong_tree.with_strict_decode(false);
+        assert!(lenient_tree.get(&1).unwrap().is_some());
+    }
+
+    #[test]
+    fn key_validation() {
+        use crate::keys::BincodeKeyCodec;
+        use bincode::{de::Decoder, enc::Encoder, error::DecodeError, error::EncodeError};
+        use bincode::{Decode, Encode};
+
+        /// Decoding drops the high byte, so it doesn't round-trip
+        #[derive(Debug, PartialEq)]
+        struct LossyKey(u16);
+
+        impl Encode for LossyKey {
+            fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+                self.0.encode(encoder)
+            }
+        }
+
+        impl Decode for LossyKey {
+            fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+                Ok(LossyKey(u16::decode(decoder)? & 0xff))
+            }
+        }
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree_with_key_codec::<LossyKey, u8, BincodeKeyCodec>("key_validation")
+            .expect("tree should open")
+            .with_key_validation(true);
+
+        tree.insert(&LossyKey(7), &1).unwrap();
+        assert!(matches!(
+            tree.insert(&LossyKey(0x1234), &2),
+            Err(Error::KeyRoundTrip)
+        ));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn rewrite_in_place() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u8, u8>("rewrite")
+            .expect("tree should open")
+            .with_strict_decode(false);
+
+        tree.insert(&1, &10).unwrap();
+        // Decodes despite its trailing bytes, which the rewrite drops
+        tree.insert_raw([2, 0], &[20u8, 0][..]).unwrap();
+        assert_eq!(tree.rewrite_in_place().unwrap(), 1);
+        assert_eq!(tree.get_raw([2]).unwrap().as_deref(), Some(&[20][..]));
+        assert_eq!(tree.get_raw([2, 0]).unwrap(), None);
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, 10), (2, 20)]);
+
+        // Nothing is left to rewrite
+        assert_eq!(tree.rewrite_in_place().unwrap(), 0);
+    }
+
+    #[test]
+    fn failed_rewrite_leaves_tree_readable() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u8, u8>("failed_rewrite")
+            .expect("tree should open");
+        for i in 0..10 {
+            tree.insert(&i, &(i * 10)).unwrap();
+        }
+
+        // An entry of the wrong type aborts the rewrite before anything is written
+        let relaxed = ser_db.open_relaxed_bincode_tree("failed_rewrite").unwrap();
+        relaxed.insert(&10u8, &300u64).unwrap();
+        assert!(tree.rewrite_in_place().is_err());
+
+        assert_eq!(tree.len(), 11);
+        assert_eq!(
+            tree.range(..10).unwrap().collect::<Vec<_>>(),
+            (0..10).map(|i| (i, i * 10)).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod projection_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::{projection::FieldSelector, Db, StrictTree};
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct User {
+        id: u64,
+        name: String,
+        bio: String,
+    }
+
+    impl User {
+        fn name_field() -> FieldSelector<User, (u64,), String> {
+            FieldSelector::new()
+        }
+    }
+
+    #[test]
+    fn project() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, User>("project")
+            .expect("tree should open");
+
+        let user = User {
+            id: 300,
+            name: "angel".to_string(),
+            bio: "a very long bio".repeat(100),
+        };
+        tree.insert(&1, &user).unwrap();
+
+        assert_eq!(
+            tree.project(&1, User::name_field()).unwrap(),
+            Some("angel".to_string())
+        );
+        assert_eq!(tree.project(&2, User::name_field()).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::{Db, StrictTree};
+
+    #[derive(Encode, Decode, Debug, PartialEq, Clone)]
+    struct User {
+        id: u64,
+        name: String,
+        visits: u64,
+    }
+
+    crate::patch_struct! {
+        struct UserPatch for User {
+            name: String,
+            visits: u64,
+        }
+    }
+
+    #[test]
+    fn patch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, User>("patch")
+            .expect("tree should open");
+
+        let user = User {
+            id: 1,
+            name: "angel".to_string(),
+            visits: 0,
+        };
+        tree.insert(&1, &user).unwrap();
+
+        let rename = UserPatch {
+            name: Some("chip".to_string()),
+            ..Default::default()
+        };
+        let patched = tree.patch(&1, &rename).unwrap().unwrap();
+        assert_eq!(patched.name, "chip");
+        assert_eq!(patched.visits, 0);
+        assert_eq!(tree.get(&1).unwrap(), Some(patched));
+
+        assert_eq!(tree.patch(&2, &rename).unwrap(), None);
+        assert!(!tree.contains_key(&2).unwrap());
+    }
+
+    #[test]
+    fn concurrent_patches() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, User>("concurrent_patches")
+            .expect("tree should open");
+
+        tree.insert(
+            &1,
+            &User {
+                id: 1,
+                name: "angel".to_string(),
+                visits: 0,
+            },
+        )
+        .unwrap();
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        tree.patch(&1, &|user: &mut User| user.visits += 1).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(tree.get(&1).unwrap().unwrap().visits, 200);
+    }
+}
+
+#[cfg(test)]
+mod bucket_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn bucket() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<(u64, u64), String>("bucket")
+            .expect("tree should open");
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"first".to_string()).unwrap();
+        tree.insert(&(42, 2), &"second".to_string()).unwrap();
+        tree.insert(&(43, 1), &"other".to_string()).unwrap();
+
+        let orders = tree.bucket::<u64, u64>(&42).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            orders,
+            vec![(1, "first".to_string()), (2, "second".to_string())]
+        );
+        assert_eq!(tree.bucket::<u64, u64>(&44).unwrap().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use crate::{bincode_tree::Batch, Db, StrictTree};
+
+    #[test]
+    fn apply_batch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("batch")
+            .expect("tree should open");
+
+        tree.insert(&1, &"removed".to_string()).unwrap();
+
+        let mut batch = Batch::new();
+        batch.insert(2, "two".to_string());
+        batch.insert(3, "three".to_string());
+        batch.remove(1);
+        batch.remove(3);
+        assert_eq!(batch.len(), 4);
+
+        tree.apply_batch(batch).unwrap();
+
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec![(2, "two".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod update_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn update_and_fetch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<String, u64>("update")
+            .expect("tree should open");
+        let key = "visits".to_string();
+        let increment = |old: Option<u64>| Some(old.unwrap_or(0) + 1);
+
+        assert_eq!(tree.update_and_fetch(&key, increment).unwrap(), Some(1));
+        assert_eq!(tree.update_and_fetch(&key, increment).unwrap(), Some(2));
+        assert_eq!(tree.fetch_and_update(&key, increment).unwrap(), Some(2));
+        assert_eq!(tree.get(&key).unwrap(), Some(3));
+
+        // Returning None removes the entry
+        assert_eq!(tree.fetch_and_update(&key, |_| None).unwrap(), Some(3));
+        assert!(!tree.contains_key(&key).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn merge() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<String, Vec<String>>("merge")
+            .expect("tree should open");
+        let key = "log".to_string();
+
+        // No operator set yet
+        assert!(tree.merge(&key, &vec!["a".to_string()]).is_err());
+
+        tree.set_merge_operator(|_key: String, old: Option<Vec<String>>, delta| {
+            let mut list = old.unwrap_or_default();
+            list.extend(delta);
+            Some(list)
+        });
+
+        tree.merge(&key, &vec!["a".to_string()]).unwrap();
+        assert_eq!(
+            tree.merge(&key, &vec!["b".to_string()]).unwrap(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            tree.get(&key).unwrap(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+}
+
+#[cfg(test)]
+mod watch_tests {
+    use std::time::Duration;
+
+    use crate::{subscriber::Event, Db, StrictTree};
+
+    #[test]
+    fn watch_prefix() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<(u64, u64), String>("watch")
+            .expect("tree should open");
+        let timeout = Duration::from_secs(1);
+
+        let mut user_42 = tree.watch_prefix(&42u64).unwrap();
+        let mut all = tree.watch().unwrap();
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"order".to_string()).unwrap();
+        tree.remove(&(42, 1)).unwrap();
+
+        assert_eq!(
+            user_42.next_timeout(timeout).unwrap(),
+            Event::Insert {
+                key: (42, 1),
+                value: "order".to_string()
+            }
+        );
+        assert_eq!(
+            user_42.next_timeout(timeout).unwrap(),
+            Event::Remove { key: (42, 1) }
+        );
+        assert!(user_42.next_timeout(Duration::from_millis(10)).is_err());
+
+        assert_eq!(all.next_timeout(timeout).unwrap().key(), &(41, 1));
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod stream_tests {
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    use futures_core::Stream;
+
+    use crate::{subscriber::Event, Db, StrictTree};
+
+    #[test]
+    fn subscriber_stream() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("stream")
+            .expect("tree should open");
+
+        let mut stream = tree.watch().unwrap();
+        tree.insert(&1, &"one".to_string()).unwrap();
+
+        let event = futures_executor::block_on(poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)));
+        assert_eq!(
+            event,
+            Some(Event::Insert {
+                key: 1,
+                value: "one".to_string()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod pop_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn pop_min_and_pop_n() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, u64>("pop")
+            .expect("tree should open");
+
+        for i in 0..10 {
+            tree.insert(&i, &(i * 10)).unwrap();
+        }
+
+        assert_eq!(tree.pop_min().unwrap(), Some((0, 0)));
+        assert_eq!(tree.pop_n_min(3).unwrap(), vec![(1, 10), (2, 20), (3, 30)]);
+        assert_eq!(tree.pop_n_max(2).unwrap(), vec![(9, 90), (8, 80)]);
+        assert_eq!(tree.len(), 4);
+
+        // Popping more than what's left empties the tree
+        assert_eq!(tree.pop_n_min(10).unwrap().len(), 4);
+        assert_eq!(tree.pop_min().unwrap(), None);
+        assert!(tree.pop_n_max(1).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod scan_prefix_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn scan_prefix() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<(u64, u64), String>("scan_prefix")
+            .expect("tree should open");
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"first".to_string()).unwrap();
+        tree.insert(&(42, 2), &"second".to_string()).unwrap();
+        tree.insert(&(43, 1), &"other".to_string()).unwrap();
+
+        let expected = vec![
+            ((42, 1), "first".to_string()),
+            ((42, 2), "second".to_string()),
+        ];
+        assert_eq!(
+            tree.scan_prefix(&42u64).unwrap().collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            tree.scan_prefix_bytes(42u64.to_be_bytes())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(tree.scan_prefix(&44u64).unwrap().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod try_iter_tests {
+    use crate::{Db, RelaxedBincodeTree, StrictTree};
+
+    #[test]
+    fn try_iter() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u8, u8>("try_iter")
+            .expect("tree should open");
+
+        tree.insert(&1, &10).unwrap();
+        tree.insert(&3, &30).unwrap();
+
+        // Write a `u64` where a `u8` is expected
+        let relaxed = ser_db.open_relaxed_bincode_tree("try_iter").unwrap();
+        relaxed.insert(&2u8, &300u64).unwrap();
+
+        assert_eq!(tree.iter().count(), 2);
+
+        let entries = tree.try_iter().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].as_ref().unwrap(), &(1, 10));
+        assert!(entries[1].is_err());
+
+        let entries = tree.try_range(2..).unwrap().collect::<Vec<_>>();
+        assert!(entries[0].is_err());
+        assert_eq!(entries[1].as_ref().unwrap(), &(3, 30));
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use crate::{
+        check::ReportLevel, codec::BincodeCodec, keys::BincodeKeyCodec, tree::Tree, Db,
+        RelaxedBincodeTree, StrictTree,
+    };
+
+    #[test]
+    fn fixed_int_encoding() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let config = bincode::config::standard().with_fixed_int_encoding();
+        let tree = ser_db
+            .open_bincode_tree_with_config::<u8, u64, _>("fixed_int_encoding", config)
+            .expect("tree should open");
+
+        tree.insert(&1, &7).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(7));
+
+        let raw = ser_db
+            .open_relaxed_bincode_tree("fixed_int_encoding")
+            .unwrap()
+            .with_config(config);
+        assert_eq!(raw.get::<_, u64>(&1u8).unwrap(), Some(7));
+
+        // The checker validates entries with the tree's configuration
+        let report = ser_db.check(ReportLevel::Summary).unwrap();
+        assert!(report.is_clean());
+        assert!(report.tree("fixed_int_encoding").unwrap().checked);
+    }
+
+    #[test]
+    fn fixed_int_keys() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let config = bincode::config::standard().with_fixed_int_encoding();
+        let tree: Tree<u32, u32, BincodeCodec, _, BincodeKeyCodec> = ser_db
+            .open_tree_with_config("fixed_int_keys", config)
+            .expect("tree should open");
+
+        tree.insert(&1, &2).unwrap();
+
+        let (key, value) = ser_db
+            .open_sled_tree("fixed_int_keys")
+            .unwrap()
+            .first()
+            .unwrap()
+            .unwrap();
+        assert_eq!(key.len(), 4);
+        assert_eq!(value.len(), 4);
+    }
+
+    #[test]
+    fn relaxed_with_config() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_relaxed_bincode_tree("relaxed_with_config")
+            .expect("tree should open")
+            .with_config(bincode::config::standard().with_limit::<16>());
+
+        tree.insert(&1u8, &vec![7u8; 8]).unwrap();
+        assert_eq!(tree.get(&1u8).unwrap(), Some(vec![7u8; 8]));
+
+        tree.insert(&2u8, &vec![7u8; 32]).unwrap();
+        assert!(matches!(
+            tree.get::<_, Vec<u8>>(&2u8),
+            Err(crate::error::Error::LimitExceeded)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod raw_access_tests {
+    use crate::error::Error;
+    use crate::keys::to_ordered_bytes;
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::{Db, StrictTree, BINCODE_CONFIG};
+
+    #[test]
+    fn strict_raw_access() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("raw").unwrap();
+        tree.insert(&1, &"one".to_string()).unwrap();
+
+        let key = to_ordered_bytes(&1u32);
+        let stored = tree.get_raw(&key).unwrap().unwrap();
+        assert_eq!(tree.inner().get(&key).unwrap(), Some(stored.clone()));
+        assert_eq!(
+            bincode::decode_from_slice::<String, _>(&stored, BINCODE_CONFIG)
+                .unwrap()
+                .0,
+            "one"
+        );
+
+        let two = bincode::encode_to_vec("two".to_string(), BINCODE_CONFIG).unwrap();
+        assert_eq!(tree.insert_raw(to_ordered_bytes(&2u32), two).unwrap(), None);
+        assert_eq!(tree.get(&2).unwrap().as_deref(), Some("two"));
+
+        assert_eq!(tree.remove_raw(&key).unwrap(), Some(stored));
+        assert!(!tree.contains_key(&1).unwrap());
+
+        // Raw writes are seen by other handles of the same tree
+        tree.inner()
+            .insert(
+                &key,
+                tree.get_raw(to_ordered_bytes(&2u32)).unwrap().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("two"));
+    }
+
+    #[test]
+    fn relaxed_raw_access() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_relaxed_bincode_tree("raw")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(1));
+
+        tree.insert_raw(b"a", b"1".to_vec()).unwrap();
+        assert_eq!(tree.get_raw(b"a").unwrap().as_deref(), Some(&b"1"[..]));
+        assert_eq!(tree.inner().len(), 1);
+
+        // Raw writes are counted by the quota
+        assert!(matches!(
+            tree.insert_raw(b"b", b"2".to_vec()),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(tree.remove_raw(b"a").unwrap().as_deref(), Some(&b"1"[..]));
+        tree.insert_raw(b"b", b"2".to_vec()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod relaxed_conversion_tests {
+    use crate::keys::BincodeKeyCodec;
+    use crate::{Db, RelaxedBincodeTree, StrictTree};
+
+    #[test]
+    fn into_relaxed_and_back() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("migrate").unwrap();
+        tree.insert(&1, &10).unwrap();
+        tree.insert(&2, &20).unwrap();
+
+        // Rewrite every value as a `String`, keeping the keys as they are stored
+        let relaxed = tree.into_relaxed();
+        let entries: Vec<_> = relaxed.inner().iter().map(|entry| entry.unwrap()).collect();
+        for (key, value) in entries {
+            let (value, _): (u32, _) =
+                bincode::decode_from_slice(&value, crate::BINCODE_CONFIG).unwrap();
+            let value = bincode::encode_to_vec(value.to_string(), crate::BINCODE_CONFIG).unwrap();
+            relaxed.insert_raw(key, value).unwrap();
+        }
+
+        let tree = relaxed.assume_types::<u32, String>();
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("10"));
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec![(1, "10".to_string()), (2, "20".to_string())]
+        );
+    }
+
+    #[test]
+    fn assume_types_with_bincode_keys() {
+        let ser_db = Db::open_temporary().unwrap();
+        let relaxed = ser_db.open_relaxed_bincode_tree("relaxed_keys").unwrap();
+        relaxed.insert(&7u64, &"seven".to_string()).unwrap();
+
+        let tree = relaxed.assume_types_with_key_codec::<u64, String, BincodeKeyCodec>();
+        assert_eq!(tree.get(&7).unwrap().as_deref(), Some("seven"));
+        tree.insert(&8, &"eight".to_string()).unwrap();
+
+        let relaxed = tree.into_relaxed();
+        assert_eq!(
+            relaxed.get::<u64, String>(&8).unwrap().as_deref(),
+            Some("eight")
+        );
+        assert_eq!(relaxed.iter::<u64, String>().count(), 2);
+    }
 }
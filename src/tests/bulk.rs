@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod insert_all_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn insert_all() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tree").unwrap();
+        tree.insert(&0, &"old".to_string()).unwrap();
+
+        // More entries than fit in one batch
+        let inserted = tree
+            .insert_all((0..2500).map(|i| (i, i.to_string())))
+            .unwrap();
+        assert_eq!(inserted, 2500);
+        assert_eq!(tree.len(), 2500);
+        assert_eq!(tree.get(&0).unwrap(), Some("0".to_string()));
+        assert_eq!(tree.get(&2499).unwrap(), Some("2499".to_string()));
+
+        assert_eq!(tree.insert_all(Vec::new()).unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod get_many_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn get_many() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tree").unwrap();
+        tree.insert(&1, &"one".to_string()).unwrap();
+        tree.insert(&3, &"three".to_string()).unwrap();
+
+        assert_eq!(
+            tree.get_many(&[3, 2, 1, 3]).unwrap(),
+            vec![
+                Some("three".to_string()),
+                None,
+                Some("one".to_string()),
+                Some("three".to_string())
+            ]
+        );
+        assert!(tree.get_many(&[]).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod remove_range_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn remove_range() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<i32, i32>("tree").unwrap();
+        tree.insert_all((-100..2500).map(|i| (i, i))).unwrap();
+
+        // Spans several batches, and negative keys sort before positive ones
+        assert_eq!(tree.remove_range(-50..2400).unwrap(), 2450);
+        assert_eq!(tree.len(), 150);
+        assert_eq!(tree.first().unwrap(), Some((-100, -100)));
+        assert_eq!(tree.get(&-51).unwrap(), Some(-51));
+        assert_eq!(tree.get(&-50).unwrap(), None);
+        assert_eq!(tree.get(&2400).unwrap(), Some(2400));
+
+        assert_eq!(tree.remove_range(0..10).unwrap(), 0);
+        assert_eq!(tree.remove_range(..).unwrap(), 150);
+        assert!(tree.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod retain_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn retain() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tree").unwrap();
+        tree.insert_all((0..2500).map(|i| (i, i.to_string())))
+            .unwrap();
+
+        let removed = tree
+            .retain(|key, value| key % 2 == 0 && value.len() < 4)
+            .unwrap();
+        assert_eq!(removed, 2000);
+        assert_eq!(tree.len(), 500);
+        assert!(tree.iter().all(|(key, _)| key % 2 == 0 && key < 1000));
+
+        assert_eq!(tree.retain(|_, _| true).unwrap(), 0);
+        assert_eq!(tree.retain(|_, _| false).unwrap(), 500);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn stops_at_corrupted_entry() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.clone().into();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("tree").unwrap();
+        tree.insert(&1, &1).unwrap();
+        db.open_tree("tree")
+            .unwrap()
+            .insert(tree.encode_key(&2).unwrap(), vec![0xff])
+            .unwrap();
+
+        assert!(tree.retain(|_, _| false).is_err());
+    }
+}
+
+#[cfg(test)]
+mod drain_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn drain() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("tree").unwrap();
+        tree.insert_all((0..10).map(|i| (i, i * 10))).unwrap();
+
+        let mut drain = tree.drain_range(2..5).unwrap();
+        assert_eq!(drain.next().unwrap().unwrap(), (2, 20));
+        // Taken by another consumer
+        assert_eq!(tree.remove(&3).unwrap(), Some(30));
+        assert_eq!(drain.next().unwrap().unwrap(), (4, 40));
+        assert!(drain.next().is_none());
+        assert_eq!(tree.len(), 7);
+
+        // Dropping the iterator keeps the rest
+        assert_eq!(tree.drain().next().unwrap().unwrap(), (0, 0));
+        assert_eq!(tree.len(), 6);
+
+        let drained = tree.drain().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            drained,
+            vec![(1, 10), (5, 50), (6, 60), (7, 70), (8, 80), (9, 90)]
+        );
+        assert!(tree.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod count_range_tests {
+    use crate::Db;
+
+    #[test]
+    fn count_range() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<i64, u32>("tree").unwrap();
+        tree.insert_all((-10..10).map(|i| (i, 0))).unwrap();
+
+        assert_eq!(tree.count_range(..).unwrap(), 20);
+        assert_eq!(tree.count_range(-5..5).unwrap(), 10);
+        assert_eq!(tree.count_range(0..=9).unwrap(), 10);
+        assert_eq!(tree.count_range(100..).unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod fold_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn fold() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, (String, u64)>("tree")
+            .unwrap();
+        for (i, (name, score)) in [("angel", 30), ("chip", 10), ("nyx", 30), ("iris", 5)]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(&(i as u32), &(name.to_string(), score))
+                .unwrap();
+        }
+
+        let names = tree
+            .fold(1..3, String::new(), |acc, (_, (name, _))| acc + &name)
+            .unwrap();
+        assert_eq!(names, "chipnyx");
+
+        assert_eq!(tree.sum_by(.., |_, (_, score)| *score).unwrap(), 75);
+        assert_eq!(tree.sum_by(10.., |_, (_, score)| *score).unwrap(), 0);
+
+        assert_eq!(
+            tree.min_by_key(.., |_, (_, score)| *score).unwrap(),
+            Some((3, ("iris".to_string(), 5)))
+        );
+        // Ties go to the first minimum and the last maximum, like `Iterator`
+        assert_eq!(
+            tree.min_by_key(..3, |_, (_, score)| *score / 100).unwrap(),
+            Some((0, ("angel".to_string(), 30)))
+        );
+        assert_eq!(
+            tree.max_by_key(.., |_, (_, score)| *score).unwrap(),
+            Some((2, ("nyx".to_string(), 30)))
+        );
+        assert_eq!(tree.max_by_key(10.., |_, (_, score)| *score).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod bulk_load_tests {
+    use crate::tree::BULK_LOAD_CHUNK_SIZE;
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn bulk_load() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<i64, String>("tree").unwrap();
+        tree.insert(&0, &"old".to_string()).unwrap();
+
+        // More than one chunk, loaded in reverse order
+        let count = BULK_LOAD_CHUNK_SIZE as i64 + 500;
+        let loaded = tree
+            .bulk_load((0..count).rev().map(|i| (i - 10, i.to_string())))
+            .unwrap();
+        assert_eq!(loaded, count as usize);
+        assert_eq!(tree.len(), count as usize);
+        assert_eq!(tree.first().unwrap(), Some((-10, "0".to_string())));
+        assert_eq!(tree.get(&0).unwrap(), Some("10".to_string()));
+
+        // Same result on a single thread, and the last duplicate wins
+        let single = ser_db.open_bincode_tree::<i64, String>("single").unwrap();
+        let entries = [(2, "a"), (1, "b"), (2, "c")].map(|(k, v)| (k, v.to_string()));
+        assert_eq!(single.bulk_load_with_threads(entries, 1).unwrap(), 3);
+        assert_eq!(
+            single.iter().collect::<Vec<_>>(),
+            vec![(1, "b".to_string()), (2, "c".to_string())]
+        );
+
+        assert_eq!(tree.bulk_load(Vec::new()).unwrap(), 0);
+    }
+
+    #[test]
+    fn bulk_load_checks_keys() {
+        use crate::keys::OrderedKey;
+
+        /// Decoding drops the high byte, so it doesn't round-trip
+        struct LossyKey(u16);
+
+        impl OrderedKey for LossyKey {
+            fn encode_ordered(&self, out: &mut Vec<u8>) {
+                self.0.encode_ordered(out);
+            }
+
+            fn decode_ordered(input: &mut &[u8]) -> Result<Self, Error> {
+                Ok(LossyKey(u16::decode_ordered(input)? & 0xff))
+            }
+        }
+
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<LossyKey, u8>("tree")
+            .unwrap()
+            .with_key_validation(true);
+
+        assert!(matches!(
+            tree.bulk_load([(LossyKey(1), 1), (LossyKey(0x1234), 2)]),
+            Err(Error::KeyRoundTrip)
+        ));
+        assert!(tree.is_empty());
+    }
+}
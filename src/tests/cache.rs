@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod cache_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let ser_db = Db::open_temporary().unwrap();
+        let cache = ser_db.open_cache_tree::<u32, String>("cache", 2).unwrap();
+
+        cache.insert(&1, &"one".to_string()).unwrap();
+        cache.insert(&2, &"two".to_string()).unwrap();
+        // Reading 1 makes 2 the least recently used
+        assert_eq!(cache.get(&1).unwrap(), Some("one".to_string()));
+        cache.insert(&3, &"three".to_string()).unwrap();
+
+        assert_eq!(cache.len().unwrap(), 2);
+        assert!(!cache.contains_key(&2).unwrap());
+        assert!(cache.contains_key(&1).unwrap());
+
+        // Peeking doesn't count as an access
+        assert_eq!(cache.peek(&1).unwrap(), Some("one".to_string()));
+        cache.insert(&4, &"four".to_string()).unwrap();
+        assert_eq!(cache.peek(&1).unwrap(), None);
+        assert_eq!(cache.tree().len(), 2);
+    }
+
+    #[test]
+    fn overwrite_and_remove() {
+        let ser_db = Db::open_temporary().unwrap();
+        let cache = ser_db.open_cache_tree::<u32, u32>("cache", 2).unwrap();
+
+        assert_eq!(cache.insert(&1, &10).unwrap(), None);
+        assert_eq!(cache.insert(&1, &11).unwrap(), Some(10));
+        assert_eq!(cache.len().unwrap(), 1);
+
+        assert_eq!(cache.remove(&1).unwrap(), Some(11));
+        assert_eq!(cache.remove(&1).unwrap(), None);
+        assert!(cache.is_empty().unwrap());
+        assert_eq!(cache.get(&1).unwrap(), None);
+    }
+
+    #[test]
+    fn tracks_existing_entries() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("cache").unwrap();
+        for i in 0..5 {
+            tree.insert(&i, &i).unwrap();
+        }
+
+        // Existing entries are tracked in key order, so the first ones are evicted
+        let cache = ser_db.open_cache_tree::<u32, u32>("cache", 3).unwrap();
+        assert_eq!(cache.len().unwrap(), 3);
+        assert_eq!(
+            tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        // The recency is kept across handles
+        cache.get(&2).unwrap();
+        let cache = ser_db.open_cache_tree::<u32, u32>("cache", 3).unwrap();
+        cache.insert(&5, &5).unwrap();
+        assert_eq!(cache.peek(&3).unwrap(), None);
+        assert_eq!(cache.peek(&2).unwrap(), Some(2));
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod capped_log_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn evicts_oldest_entries() {
+        let ser_db = Db::open_temporary().unwrap();
+        let log = ser_db.open_capped_log::<u32>("events", 3).unwrap();
+        let ids = (0..5).map(|i| log.append(&i).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(log.len().unwrap(), 3);
+        assert_eq!(log.get(ids[1]).unwrap(), None);
+        assert_eq!(
+            log.iter().map(|(_, value)| value).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(log.latest(2), vec![(ids[4], 4), (ids[3], 3)]);
+
+        // The count is kept across handles
+        let log = ser_db.open_capped_log::<u32>("events", 3).unwrap();
+        assert_eq!(log.len().unwrap(), 3);
+    }
+
+    #[test]
+    fn max_bytes() {
+        let ser_db = Db::open_temporary().unwrap();
+        let log = ser_db
+            .open_capped_log::<String>("events", 100)
+            .unwrap()
+            .with_max_bytes(10);
+
+        // Each value takes its length plus one byte
+        log.append(&"abcd".to_string()).unwrap();
+        log.append(&"efgh".to_string()).unwrap();
+        assert_eq!(log.size_in_bytes().unwrap(), 10);
+        log.append(&"ij".to_string()).unwrap();
+        assert_eq!(log.size_in_bytes().unwrap(), 8);
+        assert_eq!(log.len().unwrap(), 2);
+
+        // The latest entry stays even if it is too large
+        log.append(&"a".repeat(20)).unwrap();
+        assert_eq!(log.len().unwrap(), 1);
+        assert_eq!(log.size_in_bytes().unwrap(), 21);
+    }
+
+    #[test]
+    fn counts_existing_entries() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u64, u32>("events").unwrap();
+        for i in 0..5 {
+            tree.insert(&ser_db.generate_id().unwrap(), &i).unwrap();
+        }
+
+        let log = ser_db.open_capped_log::<u32>("events", 2).unwrap();
+        assert_eq!(log.len().unwrap(), 5);
+        log.append(&5).unwrap();
+        assert_eq!(
+            log.iter().map(|(_, value)| value).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+    }
+}
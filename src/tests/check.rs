@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod check_tests {
+    use crate::check::ReportLevel;
+    use crate::{Db, RelaxedBincodeTree, StrictTree};
+
+    fn corrupted_db() -> Db {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let tree = ser_db.open_bincode_tree::<u8, u8>("users").unwrap();
+        tree.insert(&1, &10).unwrap();
+        tree.insert(&2, &20).unwrap();
+
+        // Write a `u64` where a `u8` is expected
+        let relaxed = ser_db.open_relaxed_bincode_tree("users").unwrap();
+        relaxed.insert(&3u8, &300u64).unwrap();
+
+        let unregistered = ser_db.open_relaxed_bincode_tree("misc").unwrap();
+        unregistered.insert(&1u8, &"anything".to_string()).unwrap();
+
+        ser_db
+    }
+
+    #[test]
+    fn summary() {
+        let ser_db = corrupted_db();
+        let report = ser_db.check(ReportLevel::Summary).unwrap();
+
+        assert!(!report.is_clean());
+        let users = report.tree("users").unwrap();
+        assert!(users.checked);
+        assert_eq!((users.entries, users.failures), (3, 1));
+        assert!(users.bad_entries.is_empty());
+
+        let misc = report.tree("misc").unwrap();
+        assert!(!misc.checked);
+        assert_eq!((misc.entries, misc.failures), (1, 0));
+    }
+
+    #[test]
+    fn repair() {
+        let ser_db = corrupted_db();
+        let report = ser_db.check(ReportLevel::Repair).unwrap();
+
+        let users = report.tree("users").unwrap();
+        assert_eq!(users.bad_entries.len(), 1);
+        assert_eq!(users.quarantined, 1);
+
+        assert!(ser_db.check(ReportLevel::Detailed).unwrap().is_clean());
+
+        let quarantine = ser_db
+            .open_relaxed_bincode_tree("users_quarantine")
+            .unwrap();
+        assert_eq!(quarantine.get(&3u8).unwrap(), Some(300u64));
+    }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use crate::{Db, RelaxedBincodeTree, StrictTree};
+
+    #[test]
+    fn verify() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let tree = ser_db.open_bincode_tree::<u8, u8>("verify").unwrap();
+        tree.insert(&1, &10).unwrap();
+        assert!(tree.verify().unwrap().bad_entries.is_empty());
+
+        let relaxed = ser_db.open_relaxed_bincode_tree("verify").unwrap();
+        relaxed.insert(&2u8, &300u64).unwrap();
+
+        let report = tree.verify().unwrap();
+        assert_eq!(report.name, "verify");
+        assert_eq!((report.entries, report.failures), (2, 1));
+        assert_eq!(report.bad_entries[0].key, vec![2]);
+        let raw = ser_db.open_sled_tree("verify").unwrap().get([2]).unwrap();
+        assert_eq!(report.bad_entries[0].value, raw.unwrap().to_vec());
+        // `iter` silently skips the entry
+        assert_eq!(tree.iter().count(), 1);
+
+        // Relaxed trees decode strictly too
+        let report = relaxed.verify::<u8, u8>().unwrap();
+        assert_eq!(report.failures, 1);
+        assert_eq!(report.bad_entries[0].key, vec![2]);
+    }
+
+    #[test]
+    fn repair() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let tree = ser_db.open_bincode_tree::<u8, u8>("repair").unwrap();
+        tree.insert(&1, &10).unwrap();
+        let relaxed = ser_db.open_relaxed_bincode_tree("repair").unwrap();
+        relaxed.insert(&2u8, &300u64).unwrap();
+
+        let report = tree.repair(&ser_db).unwrap();
+        assert_eq!((report.failures, report.quarantined), (1, 1));
+        assert_eq!(tree.len(), 1);
+
+        let quarantine = ser_db
+            .open_relaxed_bincode_tree("repair_quarantine")
+            .unwrap();
+        assert_eq!(quarantine.get::<_, u64>(&2u8).unwrap(), Some(300));
+
+        // Nothing is left to repair
+        assert_eq!(tree.repair(&ser_db).unwrap().quarantined, 0);
+
+        relaxed.insert(&3u8, &"three".to_string()).unwrap();
+        let report = relaxed.repair::<u8, u8>(&ser_db).unwrap();
+        assert_eq!(report.quarantined, 1);
+        assert_eq!(quarantine.len(), 2);
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod value_codec_tests {
+    use bincode::config::Config;
+
+    use crate::codec::ValueCodec;
+    use crate::keys::OrderedKeyCodec;
+    use crate::{error::Error, Db, RelaxedBincodeTree, StrictTree};
+
+    /// Stores strings as raw UTF-8, so that other programs can read them as-is
+    struct Utf8Codec;
+
+    impl ValueCodec<String> for Utf8Codec {
+        fn encode<C: Config>(value: &String, _config: C) -> Result<Vec<u8>, Error> {
+            Ok(value.as_bytes().to_vec())
+        }
+
+        fn decode<C: Config>(bytes: &[u8], _config: C, _strict: bool) -> Result<String, Error> {
+            String::from_utf8(bytes.to_vec()).map_err(|_| Error::IllegalOperation)
+        }
+    }
+
+    #[test]
+    fn custom_value_codec() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_tree::<u32, String, Utf8Codec, OrderedKeyCodec>("utf8_values")
+            .expect("tree should open");
+
+        tree.insert(&2, &"two".to_string()).unwrap();
+        tree.insert(&1, &"one".to_string()).unwrap();
+        assert_eq!(tree.first().unwrap(), Some((1, "one".to_string())));
+        assert_eq!(
+            tree.update_and_fetch(&2, |value| value.map(|v| v + "!"))
+                .unwrap(),
+            Some("two!".to_string())
+        );
+
+        let raw = ser_db
+            .open_relaxed_bincode_tree("utf8_values")
+            .expect("tree should open");
+        assert_eq!(
+            raw.range_key_bytes::<Vec<u8>, _, [u8; 4]>(..)
+                .map(|(_, v)| v)
+                .next_back(),
+            Some(*b"two!")
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod counted_tests {
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn counts_entries() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("tree").unwrap();
+        for i in 0..10 {
+            tree.insert(&i, &i).unwrap();
+        }
+
+        // Existing entries are counted once
+        let counted = tree.clone().counted(&ser_db).unwrap();
+        assert_eq!(counted.len().unwrap(), 10);
+
+        counted.insert(&10, &10).unwrap();
+        // Replacing a value doesn't change the count
+        assert_eq!(counted.insert(&10, &11).unwrap(), Some(10));
+        assert_eq!(counted.len().unwrap(), 11);
+
+        assert_eq!(counted.remove(&0).unwrap(), Some(0));
+        assert_eq!(counted.remove(&0).unwrap(), None);
+        assert_eq!(counted.len().unwrap(), 10);
+        assert_eq!(counted.len().unwrap(), counted.tree().len() as u64);
+
+        // The count is kept when the tree is counted again
+        let counted = tree.clone().counted(&ser_db).unwrap();
+        assert_eq!(counted.len().unwrap(), 10);
+
+        // Writes through other handles need a recount
+        tree.clear().unwrap();
+        assert_eq!(counted.len().unwrap(), 10);
+        assert_eq!(counted.recount().unwrap(), 0);
+        assert!(counted.is_empty().unwrap());
+    }
+
+    #[test]
+    fn rejects_quotas() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, u32>("tree")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(1));
+
+        assert!(matches!(
+            tree.counted(&ser_db),
+            Err(Error::IllegalOperation)
+        ));
+    }
+}
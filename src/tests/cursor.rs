@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod cursor_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn seek() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("tree").unwrap();
+        tree.insert_all((0..20).step_by(2).map(|i| (i, i * 10)))
+            .unwrap();
+
+        let mut cursor = tree.cursor();
+        assert_eq!(cursor.next().unwrap().unwrap(), (0, 0));
+        cursor.seek(&7).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap(), (8, 80));
+        cursor.seek(&12).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap(), (12, 120));
+        // Seeking back is allowed
+        cursor.seek(&3).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap(), (4, 40));
+        cursor.seek(&100).unwrap();
+        assert!(cursor.next().is_none());
+
+        // Seeks stay within the range
+        let mut cursor = tree.cursor_range(4..10).unwrap();
+        cursor.seek(&0).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap(), (4, 40));
+        cursor.seek(&9).unwrap();
+        assert!(cursor.next().is_none());
+    }
+
+    #[test]
+    fn merge_join() {
+        let ser_db = Db::open_temporary().unwrap();
+        let left = ser_db.open_bincode_tree::<u32, ()>("left").unwrap();
+        let right = ser_db.open_bincode_tree::<u32, ()>("right").unwrap();
+        left.insert_all([1, 5, 9, 200, 300].map(|i| (i, ())))
+            .unwrap();
+        right
+            .insert_all((0..250).step_by(5).map(|i| (i, ())))
+            .unwrap();
+
+        let mut joined = Vec::new();
+        let mut right_cursor = right.cursor();
+        for (key, _) in left.iter() {
+            right_cursor.seek(&key).unwrap();
+            if let Some(Ok((right_key, _))) = right_cursor.next() {
+                if right_key == key {
+                    joined.push(key);
+                }
+            }
+        }
+        assert_eq!(joined, vec![5, 200]);
+    }
+}
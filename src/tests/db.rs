@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod db_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{admin::COPY_BATCH_SIZE, db_config::DbConfig, error::Error, Db, StrictTree};
+
+    #[test]
+    fn on_recovered() {
+        let path =
+            std::env::temp_dir().join(format!("ser_sled_on_recovered_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+
+        {
+            let ser_db: Db = sled::open(&path).unwrap().into();
+            assert!(!ser_db.was_recovered());
+
+            // Not recovered, so the hook never runs
+            ser_db.on_recovered(|_| panic!("database wasn't recovered"));
+            ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        }
+
+        let ser_db: Db = sled::open(&path).unwrap().into();
+        assert!(ser_db.was_recovered());
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let hook_runs = runs.clone();
+        ser_db.on_recovered(move |db| {
+            // Fails the first time, succeeds the second
+            if hook_runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(Error::IllegalOperation);
+            }
+            db.open_bincode_tree::<u8, u8>("index")?;
+            Ok(())
+        });
+
+        assert!(ser_db.open_bincode_tree::<u8, u8>("tree").is_err());
+        ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        drop(ser_db);
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn copy_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let src = ser_db.open_bincode_tree::<u32, u32>("src").unwrap();
+        for i in 0..COPY_BATCH_SIZE as u32 + 10 {
+            src.insert(&i, &(i * 2)).unwrap();
+        }
+
+        let dst = ser_db.open_bincode_tree::<u32, u32>("dst").unwrap();
+        dst.insert(&u32::MAX, &0).unwrap();
+
+        assert_eq!(
+            ser_db.copy_tree("src", "dst").unwrap(),
+            COPY_BATCH_SIZE + 10
+        );
+        assert_eq!(dst.len(), COPY_BATCH_SIZE + 11);
+        assert_eq!(dst.get(&7).unwrap(), Some(14));
+        assert_eq!(src.len(), COPY_BATCH_SIZE + 10);
+    }
+
+    #[test]
+    fn rename_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let old = ser_db.open_bincode_tree::<u8, String>("old").unwrap();
+        old.insert(&1, &"one".to_string()).unwrap();
+        old.insert(&2, &"two".to_string()).unwrap();
+
+        ser_db
+            .open_bincode_tree::<u8, u8>("taken")
+            .unwrap()
+            .insert(&1, &1)
+            .unwrap();
+        assert!(matches!(
+            ser_db.rename_tree("old", "taken"),
+            Err(Error::IllegalOperation)
+        ));
+
+        assert_eq!(ser_db.rename_tree("old", "new").unwrap(), 2);
+        assert!(!ser_db.tree_names().contains(&"old".to_string()));
+        assert_eq!(ser_db.schema("old").unwrap(), None);
+
+        // The types of the old tree come along
+        assert!(ser_db.open_bincode_tree::<u8, u8>("new").is_err());
+        let new = ser_db.open_bincode_tree::<u8, String>("new").unwrap();
+        assert_eq!(new.get(&2).unwrap().as_deref(), Some("two"));
+    }
+
+    #[test]
+    fn drop_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        assert!(ser_db.tree_names().is_empty());
+
+        let tree = ser_db.open_bincode_tree::<u8, u64>("users").unwrap();
+        tree.insert(&1, &1).unwrap();
+        assert!(ser_db.tree_names().contains(&"users".to_string()));
+
+        assert!(ser_db.drop_tree("users").unwrap());
+        assert!(!ser_db.drop_tree("users").unwrap());
+        assert!(!ser_db.tree_names().contains(&"users".to_string()));
+
+        // The schema is gone too, so the name can be reused with other types
+        let tree = ser_db.open_bincode_tree::<u8, String>("users").unwrap();
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn open() {
+        let ser_db = Db::open_temporary().unwrap();
+        ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+
+        // Reopening a path right after dropping its database can race sled's flusher
+        // thread for the lock, so each database gets its own directory
+        let path = std::env::temp_dir().join(format!("ser_sled_open_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+
+        let ser_db = Db::open(path.join("open")).unwrap();
+        ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        assert!(path.join("open").exists());
+
+        let ser_db = DbConfig::new()
+            .path(path.join("config"))
+            .cache_capacity(1024 * 1024)
+            .flush_every_ms(None)
+            .open()
+            .unwrap();
+        let tree = ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        tree.insert(&1, &2).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(2));
+        assert!(path.join("config").exists());
+
+        drop(tree);
+        drop(ser_db);
+
+        // Without a flusher thread, dropping every handle releases the lock, so the
+        // same path can be reopened to check that written data persists
+        let persisted = || {
+            DbConfig::new()
+                .path(path.join("persist"))
+                .flush_every_ms(None)
+                .open()
+                .unwrap()
+        };
+        {
+            let ser_db = persisted();
+            let tree = ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+            tree.insert(&1, &2).unwrap();
+            ser_db.flush().unwrap();
+        }
+
+        let ser_db = persisted();
+        let tree = ser_db.open_bincode_tree::<u8, u8>("tree").unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(2));
+
+        drop(tree);
+        drop(ser_db);
+        let _ = std::fs::remove_dir_all(&path);
+
+        // sled's `compression` feature isn't enabled
+        assert!(matches!(
+            DbConfig::new().temporary(true).use_compression(true).open(),
+            Err(Error::SledError(_))
+        ));
+    }
+
+    #[test]
+    fn flush() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tree").unwrap();
+        for i in 0..100 {
+            tree.insert(&i, &"value".repeat(10)).unwrap();
+        }
+
+        assert!(tree.flush().unwrap() > 0);
+        tree.insert(&100, &String::new()).unwrap();
+        assert!(futures_executor::block_on(ser_db.flush_async()).unwrap() > 0);
+        assert_eq!(ser_db.flush().unwrap(), 0);
+        assert!(ser_db.size_on_disk().unwrap() > 0);
+
+        let relaxed = ser_db.open_relaxed_bincode_tree("tree").unwrap();
+        assert_eq!(
+            futures_executor::block_on(relaxed.flush_async()).unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod derive_tests {
+    use crate::bincode_tree::BincodeTree;
+    use crate::error::Error;
+    use crate::keys::{from_ordered_bytes, to_ordered_bytes, OrderedFloat, OrderedKey, Reverse};
+    use crate::{Db, SerSledSchema, StrictTree};
+
+    #[derive(SerSledSchema)]
+    struct Schema {
+        users: BincodeTree<u64, String>,
+        #[ser_sled(name = "user_emails")]
+        emails: BincodeTree<String, u64>,
+    }
+
+    #[derive(SerSledSchema)]
+    struct Changed {
+        #[ser_sled(name = "user_emails")]
+        _emails: BincodeTree<String, String>,
+    }
+
+    #[test]
+    fn opens_every_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        assert_eq!(Schema::TREE_NAMES, &["users", "user_emails"]);
+
+        let schema = Schema::open(&ser_db).unwrap();
+        schema.users.insert(&1, &"alice".to_string()).unwrap();
+        schema
+            .emails
+            .insert(&"alice@example.com".to_string(), &1)
+            .unwrap();
+
+        let reopened = Schema::open(&ser_db).unwrap();
+        assert_eq!(reopened.users.get(&1).unwrap().as_deref(), Some("alice"));
+        assert_eq!(
+            ser_db
+                .open_bincode_tree::<String, u64>("user_emails")
+                .unwrap()
+                .get(&"alice@example.com".to_string())
+                .unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn checks_fingerprints() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        Schema::open(&ser_db).unwrap();
+        assert!(matches!(
+            Changed::open(&ser_db),
+            Err(Error::SchemaMismatch { ref tree, .. }) if tree == "user_emails"
+        ));
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, OrderedKey)]
+    struct EventKey {
+        user: String,
+        at: i64,
+        seq: Option<u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, OrderedKey)]
+    struct Scored(Reverse<OrderedFloat<f64>>, EventKey);
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, OrderedKey)]
+    struct Wrapper<T>(T);
+
+    fn event(user: &str, at: i64, seq: Option<u32>) -> EventKey {
+        EventKey {
+            user: user.to_string(),
+            at,
+            seq,
+        }
+    }
+
+    #[test]
+    fn ordered_key_round_trips() {
+        let key = Scored(Reverse(OrderedFloat(1.5)), event("a\0b", -3, Some(7)));
+        assert_eq!(
+            from_ordered_bytes::<Scored>(&to_ordered_bytes(&key)).unwrap(),
+            key
+        );
+        assert_eq!(
+            from_ordered_bytes::<Wrapper<u8>>(&to_ordered_bytes(&Wrapper(4u8))).unwrap(),
+            Wrapper(4)
+        );
+
+        // Same encoding as the tuple of the fields
+        assert_eq!(
+            to_ordered_bytes(&event("a", 1, None)),
+            to_ordered_bytes(&("a".to_string(), 1i64, None::<u32>))
+        );
+    }
+
+    #[test]
+    fn ordered_key_matches_ord() {
+        let mut keys = vec![
+            event("b", 0, None),
+            event("a", 5, Some(1)),
+            event("a", -5, None),
+            event("a", 5, None),
+            event("", i64::MAX, Some(0)),
+        ];
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db.open_bincode_tree::<EventKey, u8>("events").unwrap();
+        for key in &keys {
+            tree.insert(key, &0).unwrap();
+        }
+
+        keys.sort();
+        assert_eq!(tree.iter().map(|(key, _)| key).collect::<Vec<_>>(), keys);
+
+        let mut scores: Vec<Scored> = [2.0, -1.0, 10.5]
+            .into_iter()
+            .map(|score| Scored(Reverse(OrderedFloat(score)), event("a", 0, None)))
+            .collect();
+        let mut encoded: Vec<Vec<u8>> = scores.iter().map(to_ordered_bytes).collect();
+        scores.sort();
+        encoded.sort();
+        assert_eq!(
+            encoded,
+            scores.iter().map(to_ordered_bytes).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod diff_tests {
+    use crate::{diff::diff, diff::DiffEntry, Db, StrictTree};
+
+    #[test]
+    fn yields_differences_in_key_order() {
+        let ser_db = Db::open_temporary().unwrap();
+        let production = ser_db
+            .open_bincode_tree::<u32, String>("production")
+            .unwrap();
+        let staging = ser_db.open_bincode_tree::<u32, String>("staging").unwrap();
+
+        for (key, value) in [(1, "a"), (2, "b"), (4, "d"), (300, "x")] {
+            production.insert(&key, &value.to_string()).unwrap();
+        }
+        for (key, value) in [(2, "b"), (3, "c"), (4, "D"), (5, "e")] {
+            staging.insert(&key, &value.to_string()).unwrap();
+        }
+
+        let entries = diff(&production, &staging)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                DiffEntry::OnlyLeft(1, "a".to_string()),
+                DiffEntry::OnlyRight(3, "c".to_string()),
+                DiffEntry::Different {
+                    key: 4,
+                    left: "d".to_string(),
+                    right: "D".to_string()
+                },
+                DiffEntry::OnlyRight(5, "e".to_string()),
+                DiffEntry::OnlyLeft(300, "x".to_string()),
+            ]
+        );
+
+        assert_eq!(diff(&production, &production).count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn across_codecs() {
+        let ser_db = Db::open_temporary().unwrap();
+        let bincode = ser_db.open_bincode_tree::<u8, u64>("bincode").unwrap();
+        let serde = ser_db.open_serde_tree::<u8, u64>("serde").unwrap();
+        bincode.insert(&1, &1000).unwrap();
+        serde.insert(&1, &1000).unwrap();
+
+        assert_eq!(diff(&bincode, &serde).count(), 0);
+
+        ser_db
+            .inner_db
+            .open_tree("serde")
+            .unwrap()
+            .insert([2], vec![0xff])
+            .unwrap();
+        let mut entries = diff(&bincode, &serde);
+        assert!(matches!(
+            entries.next(),
+            Some(Err(crate::error::Error::BincodeError(_)))
+        ));
+        assert!(entries.next().is_none());
+    }
+}
+
+#[cfg(test)]
+mod sync_tests {
+    use crate::{bincode_tree::BincodeTree, diff::ConflictPolicy, Db, StrictTree};
+
+    fn trees(ser_db: &Db) -> (BincodeTree<u8, u32>, BincodeTree<u8, u32>) {
+        let local = ser_db.open_bincode_tree::<u8, u32>("local").unwrap();
+        let remote = ser_db.open_bincode_tree::<u8, u32>("remote").unwrap();
+        local.clear().unwrap();
+        remote.clear().unwrap();
+
+        for (key, value) in [(1, 10), (2, 20)] {
+            local.insert(&key, &value).unwrap();
+        }
+        for (key, value) in [(2, 200), (3, 300)] {
+            remote.insert(&key, &value).unwrap();
+        }
+
+        (local, remote)
+    }
+
+    #[test]
+    fn policies() {
+        let ser_db = Db::open_temporary().unwrap();
+
+        let (local, remote) = trees(&ser_db);
+        assert_eq!(
+            local.sync_from(&remote, ConflictPolicy::KeepLocal).unwrap(),
+            1
+        );
+        assert_eq!(
+            local.iter().collect::<Vec<_>>(),
+            vec![(1, 10), (2, 20), (3, 300)]
+        );
+
+        let (local, remote) = trees(&ser_db);
+        assert_eq!(
+            local
+                .sync_from(&remote, ConflictPolicy::TakeRemote)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            local.iter().collect::<Vec<_>>(),
+            vec![(1, 10), (2, 200), (3, 300)]
+        );
+
+        let (local, remote) = trees(&ser_db);
+        local
+            .sync_from(
+                &remote,
+                ConflictPolicy::Resolve(Box::new(|_, local, remote| local + remote)),
+            )
+            .unwrap();
+        assert_eq!(local.get(&2).unwrap(), Some(220));
+
+        // The remote tree is left as it was
+        assert_eq!(remote.iter().collect::<Vec<_>>(), vec![(2, 200), (3, 300)]);
+    }
+}
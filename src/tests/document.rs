@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod document_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::Db;
+
+    #[derive(Encode, Decode, Debug, Clone, PartialEq)]
+    struct User {
+        name: String,
+        team: String,
+    }
+
+    fn user(name: &str, team: &str) -> User {
+        User {
+            name: name.to_string(),
+            team: team.to_string(),
+        }
+    }
+
+    #[test]
+    fn insert_get_find() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut users = ser_db.open_document_store::<User>("users").unwrap();
+        let by_team = users
+            .add_index("team", |user: &User| user.team.clone())
+            .unwrap();
+
+        let alice = users.insert(&user("alice", "core")).unwrap();
+        let bob = users.insert(&user("bob", "web")).unwrap();
+        let carol = users.insert(&user("carol", "core")).unwrap();
+        assert!(alice < bob && bob < carol);
+
+        assert_eq!(users.get(bob).unwrap(), Some(user("bob", "web")));
+        let core = users.find_by(&by_team, &"core".to_string()).unwrap();
+        assert_eq!(
+            core.iter().map(|doc| doc.id).collect::<Vec<_>>(),
+            vec![alice, carol]
+        );
+
+        users.remove(alice).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(
+            users.find_by(&by_team, &"core".to_string()).unwrap().len(),
+            1
+        );
+        assert_eq!(
+            users.iter().map(|doc| doc.doc.name).collect::<Vec<_>>(),
+            vec!["bob", "carol"]
+        );
+    }
+
+    #[test]
+    fn versions() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut users = ser_db.open_document_store::<User>("users").unwrap();
+        let by_team = users
+            .add_index("team", |user: &User| user.team.clone())
+            .unwrap();
+
+        let id = users.insert(&user("alice", "core")).unwrap();
+        assert_eq!(users.get_document(id).unwrap().unwrap().version, 1);
+
+        assert_eq!(users.update(id, &user("alice", "web")).unwrap(), Some(2));
+        assert!(users
+            .find_by(&by_team, &"core".to_string())
+            .unwrap()
+            .is_empty());
+        assert_eq!(users.update(id + 1, &user("bob", "web")).unwrap(), None);
+
+        // Updating from a stale version fails
+        assert!(!users
+            .update_if_version(id, 1, &user("alice", "core"))
+            .unwrap());
+        assert!(users
+            .update_if_version(id, 2, &user("alice", "ops"))
+            .unwrap());
+        assert_eq!(
+            users.get_document(id).unwrap().unwrap(),
+            crate::document::Document {
+                id,
+                version: 3,
+                doc: user("alice", "ops")
+            }
+        );
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod dyn_tree_tests {
+    use std::collections::BTreeMap;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    use crate::backend::MemoryTree;
+    use crate::dyn_tree::DynTree;
+    use crate::keys::BincodeKeyCodec;
+    use crate::testing::TempDb;
+
+    type Registry = BTreeMap<&'static str, Box<dyn DynTree<u64, String> + Send + Sync>>;
+
+    #[test]
+    fn heterogeneous_registry() {
+        let db = TempDb::new();
+        let mut registry: Registry = BTreeMap::new();
+
+        registry.insert(
+            "ordered",
+            Box::new(db.bincode_tree_with::<u64, String, _>("a", [])),
+        );
+        registry.insert(
+            "bincode_keys",
+            Box::new(
+                db.open_bincode_tree_with_key_codec::<u64, String, BincodeKeyCodec>("b")
+                    .unwrap(),
+            ),
+        );
+        registry.insert("memory", Box::new(MemoryTree::<u64, String>::default()));
+
+        for tree in registry.values() {
+            for id in [3, 1, 2] {
+                tree.insert(&id, &id.to_string()).unwrap();
+            }
+
+            assert_eq!(tree.len(), 3);
+            assert_eq!(tree.get(&2).unwrap().as_deref(), Some("2"));
+            assert_eq!(
+                tree.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+                vec![1, 2, 3]
+            );
+            assert_eq!(
+                tree.range((Excluded(1), Unbounded))
+                    .unwrap()
+                    .rev()
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>(),
+                vec![3, 2]
+            );
+            assert_eq!(tree.range((Included(5), Unbounded)).unwrap().count(), 0);
+            assert_eq!(
+                tree.get_or_init(4, Box::new(|| "four".to_string()))
+                    .unwrap()
+                    .as_deref(),
+                Some("four")
+            );
+            assert_eq!(tree.pop_min().unwrap(), Some((1, "1".to_string())));
+            assert_eq!(tree.remove(&4).unwrap().as_deref(), Some("four"));
+            tree.clear().unwrap();
+            assert!(tree.is_empty());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod encryption_tests {
+    use std::sync::Arc;
+
+    use crate::encryption::{Key, KeyProvider};
+    use crate::{error::Error, Db};
+
+    /// Keys loaded at runtime, e.g. from configuration.
+    struct TestKeys {
+        current: u32,
+        keys: Vec<(u32, Key)>,
+    }
+
+    impl TestKeys {
+        fn single(id: u32, key: Key) -> Self {
+            Self {
+                current: id,
+                keys: vec![(id, key)],
+            }
+        }
+    }
+
+    impl KeyProvider for TestKeys {
+        fn current_key_id(&self) -> u32 {
+            self.current
+        }
+
+        fn key(&self, id: u32) -> Option<Key> {
+            self.keys
+                .iter()
+                .find(|(key_id, _)| *key_id == id)
+                .map(|(_, key)| *key)
+        }
+    }
+
+    #[test]
+    fn encrypts_values() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("encrypted")
+            .unwrap()
+            .encrypted(TestKeys::single(1, [7; 32]));
+
+        let secret = "alice@example.com".to_string();
+        assert_eq!(tree.insert(&1, &secret).unwrap(), None);
+        assert_eq!(tree.get(&1).unwrap(), Some(secret.clone()));
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, secret.clone())]);
+        assert_eq!(tree.first().unwrap(), Some((1, secret.clone())));
+
+        let stored = tree.inner().get(1u64.to_be_bytes()).unwrap().unwrap();
+        assert_eq!(stored[..4], 1u32.to_be_bytes());
+        assert!(!stored
+            .windows(secret.len())
+            .any(|window| window == secret.as_bytes()));
+
+        // Another instance of the same provider type, with another key
+        let wrong_key = ser_db
+            .open_bincode_tree::<u64, String>("encrypted")
+            .unwrap()
+            .encrypted(TestKeys::single(1, [8; 32]));
+        assert!(matches!(
+            wrong_key.get(&1),
+            Err(Error::EncryptionError("decryption failed"))
+        ));
+        assert_eq!(wrong_key.iter().count(), 0);
+
+        assert_eq!(tree.remove(&1).unwrap(), Some(secret));
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn values_are_bound_to_their_key() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("bound")
+            .unwrap()
+            .encrypted(Arc::new(TestKeys::single(1, [7; 32])));
+        tree.insert(&1, &"alice".to_string()).unwrap();
+
+        // Moving a sealed value under another key is detected
+        let sealed = tree.inner().get(1u64.to_be_bytes()).unwrap().unwrap();
+        tree.inner().insert(2u64.to_be_bytes(), sealed).unwrap();
+        assert!(matches!(
+            tree.get(&2),
+            Err(Error::EncryptionError("decryption failed"))
+        ));
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn unknown_key() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("unknown_key")
+            .unwrap()
+            .encrypted(TestKeys::single(1, [7; 32]));
+
+        tree.inner()
+            .insert(1u64.to_be_bytes(), &[0, 0, 0, 2, 0])
+            .unwrap();
+        assert!(matches!(
+            tree.get(&1),
+            Err(Error::EncryptionError("unknown key id"))
+        ));
+    }
+
+    #[test]
+    fn rotate_key() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u64, String>("rotate_key")
+            .unwrap()
+            .encrypted(TestKeys::single(1, [7; 32]));
+
+        for i in 0..5 {
+            tree.insert(&i, &i.to_string()).unwrap();
+        }
+
+        let rotated_keys = TestKeys::single(2, [9; 32]);
+        assert_eq!(tree.rotate_key(&rotated_keys, 2).unwrap(), 5);
+        // Nothing is left to rotate, so an interrupted rotation can simply be run again
+        assert_eq!(tree.rotate_key(&rotated_keys, 2).unwrap(), 0);
+
+        let rotated = ser_db
+            .open_bincode_tree::<u64, String>("rotate_key")
+            .unwrap()
+            .encrypted(rotated_keys);
+        for i in 0..5 {
+            assert_eq!(rotated.get(&i).unwrap(), Some(i.to_string()));
+        }
+        assert!(matches!(
+            tree.get(&0),
+            Err(Error::EncryptionError("unknown key id"))
+        ));
+    }
+}
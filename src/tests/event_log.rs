@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod event_log_tests {
+    use crate::Db;
+
+    #[test]
+    fn append_and_read() {
+        let ser_db = Db::open_temporary().unwrap();
+        let log = ser_db.open_event_log::<String>("events").unwrap();
+        assert_eq!(log.last_seq().unwrap(), None);
+
+        let seqs =
+            ["created", "renamed", "deleted"].map(|event| log.append(&event.to_string()).unwrap());
+        assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(log.get(seqs[1]).unwrap(), Some("renamed".to_string()));
+        assert_eq!(log.first_seq().unwrap(), Some(seqs[0]));
+        assert_eq!(log.last_seq().unwrap(), Some(seqs[2]));
+
+        let from = log.read_from(seqs[1]).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            from,
+            vec![
+                (seqs[1], "renamed".to_string()),
+                (seqs[2], "deleted".to_string())
+            ]
+        );
+        assert_eq!(log.read_after(seqs[1]).unwrap().count(), 1);
+
+        assert_eq!(log.truncate_before(seqs[2]).unwrap(), 2);
+        assert_eq!(log.first_seq().unwrap(), Some(seqs[2]));
+        assert_eq!(log.len(), 1);
+
+        // Sequence numbers keep increasing after a truncation
+        log.truncate_before(u64::MAX).unwrap();
+        assert!(log.append(&"created".to_string()).unwrap() > seqs[2]);
+    }
+}
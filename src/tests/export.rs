@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod export_tests {
+    use crate::{error::Error, Db, RelaxedBincodeTree, StrictTree};
+
+    fn export_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ser_sled_{name}_{}", std::process::id()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let ser_db = Db::open_temporary().unwrap();
+        let users = ser_db.open_bincode_tree::<u32, String>("users").unwrap();
+        for i in 0..1500 {
+            users.insert(&i, &format!("user {i}")).unwrap();
+        }
+        let relaxed = ser_db.open_relaxed_bincode_tree("misc").unwrap();
+        relaxed.insert(&"key".to_string(), &42u64).unwrap();
+
+        let path = export_path("round_trip");
+        // Entries of the schema tree are exported too
+        assert_eq!(ser_db.export_to(&path).unwrap(), 1502);
+
+        let restored = Db::open_temporary().unwrap();
+        assert_eq!(restored.import_from(&path).unwrap(), 1502);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            restored.schema("users").unwrap(),
+            ser_db.schema("users").unwrap()
+        );
+        assert!(restored.open_bincode_tree::<u32, u64>("users").is_err());
+
+        let users = restored.open_bincode_tree::<u32, String>("users").unwrap();
+        assert_eq!(users.len(), 1500);
+        assert_eq!(users.get(&1499).unwrap().as_deref(), Some("user 1499"));
+        let relaxed = restored.open_relaxed_bincode_tree("misc").unwrap();
+        assert_eq!(relaxed.get(&"key".to_string()).unwrap(), Some(42u64));
+    }
+
+    #[test]
+    fn rejects_bad_imports() {
+        let ser_db = Db::open_temporary().unwrap();
+        ser_db
+            .open_bincode_tree::<u8, u8>("tree")
+            .unwrap()
+            .insert(&1, &1)
+            .unwrap();
+
+        let path = export_path("rejects_bad_imports");
+        ser_db.export_to(&path).unwrap();
+
+        // Only into an empty database
+        assert!(matches!(
+            ser_db.import_from(&path),
+            Err(Error::IllegalOperation)
+        ));
+
+        std::fs::write(&path, b"not an export at all").unwrap();
+        assert!(matches!(
+            Db::open_temporary().unwrap().import_from(&path),
+            Err(Error::InvalidArchive(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(ser_db.import_from(&path), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,402 @@
+#[cfg(all(test, feature = "messagepack"))]
+mod messagepack_tests {
+    use std::collections::BTreeMap;
+
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn messagepack_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_messagepack_tree::<u64, BTreeMap<String, u32>>("messagepack")
+            .expect("tree should open");
+
+        let value = BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        tree.insert(&1, &value).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(value.clone()));
+
+        // Values can be read by any MessagePack implementation
+        ser_db.reset_schema("messagepack").unwrap();
+        let raw = ser_db
+            .open_tree::<u64, Vec<u8>, RawBytes, crate::keys::OrderedKeyCodec>("messagepack")
+            .unwrap()
+            .get(&1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<BTreeMap<String, u32>>(&raw).unwrap(),
+            value
+        );
+
+        ser_db.reset_schema("messagepack").unwrap();
+        let wrong_type = ser_db
+            .open_messagepack_tree::<u64, String>("messagepack")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_type.get(&1),
+            Err(Error::MessagePackError(_))
+        ));
+    }
+
+    /// Reads values back as the stored bytes
+    struct RawBytes;
+
+    impl crate::codec::ValueCodec<Vec<u8>> for RawBytes {
+        fn encode<C: bincode::config::Config>(
+            value: &Vec<u8>,
+            _config: C,
+        ) -> Result<Vec<u8>, Error> {
+            Ok(value.clone())
+        }
+
+        fn decode<C: bincode::config::Config>(
+            bytes: &[u8],
+            _config: C,
+            _strict: bool,
+        ) -> Result<Vec<u8>, Error> {
+            Ok(bytes.to_vec())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cbor"))]
+mod cbor_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn cbor_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_cbor_tree::<String, (String, Vec<f32>)>("cbor")
+            .expect("tree should open");
+
+        let reading = ("thermometer".to_string(), vec![20.5, 21.0]);
+        tree.insert(&"device-1".to_string(), &reading).unwrap();
+        assert_eq!(tree.get(&"device-1".to_string()).unwrap(), Some(reading));
+
+        ser_db.reset_schema("cbor").unwrap();
+        let wrong_type = ser_db
+            .open_cbor_tree::<String, u64>("cbor")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_type.get(&"device-1".to_string()),
+            Err(Error::CborError(_))
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "postcard"))]
+mod postcard_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn postcard_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_postcard_tree::<u32, (u8, u64)>("postcard")
+            .expect("tree should open");
+
+        tree.insert(&1, &(7, 300)).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some((7, 300)));
+        // Varints: 1 byte for 7, 2 bytes for 300
+        assert_eq!(postcard::to_allocvec(&(7u8, 300u64)).unwrap().len(), 3);
+
+        ser_db.reset_schema("postcard").unwrap();
+        let wrong_type = ser_db
+            .open_postcard_tree::<u32, u8>("postcard")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_type.get(&1),
+            Err(Error::TrailingBytes { decoded: 1, len: 3 })
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "borsh"))]
+mod borsh_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn borsh_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_borsh_tree::<[u8; 4], (String, u64)>("borsh")
+            .expect("tree should open");
+
+        let account = ("alice".to_string(), 1_000);
+        tree.insert(&[0, 0, 0, 1], &account).unwrap();
+        tree.insert(&[0, 0, 0, 2], &account).unwrap();
+        assert_eq!(tree.get(&[0, 0, 0, 1]).unwrap(), Some(account));
+        assert_eq!(tree.range([0, 0, 0, 2]..).unwrap().count(), 1);
+
+        ser_db.reset_schema("borsh").unwrap();
+        let wrong_type = ser_db
+            .open_borsh_tree::<[u8; 4], String>("borsh")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_type.get(&[0, 0, 0, 1]),
+            Err(Error::TrailingBytes { .. })
+        ));
+        ser_db.reset_schema("borsh").unwrap();
+        let too_short = ser_db
+            .open_borsh_tree::<[u8; 4], (String, u128)>("borsh")
+            .expect("tree should open");
+        assert!(matches!(
+            too_short.get(&[0, 0, 0, 1]),
+            Err(Error::BorshError(_))
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "prost"))]
+mod prost_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct User {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(uint64, tag = "2")]
+        age: u64,
+    }
+
+    #[test]
+    fn proto_tree() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_proto_tree::<u64, User>("proto")
+            .expect("tree should open");
+
+        let user = User {
+            name: "chip".to_string(),
+            age: 30,
+        };
+        tree.insert(&1, &user).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(user.clone()));
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, user)]);
+
+        ser_db.reset_schema("proto").unwrap();
+        let wrong_type = ser_db
+            .open_proto_tree::<u64, u64>("proto")
+            .expect("tree should open");
+        assert!(matches!(wrong_type.get(&1), Err(Error::ProtobufError(_))));
+    }
+}
+
+#[cfg(all(test, feature = "bincode1-compat"))]
+mod bincode1_tests {
+    use crate::{Db, RelaxedSerdeTree, StrictTree};
+
+    #[test]
+    fn reads_bincode1_layout() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        // What `bincode::serialize` from bincode 1.x writes for `1u32` and `("ab", 3u16)`
+        let sled_tree = ser_db.open_sled_tree("bincode1").unwrap();
+        sled_tree
+            .insert([1, 0, 0, 0], &[2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', 3, 0])
+            .unwrap();
+
+        let tree = ser_db
+            .open_bincode1_tree::<u32, (String, u16)>("bincode1")
+            .expect("tree should open");
+        assert_eq!(tree.get(&1).unwrap(), Some(("ab".to_string(), 3)));
+
+        tree.insert(&2, &("cd".to_string(), 4)).unwrap();
+        assert_eq!(
+            sled_tree.get([2, 0, 0, 0]).unwrap().unwrap(),
+            [2, 0, 0, 0, 0, 0, 0, 0, b'c', b'd', 4, 0]
+        );
+
+        let relaxed = ser_db.open_relaxed_bincode1_tree("bincode1").unwrap();
+        assert_eq!(
+            relaxed.get::<_, (String, u16)>(&2u32).unwrap(),
+            Some(("cd".to_string(), 4))
+        );
+    }
+}
+
+#[cfg(all(test, feature = "zstd"))]
+mod zstd_tests {
+    use crate::codec::{BincodeCodec, Compressed, MAX_DECOMPRESSED_LEN};
+    use crate::keys::OrderedKeyCodec;
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn compresses_large_values() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_tree::<u64, String, Compressed<BincodeCodec, 64>, OrderedKeyCodec>("zstd")
+            .expect("tree should open");
+        let sled_tree = ser_db.open_sled_tree("zstd").unwrap();
+
+        let small = "small".to_string();
+        let large = "repetitive ".repeat(100);
+        tree.insert(&1, &small).unwrap();
+        tree.insert(&2, &large).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(small));
+        assert_eq!(tree.get(&2).unwrap(), Some(large.clone()));
+
+        let stored = sled_tree.get(2u64.to_be_bytes()).unwrap().unwrap();
+        assert_eq!(stored[0], 1);
+        assert!(stored.len() < large.len() / 5);
+        assert_eq!(sled_tree.get(1u64.to_be_bytes()).unwrap().unwrap()[0], 0);
+
+        // Values written with another threshold still decode
+        let other = ser_db
+            .open_tree::<u64, String, Compressed<BincodeCodec, 4096>, OrderedKeyCodec>("zstd")
+            .expect("tree should open");
+        assert_eq!(other.get(&2).unwrap(), Some(large));
+
+        sled_tree.insert(3u64.to_be_bytes(), &[7, 0]).unwrap();
+        assert!(matches!(tree.get(&3), Err(Error::CompressionError(_))));
+    }
+
+    #[test]
+    fn limits_decompressed_size() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_tree::<u64, String, Compressed<BincodeCodec, 64>, OrderedKeyCodec>("zstd_limit")
+            .unwrap();
+        let sled_tree = ser_db.open_sled_tree("zstd_limit").unwrap();
+
+        let large = "repetitive ".repeat(1000);
+        tree.insert(&1, &large).unwrap();
+        assert!(matches!(
+            tree.clone().with_limit::<1024>().get(&1),
+            Err(Error::LimitExceeded)
+        ));
+
+        // Without a limit, a value can't decompress past `MAX_DECOMPRESSED_LEN`
+        let bomb = zstd::bulk::compress(&vec![0; MAX_DECOMPRESSED_LEN + 1], 1).unwrap();
+        assert!(bomb.len() < 64 * 1024);
+        sled_tree
+            .insert(2u64.to_be_bytes(), [&[1], bomb.as_slice()].concat())
+            .unwrap();
+        assert!(matches!(tree.get(&2), Err(Error::LimitExceeded)));
+    }
+}
+
+#[cfg(all(test, feature = "lz4"))]
+mod lz4_tests {
+    use crate::codec::{BincodeCodec, Lz4Compressed, MAX_DECOMPRESSED_LEN};
+    use crate::keys::OrderedKeyCodec;
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn compresses_large_values() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_tree::<u64, String, Lz4Compressed<BincodeCodec, 64>, OrderedKeyCodec>("lz4")
+            .expect("tree should open");
+
+        let large = "repetitive ".repeat(100);
+        tree.insert(&1, &large).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some(large.clone()));
+
+        let stored = ser_db
+            .open_sled_tree("lz4")
+            .unwrap()
+            .get(1u64.to_be_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored[0], 2);
+        assert!(stored.len() < large.len() / 5);
+    }
+
+    #[test]
+    fn limits_decompressed_size() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_tree::<u64, String, Lz4Compressed<BincodeCodec, 64>, OrderedKeyCodec>("lz4_limit")
+            .unwrap();
+
+        let large = "repetitive ".repeat(1000);
+        tree.insert(&1, &large).unwrap();
+        assert!(matches!(
+            tree.clone().with_limit::<1024>().get(&1),
+            Err(Error::LimitExceeded)
+        ));
+
+        // The prepended size is checked before anything is allocated
+        let size = (MAX_DECOMPRESSED_LEN as u32 + 1).to_le_bytes();
+        ser_db
+            .open_sled_tree("lz4_limit")
+            .unwrap()
+            .insert(2u64.to_be_bytes(), [&[2], &size[..], &[0]].concat())
+            .unwrap();
+        assert!(matches!(tree.get(&2), Err(Error::LimitExceeded)));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn switch_algorithm() {
+        use crate::codec::Compressed;
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let zstd_tree = ser_db
+            .open_tree::<u64, String, Compressed<BincodeCodec, 64>, OrderedKeyCodec>("switch")
+            .expect("tree should open");
+        let large = "repetitive ".repeat(100);
+        zstd_tree.insert(&1, &large).unwrap();
+
+        let lz4_tree = ser_db
+            .open_tree::<u64, String, Lz4Compressed<BincodeCodec, 64>, OrderedKeyCodec>("switch")
+            .expect("tree should open");
+        assert_eq!(lz4_tree.get(&1).unwrap(), Some(large));
+    }
+}
+
+#[cfg(all(test, feature = "checksum"))]
+mod checksum_tests {
+    use crate::codec::{BincodeCodec, Checksummed};
+    use crate::keys::OrderedKeyCodec;
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn detects_corruption() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_tree::<u64, String, Checksummed<BincodeCodec>, OrderedKeyCodec>("checksum")
+            .expect("tree should open");
+        let sled_tree = ser_db.open_sled_tree("checksum").unwrap();
+
+        tree.insert(&1, &"one".to_string()).unwrap();
+        tree.insert(&2, &"two".to_string()).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), Some("one".to_string()));
+
+        let mut stored = sled_tree.get(1u64.to_be_bytes()).unwrap().unwrap().to_vec();
+        stored[1] ^= 1;
+        sled_tree.insert(1u64.to_be_bytes(), stored).unwrap();
+
+        let key_bytes = 1u64.to_be_bytes().to_vec();
+        assert!(matches!(
+            tree.get(&1),
+            Err(Error::ChecksumMismatch { key_bytes: ref k }) if *k == key_bytes
+        ));
+        assert_eq!(tree.iter().count(), 1);
+        assert!(matches!(
+            tree.try_iter().next(),
+            Some(Err(Error::ChecksumMismatch { key_bytes: ref k })) if *k == key_bytes
+        ));
+        // The corrupted value is also reported with its key when it is replaced
+        assert!(matches!(
+            tree.insert(&1, &"uno".to_string()),
+            Err(Error::ChecksumMismatch { key_bytes: ref k }) if *k == key_bytes
+        ));
+        assert_eq!(tree.get(&1).unwrap(), Some("uno".to_string()));
+
+        sled_tree.insert(3u64.to_be_bytes(), &[0]).unwrap();
+        assert!(matches!(tree.get(&3), Err(Error::ChecksumMismatch { .. })));
+    }
+}
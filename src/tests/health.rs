@@ -0,0 +1,18 @@
+#[cfg(test)]
+mod health_tests {
+    use crate::{health::HEALTH_TREE, Db};
+
+    #[test]
+    fn health_check() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let report = ser_db.health_check();
+        assert!(report.is_healthy(), "{report:?}");
+        assert!(report.flush.is_some());
+
+        // The probe entry is cleaned up
+        let tree = ser_db.inner_db.open_tree(HEALTH_TREE).unwrap();
+        assert!(tree.is_empty());
+    }
+}
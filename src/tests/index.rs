@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod index_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::keys::CompositeKey;
+    use crate::{Db, StrictTree};
+
+    #[derive(Encode, Decode, Debug, Clone, PartialEq)]
+    struct Task {
+        status: String,
+        created_at: u64,
+    }
+
+    fn task(status: &str, created_at: u64) -> Task {
+        Task {
+            status: status.to_string(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn composite_index() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut tasks = ser_db.open_indexed_tree::<u32, Task>("tasks").unwrap();
+        let by_status = tasks
+            .add_index("status_created_at", |task: &Task| {
+                (task.status.clone(), task.created_at)
+            })
+            .unwrap();
+
+        tasks.insert(&1, &task("open", 30)).unwrap();
+        tasks.insert(&2, &task("done", 10)).unwrap();
+        tasks.insert(&3, &task("open", 10)).unwrap();
+        tasks.insert(&4, &task("open", 20)).unwrap();
+
+        let open_since_15 = by_status
+            .range(("open".to_string(), 15)..("open".to_string(), u64::MAX))
+            .map(|entry| entry.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(open_since_15, vec![4, 1]);
+
+        let open = by_status
+            .prefix(&CompositeKey::new().push(&"open".to_string()))
+            .map(|entry| entry.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(open, vec![3, 4, 1]);
+
+        assert_eq!(
+            by_status.get(&("done".to_string(), 10)).unwrap(),
+            vec![(2, task("done", 10))]
+        );
+    }
+
+    #[test]
+    fn writes_update_the_index() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut tasks = ser_db.open_indexed_tree::<u32, Task>("tasks").unwrap();
+        let by_status = tasks
+            .add_index("status", |task: &Task| task.status.clone())
+            .unwrap();
+
+        tasks.insert(&1, &task("open", 0)).unwrap();
+        assert_eq!(
+            tasks.insert(&1, &task("done", 0)).unwrap(),
+            Some(task("open", 0))
+        );
+        assert!(by_status.get(&"open".to_string()).unwrap().is_empty());
+        assert_eq!(by_status.get(&"done".to_string()).unwrap().len(), 1);
+
+        tasks.remove(&1).unwrap();
+        assert!(by_status.is_empty());
+    }
+
+    #[test]
+    fn builds_and_rebuilds() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut tasks = ser_db.open_indexed_tree::<u32, Task>("tasks").unwrap();
+        tasks.insert(&1, &task("open", 0)).unwrap();
+
+        // Entries written before the index was added are indexed
+        let by_status = tasks
+            .add_index("status", |task: &Task| task.status.clone())
+            .unwrap();
+        assert_eq!(by_status.len(), 1);
+
+        // Writes bypassing the index are only missed until it is rebuilt
+        tasks.tree().insert(&2, &task("open", 0)).unwrap();
+        assert_eq!(by_status.get(&"open".to_string()).unwrap().len(), 1);
+        by_status.rebuild().unwrap();
+        assert_eq!(by_status.get(&"open".to_string()).unwrap().len(), 2);
+
+        // Stale index entries are skipped
+        tasks.tree().insert(&2, &task("done", 0)).unwrap();
+        assert_eq!(by_status.get(&"open".to_string()).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod job_queue_tests {
+    use std::time::Duration;
+
+    use crate::Db;
+
+    const LEASE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn claim_and_ack() {
+        let ser_db = Db::open_temporary().unwrap();
+        let queue = ser_db.open_job_queue::<String>("jobs").unwrap();
+        let first = queue.enqueue(&"resize".to_string()).unwrap();
+        queue.enqueue(&"email".to_string()).unwrap();
+
+        let job = queue.claim("worker-1", LEASE).unwrap().unwrap();
+        assert_eq!((job.id, job.value.as_str()), (first, "resize"));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(
+            queue.in_flight().unwrap(),
+            vec![(first, "resize".to_string(), "worker-1".to_string())]
+        );
+
+        let other = queue.claim("worker-2", LEASE).unwrap().unwrap();
+        assert_eq!(other.value, "email");
+        assert!(queue.claim("worker-3", LEASE).unwrap().is_none());
+
+        assert!(queue.ack(&job).unwrap());
+        assert!(!queue.ack(&job).unwrap());
+        assert_eq!(queue.in_flight_len(), 1);
+    }
+
+    #[test]
+    fn nack_requeues_in_place() {
+        let ser_db = Db::open_temporary().unwrap();
+        let queue = ser_db.open_job_queue::<u32>("jobs").unwrap();
+        queue.enqueue(&1).unwrap();
+        queue.enqueue(&2).unwrap();
+
+        let job = queue.claim("worker", LEASE).unwrap().unwrap();
+        assert!(queue.nack(&job).unwrap());
+        assert!(!queue.nack(&job).unwrap());
+        assert_eq!(queue.in_flight_len(), 0);
+
+        // Back at the front of the queue
+        let again = queue.claim("worker", LEASE).unwrap().unwrap();
+        assert_eq!((again.id, again.value), (job.id, 1));
+        // A new claim of the same job
+        assert_ne!(again, job);
+    }
+
+    #[test]
+    fn expired_leases_are_requeued() {
+        let ser_db = Db::open_temporary().unwrap();
+        let queue = ser_db.open_job_queue::<u32>("jobs").unwrap();
+        queue.enqueue(&1).unwrap();
+
+        let expired = queue.claim("slow", Duration::ZERO).unwrap().unwrap();
+        assert_eq!(queue.requeue_expired().unwrap(), 1);
+        assert_eq!(queue.len(), 1);
+
+        // The job was claimed by someone else after the lease expired
+        let job = queue.claim("fast", LEASE).unwrap().unwrap();
+        assert_eq!(job.id, expired.id);
+        assert!(!queue.ack(&expired).unwrap());
+        assert!(queue.ack(&job).unwrap());
+        assert!(queue.is_empty());
+        assert_eq!(queue.in_flight_len(), 0);
+    }
+
+    #[test]
+    fn undecodable_entries_are_dead_lettered() {
+        let ser_db = Db::open_temporary().unwrap();
+        let queue = ser_db.open_job_queue::<String>("jobs").unwrap();
+
+        // A string claiming 5 bytes that aren't there, before a valid job
+        let corrupt = ser_db.generate_id().unwrap();
+        ser_db
+            .open_sled_tree("jobs")
+            .unwrap()
+            .insert(corrupt.to_be_bytes(), &[5])
+            .unwrap();
+        let valid = queue.enqueue(&"resize".to_string()).unwrap();
+
+        let job = queue.claim("worker", LEASE).unwrap().unwrap();
+        assert_eq!((job.id, job.value.as_str()), (valid, "resize"));
+        assert!(queue.is_empty());
+        assert_eq!(
+            queue.dead_letters().unwrap(),
+            vec![(corrupt, sled::IVec::from(&[5]))]
+        );
+
+        // Corrupt leases don't block requeuing the others
+        ser_db
+            .open_sled_tree("jobs_in_flight")
+            .unwrap()
+            .insert(u64::MAX.to_be_bytes(), &[0xff])
+            .unwrap();
+        assert!(queue.nack(&job).unwrap());
+        assert_eq!(queue.requeue_expired().unwrap(), 0);
+        assert_eq!(queue.in_flight_len(), 0);
+        assert_eq!(queue.dead_letters().unwrap().len(), 2);
+        assert_eq!(queue.claim("worker", LEASE).unwrap().unwrap().id, valid);
+
+        assert_eq!(queue.clear_dead_letters().unwrap(), 2);
+        assert!(queue.dead_letters().unwrap().is_empty());
+    }
+}
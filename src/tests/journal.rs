@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod journal_tests {
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn incremental_backup() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, String>("users")
+            .unwrap()
+            .journaled(&ser_db)
+            .unwrap();
+        assert_eq!(tree.last_seq().unwrap(), 0);
+
+        tree.insert(&1, &"alice".to_string()).unwrap();
+        tree.insert(&2, &"bob".to_string()).unwrap();
+
+        let backup = Db::open_temporary().unwrap();
+        let mut changes = Vec::new();
+        let seq = tree.export_changes_since(0, &mut changes).unwrap();
+        assert_eq!(seq, tree.last_seq().unwrap());
+        assert_eq!(
+            backup.replay_changes(changes.as_slice()).unwrap(),
+            Some(seq)
+        );
+
+        assert_eq!(
+            tree.insert(&1, &"alicia".to_string()).unwrap().as_deref(),
+            Some("alice")
+        );
+        assert_eq!(tree.remove(&2).unwrap().as_deref(), Some("bob"));
+        tree.insert(&3, &"carol".to_string()).unwrap();
+
+        // Only the changes made since the last export
+        let mut changes = Vec::new();
+        let next = tree.export_changes_since(seq, &mut changes).unwrap();
+        assert!(next > seq);
+        backup.replay_changes(changes.as_slice()).unwrap();
+
+        let restored = backup.open_bincode_tree::<u32, String>("users").unwrap();
+        assert_eq!(
+            restored.iter().collect::<Vec<_>>(),
+            tree.tree().iter().collect::<Vec<_>>()
+        );
+
+        // Nothing new
+        let mut changes = Vec::new();
+        assert_eq!(tree.export_changes_since(next, &mut changes).unwrap(), next);
+        assert_eq!(backup.replay_changes(changes.as_slice()).unwrap(), None);
+
+        assert_eq!(tree.prune_changes_until(seq).unwrap(), 2);
+        assert_eq!(tree.prune_changes_until(next).unwrap(), 3);
+        assert_eq!(tree.last_seq().unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_quotas() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, String>("users")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::EvictOldest).with_max_entries(1));
+
+        assert!(matches!(
+            tree.journaled(&ser_db),
+            Err(Error::IllegalOperation)
+        ));
+    }
+}
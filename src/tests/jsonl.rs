@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod jsonl_tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{error::Error, jsonl::ConflictPolicy, Db, RelaxedSerdeTree, StrictTree};
+
+    #[derive(Serialize, Deserialize, bincode::Encode, bincode::Decode, Debug, PartialEq)]
+    struct User {
+        name: String,
+        admin: bool,
+    }
+
+    #[test]
+    fn export() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, User>("users").unwrap();
+        tree.insert(
+            &2,
+            &User {
+                name: "bob".to_string(),
+                admin: false,
+            },
+        )
+        .unwrap();
+        tree.insert(
+            &1,
+            &User {
+                name: "alice".to_string(),
+                admin: true,
+            },
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(tree.export_jsonl(&mut out).unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"key\":1,\"value\":{\"name\":\"alice\",\"admin\":true}}\n\
+             {\"key\":2,\"value\":{\"name\":\"bob\",\"admin\":false}}\n"
+        );
+
+        let relaxed = ser_db.open_relaxed_serde_tree("misc").unwrap();
+        relaxed.insert(&"answer", &42u8).unwrap();
+        let mut out = Vec::new();
+        relaxed.export_jsonl::<String, u8, _>(&mut out).unwrap();
+        assert_eq!(out, b"{\"key\":\"answer\",\"value\":42}\n");
+
+        // Entries that don't decode stop the export
+        let mut out = Vec::new();
+        assert!(matches!(
+            relaxed.export_jsonl::<String, String, _>(&mut out),
+            Err(Error::BincodeError(_))
+        ));
+    }
+
+    #[test]
+    fn import() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tree").unwrap();
+        tree.insert(&1, &"stored".to_string()).unwrap();
+
+        let input = "{\"key\":1,\"value\":\"one\"}\n\n{\"key\":2,\"value\":\"two\"}\n";
+
+        assert_eq!(
+            tree.import_jsonl(input.as_bytes(), ConflictPolicy::Skip)
+                .unwrap(),
+            1
+        );
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("stored"));
+        assert_eq!(tree.get(&2).unwrap().as_deref(), Some("two"));
+
+        assert!(matches!(
+            tree.import_jsonl(input.as_bytes(), ConflictPolicy::Error),
+            Err(Error::KeyExists)
+        ));
+
+        assert_eq!(
+            tree.import_jsonl(input.as_bytes(), ConflictPolicy::Overwrite)
+                .unwrap(),
+            2
+        );
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("one"));
+
+        assert!(matches!(
+            tree.import_jsonl("{\"key\":\"three\"}".as_bytes(), ConflictPolicy::Skip),
+            Err(Error::JsonError(_))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let ser_db = Db::open_temporary().unwrap();
+        let relaxed = ser_db.open_relaxed_serde_tree("src").unwrap();
+        for i in 0..10u64 {
+            relaxed.insert(&i, &vec![i; i as usize]).unwrap();
+        }
+
+        let mut dump = Vec::new();
+        relaxed.export_jsonl::<u64, Vec<u64>, _>(&mut dump).unwrap();
+
+        let restored = ser_db.open_relaxed_serde_tree("dst").unwrap();
+        assert_eq!(
+            restored
+                .import_jsonl::<u64, Vec<u64>, _>(dump.as_slice(), ConflictPolicy::Error)
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            restored.get::<_, Vec<u64>>(&3u64).unwrap(),
+            Some(vec![3; 3])
+        );
+    }
+}
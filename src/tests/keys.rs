@@ -0,0 +1,376 @@
+#[cfg(test)]
+mod ordered_key_tests {
+    use crate::keys::{from_ordered_bytes, to_ordered_bytes};
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn ordering() {
+        let ints = [i64::MIN, -300, -1, 0, 1, 255, 256, i64::MAX];
+        for pair in ints.windows(2) {
+            assert!(to_ordered_bytes(&pair[0]) < to_ordered_bytes(&pair[1]));
+        }
+
+        let strings = ["", "\0", "\0\0", "a", "a\0", "aa", "b"].map(String::from);
+        for pair in strings.windows(2) {
+            assert!(to_ordered_bytes(&pair[0]) < to_ordered_bytes(&pair[1]));
+        }
+
+        let tuples = [
+            ("a".to_string(), 2u8),
+            ("ab".to_string(), 1),
+            ("b".to_string(), 0),
+        ];
+        for pair in tuples.windows(2) {
+            assert!(to_ordered_bytes(&pair[0]) < to_ordered_bytes(&pair[1]));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = (-42i32, "a\0b".to_string(), Some('é'));
+        assert_eq!(
+            from_ordered_bytes::<(i32, String, Option<char>)>(&to_ordered_bytes(&key)).unwrap(),
+            key
+        );
+
+        assert!(matches!(
+            from_ordered_bytes::<u32>(&[0, 1]),
+            Err(Error::InvalidKey(_))
+        ));
+        assert!(matches!(
+            from_ordered_bytes::<u8>(&[0, 1]),
+            Err(Error::TrailingBytes { decoded: 1, len: 2 })
+        ));
+    }
+
+    #[test]
+    fn strict_tree_range() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<i64, u8>("ordered_keys")
+            .expect("tree should open");
+
+        for key in [300, -5, 0, -300, 5] {
+            tree.insert(&key, &0).unwrap();
+        }
+
+        assert_eq!(
+            tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![-300, -5, 0, 5, 300]
+        );
+        assert_eq!(
+            tree.range(-10..10)
+                .unwrap()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            vec![-5, 0, 5]
+        );
+    }
+}
+
+#[cfg(test)]
+mod composite_key_tests {
+    use crate::keys::{to_ordered_bytes, CompositeKey};
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn composite_key() {
+        let key = CompositeKey::new().push(&"chip".to_string()).push(&7u64);
+        assert_eq!(
+            key.as_bytes(),
+            to_ordered_bytes(&("chip".to_string(), 7u64))
+        );
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<(String, u64), u8>("composite_key")
+            .expect("tree should open");
+
+        tree.insert(&("chip".to_string(), 2), &0).unwrap();
+        tree.insert(&("chip".to_string(), 10), &0).unwrap();
+        tree.insert(&("chipshifter".to_string(), 1), &0).unwrap();
+        tree.insert(&("angel".to_string(), 5), &0).unwrap();
+
+        // "chip" doesn't match "chipshifter", and timestamps are in numeric order
+        let user = CompositeKey::new().push(&"chip".to_string());
+        assert_eq!(
+            tree.scan_prefix_bytes(user)
+                .map(|((_, ts), _)| ts)
+                .collect::<Vec<_>>(),
+            vec![2, 10]
+        );
+
+        let since = ("chip".to_string(), 5);
+        let until = ("chip".to_string(), u64::MAX);
+        assert_eq!(tree.range(since..=until).unwrap().count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod str_key_tests {
+    use crate::keys::StrKeyCodec;
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn str_keys() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree_with_key_codec::<String, u8, StrKeyCodec>("str_keys")
+            .expect("tree should open");
+
+        for (key, value) in [("user:b", 2), ("user:a", 1), ("group:a", 0), ("user", 3)] {
+            tree.insert(&key.to_string(), &value).unwrap();
+        }
+
+        assert_eq!(
+            tree.scan_prefix_bytes("user:").collect::<Vec<_>>(),
+            vec![("user:a".to_string(), 1), ("user:b".to_string(), 2)]
+        );
+        assert_eq!(tree.scan_prefix(&"user".to_string()).unwrap().count(), 3);
+        assert_eq!(
+            tree.range_key_bytes("user:a".."user:b").collect::<Vec<_>>(),
+            vec![(b"user:a".to_vec(), 1)]
+        );
+        assert_eq!(tree.first().unwrap(), Some(("group:a".to_string(), 0)));
+    }
+}
+
+#[cfg(test)]
+mod reverse_key_tests {
+    use crate::keys::{from_ordered_bytes, to_ordered_bytes, Reverse};
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn reverse() {
+        let key = (Reverse("b\0".to_string()), 3u8);
+        assert_eq!(
+            from_ordered_bytes::<(Reverse<String>, u8)>(&to_ordered_bytes(&key)).unwrap(),
+            key
+        );
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<Reverse<u64>, String>("reverse")
+            .expect("tree should open");
+
+        for ts in [10u64, 30, 20] {
+            tree.insert(&Reverse(ts), &ts.to_string()).unwrap();
+        }
+
+        assert_eq!(tree.first().unwrap(), Some((Reverse(30), "30".to_string())));
+        assert_eq!(
+            tree.iter()
+                .take(2)
+                .map(|(Reverse(ts), _)| ts)
+                .collect::<Vec<_>>(),
+            vec![30, 20]
+        );
+        assert_eq!(tree.range(..Reverse(20)).unwrap().count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod ordered_float_tests {
+    use crate::keys::{from_ordered_bytes, to_ordered_bytes, OrderedFloat};
+
+    #[test]
+    fn byte_order_matches_total_order() {
+        let floats = [
+            f64::NEG_INFINITY,
+            -1.5,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            2.0,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+
+        for pair in floats.windows(2) {
+            let (a, b) = (OrderedFloat(pair[0]), OrderedFloat(pair[1]));
+            assert!(a < b);
+            assert!(to_ordered_bytes(&a) < to_ordered_bytes(&b));
+        }
+
+        for float in floats {
+            let decoded: OrderedFloat<f64> =
+                from_ordered_bytes(&to_ordered_bytes(&OrderedFloat(float))).unwrap();
+            assert_eq!(decoded.0.to_bits(), float.to_bits());
+        }
+
+        assert!(to_ordered_bytes(&OrderedFloat(-3.0f32)) < to_ordered_bytes(&OrderedFloat(1.0f32)));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod key_codec_tests {
+    use crate::keys::SerdeKeyCodec;
+    use crate::{Db, RelaxedSerdeTree, StrictTree};
+
+    #[test]
+    fn ordered_keys_with_serde_values() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<i32, (String, f64)>("ordered_serde")
+            .expect("tree should open");
+
+        for ts in [3, -1, 2] {
+            tree.insert(&ts, &("s1".to_string(), ts as f64)).unwrap();
+        }
+
+        assert_eq!(
+            tree.range(..3).unwrap().collect::<Vec<_>>(),
+            vec![(-1, ("s1".to_string(), -1.0)), (2, ("s1".to_string(), 2.0))]
+        );
+    }
+
+    #[test]
+    fn serde_keys_with_bincode_values() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree_with_key_codec::<String, u64, SerdeKeyCodec>("serde_keys")
+            .expect("tree should open");
+
+        tree.insert(&"key".to_string(), &7).unwrap();
+        assert_eq!(tree.get(&"key".to_string()).unwrap(), Some(7));
+
+        let relaxed = ser_db
+            .open_relaxed_serde_tree("serde_keys")
+            .expect("tree should open");
+        assert!(relaxed.contains_key(&"key".to_string()).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "keyed-hashing"))]
+mod hashed_key_tests {
+    use crate::check::ReportLevel;
+    use crate::codec::BincodeCodec;
+    use crate::keys::{HashedKeyCodec, HASHED_KEY_LEN};
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn hashed_keys() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let strict = ser_db
+            .open_tree::<String, u64, BincodeCodec, HashedKeyCodec>("hashed_keys")
+            .expect("tree should open");
+        let tree = strict.hashed(b"pepper");
+
+        let email = "alice@example.com".to_string();
+        tree.insert(&email, &1).unwrap();
+        assert_eq!(tree.get(&email).unwrap(), Some(1));
+        assert!(tree.contains_key(&email).unwrap());
+        assert!(!tree.contains_key(&"bob@example.com".to_string()).unwrap());
+
+        let entries: Vec<_> = tree.iter_hashed().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, tree.hash_key(&email).unwrap());
+        assert_eq!(entries[0].0.len(), HASHED_KEY_LEN);
+        assert_eq!(entries[0].1, 1);
+        assert_eq!(strict.iter_hashed().count(), 1);
+
+        let (key, _) = tree.inner().first().unwrap().unwrap();
+        assert!(!key
+            .windows(email.len())
+            .any(|window| window == email.as_bytes()));
+
+        // Hashes can't be computed without the secret, nor decoded
+        assert!(matches!(strict.get(&email), Err(Error::IllegalOperation)));
+        assert!(matches!(
+            strict.try_iter().next(),
+            Some(Err(Error::IllegalOperation))
+        ));
+        assert!(ser_db.check(ReportLevel::Summary).unwrap().is_clean());
+
+        // Another secret hashes keys differently
+        let other = strict.hashed(b"salt");
+        assert_eq!(other.get(&email).unwrap(), None);
+        assert_ne!(
+            other.hash_key(&email).unwrap(),
+            tree.hash_key(&email).unwrap()
+        );
+
+        assert_eq!(tree.remove(&email).unwrap(), Some(1));
+        assert!(tree.is_empty());
+    }
+}
+
+#[cfg(all(test, feature = "ulid"))]
+mod ulid_key_tests {
+    use crate::keys::UlidKey;
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn ulid_key() {
+        let ulid = UlidKey::from_parts(1_700_000_000_000, 42);
+        assert_eq!(ulid.timestamp_ms(), 1_700_000_000_000);
+        assert_eq!(ulid.to_string().len(), 26);
+        assert_eq!(ulid.to_string().parse::<UlidKey>().unwrap(), ulid);
+        assert_eq!(
+            ulid.to_string().to_lowercase().parse::<UlidKey>().unwrap(),
+            ulid
+        );
+        assert_eq!(UlidKey(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+        assert!("8ZZZZZZZZZZZZZZZZZZZZZZZZZ".parse::<UlidKey>().is_err());
+        assert!("0000000000000000000000000U".parse::<UlidKey>().is_err());
+
+        assert!(UlidKey::new().timestamp_ms() > 1_700_000_000_000);
+        assert_ne!(UlidKey::new(), UlidKey::new());
+    }
+
+    #[test]
+    fn ordered_by_time() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<UlidKey, u32>("events").unwrap();
+        for (i, timestamp) in [3_000, 1_000, 2_000].into_iter().enumerate() {
+            tree.insert(&UlidKey::from_parts(timestamp, u128::MAX), &(i as u32))
+                .unwrap();
+        }
+
+        assert_eq!(
+            tree.iter()
+                .rev()
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>(),
+            vec![0, 2, 1]
+        );
+        assert_eq!(tree.range(UlidKey::min_at(2_000)..).unwrap().count(), 2);
+    }
+}
+
+#[cfg(all(test, feature = "uuid"))]
+mod uuid_key_tests {
+    use crate::keys::{to_ordered_bytes, UuidKey};
+
+    #[test]
+    fn uuid_key() {
+        let uuid: UuidKey = "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap();
+        assert_eq!(uuid.version(), 4);
+        assert_eq!(uuid.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!(to_ordered_bytes(&uuid), uuid.as_bytes());
+        assert!("67e5504410b1426f9247bb680e5fe0c8"
+            .parse::<UuidKey>()
+            .is_err());
+        assert!("67e55044-10b1-426f-9247-bb680e5fe0c+"
+            .parse::<UuidKey>()
+            .is_err());
+
+        let v4 = UuidKey::new_v4();
+        assert_eq!(v4.version(), 4);
+        assert_eq!(v4.as_bytes()[8] & 0xc0, 0x80);
+
+        let first = UuidKey::now_v7();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = UuidKey::now_v7();
+        assert_eq!(first.version(), 7);
+        assert!(first < second);
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod migration_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::{
+        error::Error,
+        migration::{MigrationRegistry, VersionedTree},
+        Db,
+    };
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: u8,
+    }
+
+    fn registry() -> MigrationRegistry<User> {
+        MigrationRegistry::new()
+            .register(|age: u8| (String::from("unknown"), age))
+            .register(|(name, age): (String, u8)| User { name, age })
+    }
+
+    #[test]
+    fn upgrades_on_get() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let old: VersionedTree<u8, u8> = ser_db
+            .open_versioned_tree("users", MigrationRegistry::new())
+            .unwrap();
+        old.insert(&1, &30).unwrap();
+        old.insert(&2, &40).unwrap();
+
+        let tree: VersionedTree<u8, User> =
+            ser_db.open_versioned_tree("users", registry()).unwrap();
+        assert_eq!(tree.current_version(), 2);
+        assert_eq!(
+            tree.get(&1).unwrap(),
+            Some(User {
+                name: "unknown".to_string(),
+                age: 30
+            })
+        );
+
+        // Without write back, the stored value is left as it was
+        assert_eq!(old.get(&1).unwrap(), Some(30));
+
+        let alice = User {
+            name: "alice".to_string(),
+            age: 25,
+        };
+        tree.insert(&3, &alice).unwrap();
+        assert_eq!(tree.get(&3).unwrap(), Some(alice));
+        assert_eq!(tree.iter().count(), 3);
+
+        // Values newer than the registry knows about aren't decoded as something else
+        assert!(matches!(
+            old.get(&3),
+            Err(Error::UnsupportedVersion {
+                version: 2,
+                current: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn write_back() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let old: VersionedTree<u8, u8> = ser_db
+            .open_versioned_tree("users", MigrationRegistry::new())
+            .unwrap();
+        old.insert(&1, &30).unwrap();
+        old.insert(&2, &40).unwrap();
+
+        let tree: VersionedTree<u8, User> = ser_db
+            .open_versioned_tree("users", registry())
+            .unwrap()
+            .with_write_back(true);
+        assert_eq!(tree.get(&1).unwrap().unwrap().age, 30);
+        assert!(old.get(&1).is_err());
+        assert_eq!(old.get(&2).unwrap(), Some(40));
+
+        assert_eq!(tree.migrate_all().unwrap(), 1);
+        assert!(old.get(&2).is_err());
+        assert_eq!(tree.migrate_all().unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod migrate_tree_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_to_bincode() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let src = ser_db.open_serde_tree::<u32, String>("old").unwrap();
+        for i in 0..25 {
+            src.insert(&i, &i.to_string()).unwrap();
+        }
+
+        let dst = ser_db.open_bincode_tree::<u64, usize>("new").unwrap();
+        let migrated = ser_db
+            .migrate_tree(&src, &dst, |k, v| (k as u64, v.len()), 10)
+            .unwrap();
+
+        assert_eq!(migrated, 25);
+        assert_eq!(dst.len(), 25);
+        assert_eq!(dst.get(&12).unwrap(), Some(2));
+        assert_eq!(src.len(), 25);
+    }
+
+    #[test]
+    fn resumes_after_error() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let src = ser_db.open_bincode_tree::<u8, u64>("old").unwrap();
+        for i in 0..10 {
+            src.insert(&i, &(i as u64)).unwrap();
+        }
+        // An entry that doesn't decode as `u64` stops the migration
+        ser_db
+            .inner_db
+            .open_tree("old")
+            .unwrap()
+            .insert([5], vec![0xff, 0xff])
+            .unwrap();
+
+        let dst = ser_db.open_bincode_tree::<u8, u64>("new").unwrap();
+        assert!(matches!(
+            ser_db.migrate_tree(&src, &dst, |k, v| (k, v * 2), 2),
+            Err(Error::BincodeError(_))
+        ));
+        // The batches before the failing one were written
+        assert_eq!(dst.len(), 4);
+
+        ser_db
+            .inner_db
+            .open_tree("old")
+            .unwrap()
+            .remove([5])
+            .unwrap();
+        src.insert(&5, &5).unwrap();
+        dst.clear().unwrap();
+        assert_eq!(
+            ser_db
+                .migrate_tree(&src, &dst, |k, v| (k, v * 2), 2)
+                .unwrap(),
+            6
+        );
+        assert_eq!(dst.len(), 6);
+        assert_eq!(dst.get(&9).unwrap(), Some(18));
+
+        // Once done, migrating again starts over
+        assert_eq!(
+            ser_db
+                .migrate_tree(&src, &dst, |k, v| (k, v * 2), 2)
+                .unwrap(),
+            10
+        );
+    }
+}
@@ -1,3 +1,52 @@
+pub mod aggregate;
+#[cfg(feature = "serde")]
+pub mod any_tree;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "tokio")]
+pub mod async_tree;
+pub mod backend;
 pub mod bincode;
+pub mod bulk;
+pub mod cache;
+pub mod capped_log;
+pub mod check;
+pub mod codec;
+pub mod counted;
+pub mod cursor;
+pub mod db;
+#[cfg(feature = "derive")]
+pub mod derive;
+pub mod diff;
+pub mod document;
+pub mod dyn_tree;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod event_log;
+pub mod export;
+pub mod formats;
+pub mod health;
+pub mod index;
+pub mod job_queue;
+pub mod journal;
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+pub mod keys;
+pub mod migration;
+pub mod query;
+pub mod quota;
+pub mod schema;
+pub mod scoped;
 #[cfg(feature = "serde")]
-pub mod serde;
\ No newline at end of file
+pub mod serde;
+pub mod set;
+pub mod snapshot;
+pub mod sorted_set;
+pub mod tagged;
+pub mod testing;
+#[cfg(feature = "text-index")]
+pub mod text_index;
+pub mod time_series;
+pub mod ttl;
+pub mod value_ref;
+pub mod view;
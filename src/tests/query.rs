@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod query_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::query::Plan;
+    use crate::{Db, StrictTree};
+
+    #[derive(Encode, Decode, Debug, Clone, PartialEq)]
+    struct Task {
+        status: String,
+        priority: u8,
+    }
+
+    fn task(status: &str, priority: u8) -> Task {
+        Task {
+            status: status.to_string(),
+            priority,
+        }
+    }
+
+    #[test]
+    fn filter_and_limit() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, u32>("numbers").unwrap();
+        for i in 0..10 {
+            tree.insert(&i, &(i * 10)).unwrap();
+        }
+
+        let query = tree.query().filter(|value| value % 20 == 0).limit(3);
+        assert_eq!(query.plan(), Plan::FullScan);
+        assert_eq!(query.collect().unwrap(), vec![(0, 0), (2, 20), (4, 40)]);
+
+        let query = tree
+            .query_range(5..)
+            .unwrap()
+            .filter(|value| *value > 60)
+            .filter(|value| *value < 90);
+        assert_eq!(query.plan(), Plan::KeyRange);
+        assert_eq!(query.collect().unwrap(), vec![(7, 70), (8, 80)]);
+    }
+
+    #[test]
+    fn prefers_index_range() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut tasks = ser_db.open_indexed_tree::<u32, Task>("tasks").unwrap();
+        let by_status = tasks
+            .add_index("status", |task: &Task| task.status.clone())
+            .unwrap();
+
+        tasks.insert(&1, &task("open", 1)).unwrap();
+        tasks.insert(&2, &task("done", 3)).unwrap();
+        tasks.insert(&3, &task("open", 3)).unwrap();
+        tasks.insert(&4, &task("open", 2)).unwrap();
+
+        let query = tasks
+            .query()
+            .filter(|task| task.priority > 1)
+            .range_by_index(&by_status, "open".to_string()..="open".to_string());
+        assert_eq!(query.plan(), Plan::IndexRange);
+
+        let keys = query
+            .into_iter()
+            .map(|entry| entry.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![3, 4]);
+    }
+}
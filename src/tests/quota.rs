@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod quota_tests {
+    use crate::error::Error;
+    use crate::quota::{Quota, QuotaPolicy};
+    use crate::tree::Batch;
+    use crate::{Db, RelaxedBincodeTree, StrictTree};
+
+    #[test]
+    fn reject_writes() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, u32>("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(2));
+
+        tree.insert(&1, &1).unwrap();
+        tree.insert(&2, &2).unwrap();
+        assert!(matches!(tree.insert(&3, &3), Err(Error::QuotaExceeded)));
+        assert_eq!(tree.len(), 2);
+
+        // Overwriting doesn't add an entry, and removing makes room
+        assert_eq!(tree.insert(&2, &20).unwrap(), Some(2));
+        tree.remove(&1).unwrap();
+        tree.insert(&3, &3).unwrap();
+        assert_eq!(tree.get(&3).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn evict_oldest() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u64, u64>("log").unwrap();
+        // Entries already in the tree are counted
+        tree.insert(&0, &0).unwrap();
+
+        let tree = tree.with_quota(Quota::new(QuotaPolicy::EvictOldest).with_max_entries(3));
+        for i in 1..6 {
+            tree.insert(&i, &i).unwrap();
+        }
+
+        assert_eq!(
+            tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn max_bytes() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u8, String>("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::EvictOldest).with_max_bytes(12));
+
+        // Each entry takes 1 byte of key, plus its length and 1 byte of value
+        tree.insert(&1, &"abcd".to_string()).unwrap();
+        tree.insert(&2, &"efgh".to_string()).unwrap();
+        tree.insert(&3, &"ij".to_string()).unwrap();
+        assert_eq!(tree.get(&1).unwrap(), None);
+        assert_eq!(tree.len(), 2);
+
+        // An entry that can't fit on its own is always rejected
+        assert!(matches!(
+            tree.insert(&4, &"too long for the quota".to_string()),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn relaxed_tree() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_relaxed_bincode_tree("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(1));
+
+        tree.insert(&1u32, &1u32).unwrap();
+        assert!(matches!(
+            tree.insert(&2u32, &2u32),
+            Err(Error::QuotaExceeded)
+        ));
+    }
+
+    #[test]
+    fn clear_and_pop() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, u32>("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(2));
+
+        tree.insert(&1, &1).unwrap();
+        tree.insert(&2, &2).unwrap();
+        tree.clear().unwrap();
+        tree.insert(&3, &3).unwrap();
+        tree.insert(&4, &4).unwrap();
+        assert!(matches!(tree.insert(&5, &5), Err(Error::QuotaExceeded)));
+
+        assert_eq!(tree.pop_min().unwrap(), Some((3, 3)));
+        tree.insert(&5, &5).unwrap();
+        assert_eq!(tree.pop_max().unwrap(), Some((5, 5)));
+        tree.insert(&6, &6).unwrap();
+        assert_eq!(tree.pop_n_min(2).unwrap(), vec![(4, 4), (6, 6)]);
+        tree.insert(&7, &7).unwrap();
+        tree.insert(&8, &8).unwrap();
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn other_writes() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u32, u32>("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_entries(3));
+
+        assert!(tree.insert_if_absent(&1, &1).unwrap());
+        let mut batch = Batch::new();
+        batch.insert(2, 2);
+        batch.insert(3, 3);
+        tree.apply_batch(batch).unwrap();
+        assert!(matches!(
+            tree.insert_if_absent(&4, &4),
+            Err(Error::QuotaExceeded)
+        ));
+        assert!(matches!(
+            tree.bulk_load([(4, 4), (5, 5)]),
+            Err(Error::QuotaExceeded)
+        ));
+        assert!(matches!(
+            tree.update_and_fetch(&4, |_| Some(4)),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(tree.len(), 3);
+
+        // Batches that make room for their inserts go through
+        let mut batch = Batch::new();
+        batch.remove(1);
+        batch.insert(4, 4);
+        tree.apply_batch(batch).unwrap();
+
+        assert_eq!(tree.remove_range(..3).unwrap(), 1);
+        assert_eq!(tree.retain(|key, _| *key != 3).unwrap(), 1);
+        assert_eq!(tree.drain().count(), 1);
+        for i in 0..3 {
+            tree.insert(&i, &i).unwrap();
+        }
+        assert!(matches!(tree.insert(&3, &3), Err(Error::QuotaExceeded)));
+
+        tree.rewrite_in_place().unwrap();
+        assert!(matches!(tree.insert(&3, &3), Err(Error::QuotaExceeded)));
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn merge() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_bincode_tree::<u8, String>("limited")
+            .unwrap()
+            .with_quota(Quota::new(QuotaPolicy::RejectWrites).with_max_bytes(7));
+        tree.set_merge_operator(|_, old: Option<String>, delta| {
+            Some(old.unwrap_or_default() + &delta)
+        });
+
+        // 1 byte of key, plus the length and the characters of the value: 5 then 8 bytes
+        tree.merge(&1, &"abc".to_string()).unwrap();
+        assert!(matches!(
+            tree.merge(&1, &"def".to_string()),
+            Err(Error::QuotaExceeded)
+        ));
+        assert_eq!(tree.get(&1).unwrap().as_deref(), Some("abc"));
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod schema_tests {
+    use crate::{error::Error, Db, StrictTree};
+
+    #[test]
+    fn mismatch_on_open() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        let tree = ser_db.open_bincode_tree::<u8, u64>("users").unwrap();
+        tree.insert(&1, &300).unwrap();
+        assert_eq!(
+            ser_db.schema("users").unwrap().as_deref(),
+            Some("key=u8;value=u64")
+        );
+
+        // Opening again with the same types is fine
+        ser_db.open_bincode_tree::<u8, u64>("users").unwrap();
+
+        assert!(matches!(
+            ser_db.open_bincode_tree::<u8, String>("users"),
+            Err(Error::SchemaMismatch { ref tree, .. }) if tree == "users"
+        ));
+
+        ser_db.reset_schema("users").unwrap();
+        assert_eq!(ser_db.schema("users").unwrap(), None);
+        ser_db.open_bincode_tree::<u8, String>("users").unwrap();
+        assert!(ser_db.open_bincode_tree::<u8, u64>("users").is_err());
+    }
+
+    #[test]
+    fn codecs_can_change() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+
+        ser_db.open_bincode_tree::<u8, u64>("codecs").unwrap();
+        ser_db
+            .open_bincode_tree_with_key_codec::<u8, u64, crate::keys::BincodeKeyCodec>("codecs")
+            .unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod scoped_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn scopes_are_isolated() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u32, String>("tenants").unwrap();
+        let acme = tree.scoped(&"acme").unwrap();
+        let acme_corp = tree.scoped(&"acme corp").unwrap();
+
+        acme.insert(&1, &"a1".to_string()).unwrap();
+        acme.insert(&2, &"a2".to_string()).unwrap();
+        acme_corp.insert(&1, &"c1".to_string()).unwrap();
+
+        assert_eq!(acme.get(&1).unwrap(), Some("a1".to_string()));
+        assert_eq!(acme_corp.get(&1).unwrap(), Some("c1".to_string()));
+        assert!(!acme_corp.contains_key(&2).unwrap());
+        assert_eq!(acme.len(), 2);
+        assert_eq!(tree.len(), 3);
+
+        assert_eq!(acme.clear().unwrap(), 2);
+        assert!(acme.is_empty());
+        assert_eq!(acme_corp.len(), 1);
+    }
+
+    #[test]
+    fn ranges_stay_in_scope() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<i32, i32>("tenants").unwrap();
+        let first = tree.scoped(&1u8).unwrap();
+        let second = tree.scoped(&2u8).unwrap();
+
+        for i in -3..3 {
+            first.insert(&i, &i).unwrap();
+            second.insert(&i, &(i * 10)).unwrap();
+        }
+
+        assert_eq!(
+            first.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![-3, -2, -1, 0, 1, 2]
+        );
+        assert_eq!(
+            second
+                .range(-1..)
+                .unwrap()
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>(),
+            vec![-10, 0, 10, 20]
+        );
+        assert_eq!(first.range(..=-2).unwrap().count(), 2);
+        assert_eq!(second.first().unwrap(), Some((-3, -30)));
+        assert_eq!(first.last().unwrap(), Some((2, 2)));
+        assert_eq!(first.remove(&2).unwrap(), Some(2));
+        assert_eq!(first.last().unwrap(), Some((1, 1)));
+
+        // Scopes nest
+        let nested = first.scoped(&"users").unwrap();
+        nested.insert(&7, &7).unwrap();
+        assert_eq!(nested.iter().collect::<Vec<_>>(), vec![(7, 7)]);
+        assert!(nested.prefix().starts_with(first.prefix()));
+    }
+}
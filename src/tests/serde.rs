@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod relaxed_serde_tests {
-    use crate::{RelaxedSerdeTree, Db};
+    use crate::{error::Error, Db, RelaxedSerdeTree};
 
     #[test]
     fn insert_and_get() {
@@ -229,11 +229,31 @@ mod relaxed_serde_tests {
         assert_eq!(iter.next(), Some(([4u8], [4u8])));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn with_limit() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_relaxed_serde_tree("with_limit")
+            .expect("tree should open")
+            .with_limit::<64>();
+
+        tree.insert(&1u8, &vec![7u8; 8]).unwrap();
+        assert_eq!(tree.get(&1u8).unwrap(), Some(vec![7u8; 8]));
+
+        // A huge length prefix must not be trusted
+        tree.insert(&2u8, &u64::MAX).unwrap();
+        assert!(matches!(
+            tree.get::<_, String>(&2u8),
+            Err(Error::LimitExceeded)
+        ));
+    }
 }
 
 #[cfg(test)]
 mod strict_serde_tests {
-    use crate::{Db, StrictTree};
+    use crate::{error::Error, Db, RelaxedSerdeTree, StrictTree};
 
     #[test]
     fn insert_and_get() {
@@ -465,4 +485,425 @@ mod strict_serde_tests {
         assert_eq!(iter.next(), Some(([4u8], [4u8])));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn strict_decode() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u8, u64>("strict_decode")
+            .expect("tree should open");
+        tree.insert(&1, &300).unwrap();
+
+        // Decoding a `u64` as a `u8` leaves trailing bytes behind
+        ser_db.reset_schema("strict_decode").unwrap();
+        let wrong_tree = ser_db
+            .open_serde_tree::<u8, u8>("strict_decode")
+            .expect("tree should open");
+        assert!(matches!(
+            wrong_tree.get(&1),
+            Err(Error::TrailingBytes { decoded: 1, len: 3 })
+        ));
+        assert!(wrong_tree.iter().next().is_none());
+
+        let lenient_tree = wrong_tree.with_strict_decode(false);
+        assert!(lenient_tree.get(&1).unwrap().is_some());
+    }
+
+    #[test]
+    fn key_validation() {
+        use crate::keys::SerdeKeyCodec;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserializing drops the high byte, so it doesn't round-trip
+        #[derive(Debug, PartialEq)]
+        struct LossyKey(u16);
+
+        impl Serialize for LossyKey {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u16(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for LossyKey {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok(LossyKey(u16::deserialize(deserializer)? & 0xff))
+            }
+        }
+
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree_with_key_codec::<LossyKey, u8, SerdeKeyCodec>("key_validation")
+            .expect("tree should open")
+            .with_key_validation(true);
+
+        tree.insert(&LossyKey(7), &1).unwrap();
+        assert!(matches!(
+            tree.insert(&LossyKey(0x1234), &2),
+            Err(Error::KeyRoundTrip)
+        ));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn rewrite_in_place() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u8, u8>("rewrite")
+            .expect("tree should open");
+
+        tree.insert(&1, &10).unwrap();
+        tree.insert(&2, &20).unwrap();
+        // Entries already encoded with the current settings are left alone
+        assert_eq!(tree.rewrite_in_place().unwrap(), 0);
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, 10), (2, 20)]);
+    }
+
+    #[test]
+    fn failed_rewrite_leaves_tree_readable() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_serde_tree::<u8, u8>("failed_rewrite")
+            .expect("tree should open");
+        for i in 0..10 {
+            tree.insert(&i, &(i * 10)).unwrap();
+        }
+
+        // An entry of the wrong type aborts the rewrite before anything is written
+        let relaxed = ser_db.open_relaxed_serde_tree("failed_rewrite").unwrap();
+        relaxed.insert(&10u8, &300u64).unwrap();
+        assert!(tree.rewrite_in_place().is_err());
+
+        assert_eq!(tree.len(), 11);
+        assert_eq!(
+            tree.range(..10).unwrap().collect::<Vec<_>>(),
+            (0..10).map(|i| (i, i * 10)).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod projection_tests {
+    use crate::{projection::FieldSelector, Db, StrictTree};
+
+    type Record = (u64, String, Vec<u8>);
+
+    #[test]
+    fn project() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u64, Record>("project")
+            .expect("tree should open");
+
+        tree.insert(&1, &(300, "angel".to_string(), vec![7; 1000]))
+            .unwrap();
+
+        let name_field: FieldSelector<Record, (u64,), String> = FieldSelector::new();
+        assert_eq!(
+            tree.project(&1, name_field).unwrap(),
+            Some("angel".to_string())
+        );
+        assert_eq!(tree.project(&2, name_field).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn patch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u64, (String, u64)>("patch")
+            .expect("tree should open");
+
+        tree.insert(&1, &("angel".to_string(), 0)).unwrap();
+
+        let visit = |value: &mut (String, u64)| value.1 += 1;
+        assert_eq!(
+            tree.patch(&1, &visit).unwrap(),
+            Some(("angel".to_string(), 1))
+        );
+        assert_eq!(tree.get(&1).unwrap(), Some(("angel".to_string(), 1)));
+        assert_eq!(tree.patch(&2, &visit).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod bucket_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn bucket() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<(u64, u64), String>("bucket")
+            .expect("tree should open");
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"first".to_string()).unwrap();
+        tree.insert(&(42, 2), &"second".to_string()).unwrap();
+        tree.insert(&(43, 1), &"other".to_string()).unwrap();
+
+        let orders = tree.bucket::<u64, u64>(&42).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            orders,
+            vec![(1, "first".to_string()), (2, "second".to_string())]
+        );
+        assert_eq!(tree.bucket::<u64, u64>(&44).unwrap().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use crate::{serde_tree::Batch, Db, StrictTree};
+
+    #[test]
+    fn apply_batch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u64, String>("batch")
+            .expect("tree should open");
+
+        tree.insert(&1, &"removed".to_string()).unwrap();
+
+        let mut batch = Batch::new();
+        batch.insert(2, "two".to_string());
+        batch.insert(3, "three".to_string());
+        batch.remove(1);
+        batch.remove(3);
+        assert_eq!(batch.len(), 4);
+
+        tree.apply_batch(batch).unwrap();
+
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec![(2, "two".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod update_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn update_and_fetch() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<String, u64>("update")
+            .expect("tree should open");
+        let key = "visits".to_string();
+        let increment = |old: Option<u64>| Some(old.unwrap_or(0) + 1);
+
+        assert_eq!(tree.update_and_fetch(&key, increment).unwrap(), Some(1));
+        assert_eq!(tree.update_and_fetch(&key, increment).unwrap(), Some(2));
+        assert_eq!(tree.fetch_and_update(&key, increment).unwrap(), Some(2));
+        assert_eq!(tree.get(&key).unwrap(), Some(3));
+
+        // Returning None removes the entry
+        assert_eq!(tree.fetch_and_update(&key, |_| None).unwrap(), Some(3));
+        assert!(!tree.contains_key(&key).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn merge() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<String, Vec<String>>("merge")
+            .expect("tree should open");
+        let key = "log".to_string();
+
+        // No operator set yet
+        assert!(tree.merge(&key, &vec!["a".to_string()]).is_err());
+
+        tree.set_merge_operator(|_key: String, old: Option<Vec<String>>, delta| {
+            let mut list = old.unwrap_or_default();
+            list.extend(delta);
+            Some(list)
+        });
+
+        tree.merge(&key, &vec!["a".to_string()]).unwrap();
+        assert_eq!(
+            tree.merge(&key, &vec!["b".to_string()]).unwrap(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            tree.get(&key).unwrap(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+}
+
+#[cfg(test)]
+mod watch_tests {
+    use std::time::Duration;
+
+    use crate::{subscriber::Event, Db, StrictTree};
+
+    #[test]
+    fn watch_prefix() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<(u64, u64), String>("watch")
+            .expect("tree should open");
+        let timeout = Duration::from_secs(1);
+
+        let mut user_42 = tree.watch_prefix(&42u64).unwrap();
+        let mut all = tree.watch().unwrap();
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"order".to_string()).unwrap();
+        tree.remove(&(42, 1)).unwrap();
+
+        assert_eq!(
+            user_42.next_timeout(timeout).unwrap(),
+            Event::Insert {
+                key: (42, 1),
+                value: "order".to_string()
+            }
+        );
+        assert_eq!(
+            user_42.next_timeout(timeout).unwrap(),
+            Event::Remove { key: (42, 1) }
+        );
+        assert!(user_42.next_timeout(Duration::from_millis(10)).is_err());
+
+        assert_eq!(all.next_timeout(timeout).unwrap().key(), &(41, 1));
+    }
+}
+
+#[cfg(test)]
+mod pop_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn pop_min_and_pop_n() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u64, u64>("pop")
+            .expect("tree should open");
+
+        for i in 0..10 {
+            tree.insert(&i, &(i * 10)).unwrap();
+        }
+
+        assert_eq!(tree.pop_min().unwrap(), Some((0, 0)));
+        assert_eq!(tree.pop_n_min(3).unwrap(), vec![(1, 10), (2, 20), (3, 30)]);
+        assert_eq!(tree.pop_n_max(2).unwrap(), vec![(9, 90), (8, 80)]);
+        assert_eq!(tree.len(), 4);
+
+        // Popping more than what's left empties the tree
+        assert_eq!(tree.pop_n_min(10).unwrap().len(), 4);
+        assert_eq!(tree.pop_min().unwrap(), None);
+        assert!(tree.pop_n_max(1).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod scan_prefix_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn scan_prefix() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<(u64, u64), String>("scan_prefix")
+            .expect("tree should open");
+
+        tree.insert(&(41, 1), &"other".to_string()).unwrap();
+        tree.insert(&(42, 1), &"first".to_string()).unwrap();
+        tree.insert(&(42, 2), &"second".to_string()).unwrap();
+        tree.insert(&(43, 1), &"other".to_string()).unwrap();
+
+        let expected = vec![
+            ((42, 1), "first".to_string()),
+            ((42, 2), "second".to_string()),
+        ];
+        assert_eq!(
+            tree.scan_prefix(&42u64).unwrap().collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            tree.scan_prefix_bytes(42u64.to_be_bytes())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(tree.scan_prefix(&44u64).unwrap().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod try_iter_tests {
+    use crate::{Db, RelaxedSerdeTree, StrictTree};
+
+    #[test]
+    fn try_iter() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_serde_tree::<u8, u8>("try_iter")
+            .expect("tree should open");
+
+        tree.insert(&1, &10).unwrap();
+        tree.insert(&3, &30).unwrap();
+
+        // Write a `u64` where a `u8` is expected
+        let relaxed = ser_db.open_relaxed_serde_tree("try_iter").unwrap();
+        relaxed.insert(&2u8, &300u64).unwrap();
+
+        assert_eq!(tree.iter().count(), 2);
+
+        let entries = tree.try_iter().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].as_ref().unwrap(), &(1, 10));
+        assert!(entries[1].is_err());
+
+        let entries = tree.try_range(2..).unwrap().collect::<Vec<_>>();
+        assert!(entries[0].is_err());
+        assert_eq!(entries[1].as_ref().unwrap(), &(3, 30));
+    }
+}
+
+#[cfg(test)]
+mod relaxed_conversion_tests {
+    use crate::keys::SerdeKeyCodec;
+    use crate::{Db, RelaxedSerdeTree, StrictTree};
+
+    #[test]
+    fn assume_types_with_serde_keys() {
+        let ser_db = Db::open_temporary().unwrap();
+        let relaxed = ser_db.open_relaxed_serde_tree("relaxed_keys").unwrap();
+        relaxed.insert(&7u64, &"seven".to_string()).unwrap();
+
+        let tree = relaxed.assume_types_with_key_codec::<u64, String, SerdeKeyCodec>();
+        assert_eq!(tree.get(&7).unwrap().as_deref(), Some("seven"));
+        tree.insert(&8, &"eight".to_string()).unwrap();
+
+        let relaxed = tree.into_relaxed();
+        assert_eq!(
+            relaxed.get::<u64, String>(&8).unwrap().as_deref(),
+            Some("eight")
+        );
+
+        let tree = relaxed.assume_types::<u64, String>();
+        assert_eq!(tree.len(), 2);
+    }
 }
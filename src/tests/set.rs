@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod set_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::keys::BincodeKeyCodec;
+    use crate::Db;
+
+    #[test]
+    fn members() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.clone().into();
+        let set = ser_db.open_set::<String>("tags").unwrap();
+
+        assert!(set.insert(&"rust".to_string()).unwrap());
+        assert!(!set.insert(&"rust".to_string()).unwrap());
+        assert!(set.insert(&"db".to_string()).unwrap());
+        assert!(set.contains(&"db".to_string()).unwrap());
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec!["db", "rust"]);
+
+        assert!(set.remove(&"db".to_string()).unwrap());
+        assert!(!set.remove(&"db".to_string()).unwrap());
+        assert!(!set.contains(&"db".to_string()).unwrap());
+
+        // Members are stored with empty values
+        let raw = db.open_tree("tags").unwrap();
+        assert!(raw.iter().values().all(|value| value.unwrap().is_empty()));
+    }
+
+    #[test]
+    fn set_operations() {
+        let ser_db = Db::open_temporary().unwrap();
+        let left = ser_db.open_set::<u32>("left").unwrap();
+        let right = ser_db.open_set::<u32>("right").unwrap();
+        for member in [1, 2, 3, 5] {
+            left.insert(&member).unwrap();
+        }
+        for member in [2, 4, 5, 6] {
+            right.insert(&member).unwrap();
+        }
+
+        assert_eq!(
+            left.union(&right).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(left.intersection(&right).collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(left.difference(&right).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(right.difference(&left).collect::<Vec<_>>(), vec![4, 6]);
+
+        right.clear().unwrap();
+        assert_eq!(left.intersection(&right).count(), 0);
+        assert_eq!(left.union(&right).count(), 4);
+    }
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn bincode_members() {
+        let ser_db = Db::open_temporary().unwrap();
+        let set = ser_db
+            .open_set_with_key_codec::<Point, BincodeKeyCodec>("points")
+            .unwrap();
+
+        assert!(set.insert(&Point { x: 1, y: -1 }).unwrap());
+        assert!(set.contains(&Point { x: 1, y: -1 }).unwrap());
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Point { x: 1, y: -1 }]);
+    }
+}
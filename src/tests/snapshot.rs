@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod snapshot_store_tests {
+    use crate::Db;
+
+    #[test]
+    fn replay_from_snapshot() {
+        let ser_db = Db::open_temporary().unwrap();
+        let log = ser_db.open_event_log::<i64>("deposits").unwrap();
+        let snapshots = ser_db.open_snapshot_store::<i64>("balances").unwrap();
+        let apply = |balance: i64, deposit: i64| balance + deposit;
+
+        assert_eq!(snapshots.replay(&log, 0, apply).unwrap(), (0, None));
+
+        log.append(&10).unwrap();
+        let seq = log.append(&20).unwrap();
+        let (balance, last) = snapshots.replay(&log, 0, apply).unwrap();
+        assert_eq!((balance, last), (30, Some(seq)));
+        snapshots.save(seq, &balance).unwrap();
+
+        // Events included in the snapshot aren't needed anymore
+        log.truncate_before(seq + 1).unwrap();
+        let last = log.append(&5).unwrap();
+        assert_eq!(snapshots.replay(&log, 0, apply).unwrap(), (35, Some(last)));
+        assert_eq!(snapshots.latest_until(seq - 1).unwrap(), None);
+    }
+
+    #[test]
+    fn prune() {
+        let ser_db = Db::open_temporary().unwrap();
+        let snapshots = ser_db.open_snapshot_store::<u32>("snapshots").unwrap();
+        for seq in 0..5 {
+            snapshots.save(seq, &(seq as u32)).unwrap();
+        }
+
+        assert_eq!(snapshots.latest_until(2).unwrap(), Some((2, 2)));
+        assert_eq!(snapshots.prune(2).unwrap(), 3);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots.latest().unwrap(), Some((4, 4)));
+        assert_eq!(snapshots.prune(2).unwrap(), 0);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod sorted_set_tests {
+    use crate::Db;
+
+    #[test]
+    fn leaderboard() {
+        let ser_db = Db::open_temporary().unwrap();
+        let board = ser_db.open_sorted_set::<String, i64>("board").unwrap();
+        for (player, score) in [("angel", 30), ("chip", -5), ("nyx", 30), ("iris", 12)] {
+            assert_eq!(board.add(&player.to_string(), &score).unwrap(), None);
+        }
+
+        assert_eq!(board.score(&"iris".to_string()).unwrap(), Some(12));
+        assert_eq!(board.rank(&"chip".to_string()).unwrap(), Some(0));
+        assert_eq!(board.rank(&"nyx".to_string()).unwrap(), Some(3));
+        assert_eq!(board.rank(&"zed".to_string()).unwrap(), None);
+
+        let top = board.top(3);
+        assert_eq!(
+            top,
+            vec![
+                ("nyx".to_string(), 30),
+                ("angel".to_string(), 30),
+                ("iris".to_string(), 12)
+            ]
+        );
+
+        // Updating a score moves the member
+        assert_eq!(board.add(&"chip".to_string(), &100).unwrap(), Some(-5));
+        assert_eq!(board.top(1), vec![("chip".to_string(), 100)]);
+        assert_eq!(board.rank(&"chip".to_string()).unwrap(), Some(3));
+
+        assert_eq!(board.remove(&"angel".to_string()).unwrap(), Some(30));
+        assert_eq!(board.remove(&"angel".to_string()).unwrap(), None);
+        assert_eq!(board.len(), 3);
+        assert_eq!(board.range_by_score(..).count(), 3);
+    }
+
+    #[test]
+    fn range_by_score() {
+        let ser_db = Db::open_temporary().unwrap();
+        let set = ser_db.open_sorted_set::<u32, u8>("set").unwrap();
+        for member in 0..6 {
+            set.add(&member, &(member as u8 / 2 * 100)).unwrap();
+        }
+        set.add(&6, &u8::MAX).unwrap();
+        let members = |iter: &mut dyn Iterator<Item = (u32, u8)>| {
+            iter.map(|(member, _)| member).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            members(&mut set.range_by_score(100..=200)),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(members(&mut set.range_by_score(100..200)), vec![2, 3]);
+        assert_eq!(
+            members(
+                &mut set
+                    .range_by_score((std::ops::Bound::Excluded(0), std::ops::Bound::Included(100)))
+            ),
+            vec![2, 3]
+        );
+        assert_eq!(members(&mut set.range_by_score(200..)), vec![4, 5, 6]);
+        assert_eq!(members(&mut set.range_by_score(..=u8::MAX)).len(), 7);
+        assert_eq!(
+            members(&mut set.range_by_score((
+                std::ops::Bound::Excluded(u8::MAX),
+                std::ops::Bound::Unbounded
+            ))),
+            Vec::<u32>::new()
+        );
+    }
+}
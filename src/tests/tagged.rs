@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tagged_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::error::Error;
+    use crate::Db;
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct User {
+        name: String,
+    }
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct Order {
+        total: u64,
+    }
+
+    #[test]
+    fn checks_types() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db
+            .open_tagged_tree::<String>("mixed")
+            .unwrap()
+            .register::<User>(1)
+            .register::<Order>(2);
+
+        let user = User {
+            name: "alice".to_string(),
+        };
+        tree.insert_as(&"user".to_string(), &user).unwrap();
+        tree.insert_as(&"order".to_string(), &Order { total: 42 })
+            .unwrap();
+
+        assert_eq!(
+            tree.get_as::<User>(&"user".to_string()).unwrap(),
+            Some(user)
+        );
+        assert_eq!(tree.stored_tag(&"order".to_string()).unwrap(), Some(2));
+        assert!(matches!(
+            tree.get_as::<Order>(&"user".to_string()),
+            Err(Error::TypeMismatch {
+                expected: 2,
+                stored: 1
+            })
+        ));
+        assert!(matches!(
+            tree.get_as::<u32>(&"user".to_string()),
+            Err(Error::UnregisteredType(_))
+        ));
+        assert_eq!(tree.get_as::<Order>(&"missing".to_string()).unwrap(), None);
+
+        let orders = tree.iter_as::<Order>().unwrap().collect::<Vec<_>>();
+        assert_eq!(orders, vec![("order".to_string(), Order { total: 42 })]);
+
+        // Replacing a value can change its type
+        tree.insert_as(&"user".to_string(), &Order { total: 1 })
+            .unwrap();
+        assert_eq!(tree.iter_as::<Order>().unwrap().count(), 2);
+        assert!(tree.remove(&"user".to_string()).unwrap());
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "tag 1 is already registered")]
+    fn duplicate_tags() {
+        let ser_db = Db::open_temporary().unwrap();
+        let _ = ser_db
+            .open_tagged_tree::<u32>("mixed")
+            .unwrap()
+            .register::<User>(1)
+            .register::<Order>(1);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod model_check_tests {
+    use crate::testing::{model_check, Arbitrary, Gen, TempDb};
+    use crate::StrictTree;
+
+    #[test]
+    fn gen_is_deterministic() {
+        let mut a = Gen::new(42);
+        let mut b = Gen::new(42);
+
+        for _ in 0..64 {
+            assert_eq!(u64::arbitrary(&mut a), u64::arbitrary(&mut b));
+        }
+        assert_eq!(String::arbitrary(&mut a), String::arbitrary(&mut b));
+    }
+
+    #[test]
+    fn bincode_tree_matches_model() {
+        let tree = TempDb::new()
+            .open_bincode_tree::<u16, Vec<u8>>("model_check")
+            .expect("tree should open");
+
+        for seed in 0..32 {
+            model_check(&tree, seed, 200).expect("tree should match model");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_tree_matches_model() {
+        let tree = TempDb::new()
+            .open_serde_tree::<String, (u32, bool)>("model_check")
+            .expect("tree should open");
+
+        for seed in 0..32 {
+            model_check(&tree, seed, 200).expect("tree should match model");
+        }
+    }
+
+    #[test]
+    fn temp_db_fixtures() {
+        let db = TempDb::new();
+        let tree = db.bincode_tree_with("fixture", [(2u8, "b".to_string()), (1, "a".to_string())]);
+
+        assert_eq!(tree.first().unwrap(), Some((1, "a".to_string())));
+        assert!(db.tree_names().contains(&"fixture".to_string()));
+        assert!(db.sled_db().tree_names().contains(&"fixture".into()));
+
+        // Each one is a separate database
+        assert!(!TempDb::new().tree_names().contains(&"fixture".to_string()));
+    }
+}
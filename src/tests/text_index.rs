@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod text_index_tests {
+    use bincode::{Decode, Encode};
+
+    use crate::text_index::tokenize;
+    use crate::Db;
+
+    #[derive(Encode, Decode, Debug, Clone, PartialEq)]
+    struct Note {
+        title: String,
+        body: String,
+    }
+
+    fn note(title: &str, body: &str) -> Note {
+        Note {
+            title: title.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn tokenizes() {
+        assert_eq!(
+            tokenize("Hello, world! hello-again")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["again", "hello", "world"]
+        );
+    }
+
+    #[test]
+    fn search_and_semantics() {
+        let ser_db = Db::open_temporary().unwrap();
+        let mut notes = ser_db.open_indexed_tree::<u32, Note>("notes").unwrap();
+        let text = notes
+            .add_text_index("text", |note: &Note| vec![&note.title, &note.body])
+            .unwrap();
+
+        notes
+            .insert(&1, &note("Groceries", "Milk, eggs and bread"))
+            .unwrap();
+        notes
+            .insert(&2, &note("Recipe", "Bread: flour, water, salt"))
+            .unwrap();
+        notes.insert(&3, &note("Todo", "Buy milk")).unwrap();
+
+        let keys = |query| {
+            text.search(query)
+                .unwrap()
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys("bread"), vec![1, 2]);
+        assert_eq!(keys("MILK bread"), vec![1]);
+        assert_eq!(keys("milk butter"), Vec::<u32>::new());
+        assert_eq!(keys("bre"), Vec::<u32>::new());
+        assert_eq!(keys("  "), Vec::<u32>::new());
+
+        // Updates replace the terms of the entry
+        notes.insert(&3, &note("Todo", "Buy bread")).unwrap();
+        assert_eq!(keys("milk"), vec![1]);
+        assert_eq!(keys("bread"), vec![1, 2, 3]);
+        notes.remove(&1).unwrap();
+        assert_eq!(keys("bread"), vec![2, 3]);
+    }
+}
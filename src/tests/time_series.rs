@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod time_series_tests {
+    use crate::Db;
+
+    #[test]
+    fn series() {
+        let ser_db = Db::open_temporary().unwrap();
+        let metrics = ser_db.open_time_series_tree::<f64>("metrics").unwrap();
+        for t in 0..10 {
+            metrics.append("cpu", t * 10, &(t as f64)).unwrap();
+        }
+        metrics.append("mem", 5, &0.5).unwrap();
+        metrics.append("a", u64::MAX, &1.0).unwrap();
+
+        assert_eq!(metrics.get("cpu", 20).unwrap(), Some(2.0));
+        assert_eq!(metrics.get("cpu", 25).unwrap(), None);
+
+        let between = metrics.between("cpu", 20..50).unwrap().collect::<Vec<_>>();
+        assert_eq!(between, vec![(20, 2.0), (30, 3.0), (40, 4.0)]);
+        assert_eq!(metrics.between("cpu", ..).unwrap().count(), 10);
+        assert_eq!(metrics.between("mem", 6..).unwrap().count(), 0);
+
+        assert_eq!(
+            metrics.latest("cpu", 2).unwrap(),
+            vec![(90, 9.0), (80, 8.0)]
+        );
+        assert_eq!(metrics.latest("disk", 2).unwrap(), vec![]);
+
+        assert_eq!(metrics.series().unwrap(), vec!["a", "cpu", "mem"]);
+
+        assert_eq!(metrics.remove_before("cpu", 50).unwrap(), 5);
+        assert_eq!(metrics.between("cpu", ..).unwrap().next(), Some((50, 5.0)));
+        assert_eq!(metrics.len(), 7);
+    }
+}
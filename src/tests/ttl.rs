@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod ttl_tests {
+    use std::time::Duration;
+
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn expired_entries_are_hidden_then_swept() {
+        let ser_db = Db::open_temporary().unwrap();
+        let sessions = ser_db.open_ttl_tree::<String, u32>("sessions").unwrap();
+
+        sessions
+            .insert_with_ttl(&"short".to_string(), &1, Duration::ZERO)
+            .unwrap();
+        sessions
+            .insert_with_ttl(&"long".to_string(), &2, Duration::from_secs(3600))
+            .unwrap();
+        sessions.insert(&"forever".to_string(), &3).unwrap();
+
+        assert_eq!(sessions.get(&"short".to_string()).unwrap(), None);
+        assert!(!sessions.contains_key(&"short".to_string()).unwrap());
+        assert_eq!(sessions.get(&"long".to_string()).unwrap(), Some(2));
+        assert_eq!(sessions.ttl(&"forever".to_string()).unwrap(), None);
+        assert!(sessions.ttl(&"long".to_string()).unwrap().unwrap() > Duration::from_secs(3500));
+        assert_eq!(sessions.len(), 3);
+
+        assert_eq!(sessions.sweep_expired().unwrap(), 1);
+        assert_eq!(sessions.sweep_expired().unwrap(), 0);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.tree().get(&"short".to_string()).unwrap(), None);
+    }
+
+    #[test]
+    fn insert_replaces_ttl() {
+        let ser_db = Db::open_temporary().unwrap();
+        let sessions = ser_db.open_ttl_tree::<u32, u32>("sessions").unwrap();
+
+        sessions.insert_with_ttl(&1, &1, Duration::ZERO).unwrap();
+        // Returns the expired value, and the new one doesn't expire
+        assert_eq!(sessions.insert(&1, &2).unwrap(), Some(1));
+        assert_eq!(sessions.sweep_expired().unwrap(), 0);
+        assert_eq!(sessions.get(&1).unwrap(), Some(2));
+
+        sessions.insert_with_ttl(&2, &2, Duration::ZERO).unwrap();
+        assert_eq!(sessions.remove(&2).unwrap(), Some(2));
+        assert_eq!(sessions.sweep_expired().unwrap(), 0);
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[test]
+    fn background_sweeper() {
+        let ser_db = Db::open_temporary().unwrap();
+        let sessions = ser_db.open_ttl_tree::<u32, u32>("sessions").unwrap();
+        sessions.insert_with_ttl(&1, &1, Duration::ZERO).unwrap();
+
+        let sweeper = sessions.spawn_sweeper(Duration::from_millis(5));
+        for _ in 0..200 {
+            if sessions.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        sweeper.stop();
+
+        assert!(sessions.is_empty());
+    }
+}
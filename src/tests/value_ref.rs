@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod value_ref_tests {
+    use bincode::{BorrowDecode, Decode, Encode};
+
+    use crate::error::Error;
+    use crate::testing::TempDb;
+    use crate::StrictTree;
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct Article {
+        title: String,
+        body: String,
+        tags: Vec<String>,
+    }
+
+    #[derive(BorrowDecode)]
+    struct ArticleRef<'a> {
+        title: &'a str,
+        body: &'a str,
+        tags: Vec<&'a str>,
+    }
+
+    fn borrows_from(bytes: &[u8], field: &str) -> bool {
+        bytes.as_ptr_range().contains(&field.as_ptr())
+    }
+
+    #[test]
+    fn get_ref() {
+        let db = TempDb::new();
+        let article = Article {
+            title: "Title".to_string(),
+            body: "x".repeat(10_000),
+            tags: vec!["a".to_string(), "b".to_string()],
+        };
+        let tree = db.bincode_tree_with("articles", [(1u64, article)]);
+
+        assert!(tree.get_ref(&2).unwrap().is_none());
+
+        let value_ref = tree.get_ref(&1).unwrap().unwrap();
+        let view: ArticleRef = value_ref.decode_borrowed().unwrap();
+        assert_eq!(view.title, "Title");
+        assert_eq!(view.tags, vec!["a", "b"]);
+        assert!(borrows_from(value_ref.as_bytes(), view.body));
+
+        assert_eq!(value_ref.decode().unwrap(), tree.get(&1).unwrap().unwrap());
+
+        // Views must be encoded like the value, down to the last byte
+        assert!(matches!(
+            value_ref.decode_borrowed::<(&str, &str)>(),
+            Err(Error::TrailingBytes { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn get_ref_serde() {
+        let db = TempDb::new();
+        let tree = db.serde_tree_with(
+            "pairs",
+            [(1u8, ("name".to_string(), vec!["x".to_string()]))],
+        );
+
+        let value_ref = tree.get_ref(&1).unwrap().unwrap();
+        let (name, items): (&str, Vec<&str>) = value_ref.decode_borrowed().unwrap();
+        assert_eq!((name, items), ("name", vec!["x"]));
+        assert!(borrows_from(value_ref.as_bytes(), name));
+    }
+}
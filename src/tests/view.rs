@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod mapped_view_tests {
+    use crate::{Db, StrictTree};
+
+    #[test]
+    fn map_view() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let ser_db: Db = db.into();
+        let tree = ser_db
+            .open_bincode_tree::<u64, (String, u64)>("map_view")
+            .expect("tree should open");
+
+        tree.insert(&1, &("angel".to_string(), 10)).unwrap();
+        tree.insert(&2, &("chip".to_string(), 20)).unwrap();
+
+        let names = tree.clone().map_view(|(name, _)| name);
+        assert_eq!(names.get(&1).unwrap(), Some("angel".to_string()));
+        assert_eq!(names.get(&3).unwrap(), None);
+        assert_eq!(names.last().unwrap(), Some((2, "chip".to_string())));
+        assert_eq!(
+            names.iter().rev().collect::<Vec<_>>(),
+            vec![(2, "chip".to_string()), (1, "angel".to_string())]
+        );
+        assert_eq!(names.range(2..).unwrap().count(), 1);
+
+        // The view follows writes made through the tree
+        tree.insert(&3, &("nyx".to_string(), 30)).unwrap();
+        assert_eq!(names.len(), 3);
+    }
+}
+
+#[cfg(test)]
+mod read_only_tests {
+    use crate::{view::ReadOnlyTree, Db, StrictTree};
+
+    /// Stands for code that only gets to read.
+    fn report(tree: &ReadOnlyTree<u64, String>) -> Vec<String> {
+        tree.iter().map(|(_, name)| name).collect()
+    }
+
+    #[test]
+    fn read_only() {
+        let ser_db = Db::open_temporary().unwrap();
+        let tree = ser_db.open_bincode_tree::<u64, String>("users").unwrap();
+        tree.insert(&1, &"angel".to_string()).unwrap();
+        tree.insert(&2, &"chip".to_string()).unwrap();
+
+        let read_only = tree.read_only();
+        assert_eq!(report(&read_only), vec!["angel", "chip"]);
+        assert_eq!(read_only.get(&2).unwrap().as_deref(), Some("chip"));
+        assert_eq!(read_only.first().unwrap().unwrap().0, 1);
+        assert_eq!(read_only.range(2..).unwrap().count(), 1);
+        assert!(read_only.contains_key(&1).unwrap());
+
+        // Writes made through the tree are visible
+        tree.insert(&3, &"nyx".to_string()).unwrap();
+        assert_eq!(read_only.len(), 3);
+
+        let converted: ReadOnlyTree<u64, String> = tree.into();
+        assert_eq!(converted.last().unwrap().unwrap().0, 3);
+    }
+}
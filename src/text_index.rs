@@ -0,0 +1,125 @@
+//! A full-text index, maintained by the writes of an [`IndexedTree`].
+//!
+//! Text is split into terms at every character that isn't alphanumeric, and terms are
+//! lowercased. There is no stemming, ranking or phrase search: [`TextIndex::search`]
+//! returns the entries containing every term of the query.
+
+use bincode::{Decode, Encode};
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::index::{IndexTree, IndexedTree};
+use crate::keys::{to_ordered_bytes, OrderedKey};
+use crate::tree::RawTree;
+use crate::DefaultConfig;
+
+/// The distinct terms of `text`, sorted.
+pub fn tokenize(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl<K: OrderedKey, V: Encode + Decode> IndexedTree<K, V> {
+    /// Indexes the terms of the strings `fields` returns for every entry, and returns
+    /// the handle to search the index. Like [`IndexedTree::add_index`], the index is
+    /// built if it is empty but the tree isn't.
+    pub fn add_text_index<F>(
+        &mut self,
+        index_name: &str,
+        fields: F,
+    ) -> Result<TextIndex<K, V>, Error>
+    where
+        F: Fn(&V) -> Vec<&str> + Send + Sync + 'static,
+    {
+        let index = self.register(
+            index_name,
+            Arc::new(move |value| {
+                fields(value)
+                    .into_iter()
+                    .flat_map(tokenize)
+                    .collect::<BTreeSet<_>>()
+                    .iter()
+                    .map(to_ordered_bytes)
+                    .collect()
+            }),
+        )?;
+
+        Ok(TextIndex {
+            raw: self.tree().raw().clone(),
+            index,
+            key_type: PhantomData,
+        })
+    }
+}
+
+/// A full-text index of an [`IndexedTree`], mapping every term to the keys of the
+/// entries containing it. Returned by [`IndexedTree::add_text_index`].
+pub struct TextIndex<K, V> {
+    raw: RawTree<DefaultConfig>,
+    index: IndexTree<V>,
+    key_type: PhantomData<fn() -> K>,
+}
+
+impl<K, V> Clone for TextIndex<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            index: self.index.clone(),
+            key_type: PhantomData,
+        }
+    }
+}
+
+impl<K: OrderedKey, V: Encode + Decode> TextIndex<K, V> {
+    /// The entries containing every term of `query`, in key order. Returns nothing if
+    /// `query` has no terms.
+    pub fn search(&self, query: &str) -> Result<Vec<(K, V)>, Error> {
+        let terms = tokenize(query)
+            .iter()
+            .map(to_ordered_bytes)
+            .collect::<Vec<_>>();
+
+        let mut matching: Option<BTreeSet<Vec<u8>>> = None;
+        for term in &terms {
+            let mut postings = BTreeSet::new();
+            for key_bytes in self.index.tree.scan_prefix(term).values() {
+                let key_bytes = key_bytes?.to_vec();
+                if matching
+                    .as_ref()
+                    .is_none_or(|matching| matching.contains(&key_bytes))
+                {
+                    postings.insert(key_bytes);
+                }
+            }
+
+            if postings.is_empty() {
+                return Ok(Vec::new());
+            }
+            matching = Some(postings);
+        }
+
+        let mut entries = Vec::new();
+        for key_bytes in matching.unwrap_or_default() {
+            let entry = self.index.lookup(&self.raw, &key_bytes, |entry_keys| {
+                terms.iter().all(|term| {
+                    entry_keys
+                        .iter()
+                        .any(|entry_key| *entry_key == [term.as_slice(), &key_bytes].concat())
+                })
+            })?;
+            entries.extend(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Clears the index and indexes every entry of the tree again, e.g. after writes
+    /// made without the index.
+    pub fn rebuild(&self) -> Result<(), Error> {
+        self.index.rebuild(&self.raw)
+    }
+}
@@ -0,0 +1,98 @@
+use bincode::{Decode, Encode};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+
+use crate::bincode_tree::BincodeTree;
+use crate::{error::Error, StrictTree};
+
+/// The key of a point: the name of its series and its timestamp.
+type PointKey = (String, u64);
+
+/// Values of several time series, opened with [`crate::Db::open_time_series_tree`].
+///
+/// Entries are keyed by `(series, timestamp)`, so that the points of a series are
+/// stored together and in time order. Timestamps are plain `u64`s, in whatever unit
+/// the caller picks, and a series holds at most one value per timestamp.
+#[derive(Clone)]
+pub struct TimeSeriesTree<V> {
+    tree: BincodeTree<PointKey, V>,
+}
+
+impl<V: Encode + Decode> TimeSeriesTree<V> {
+    pub(crate) fn new(tree: BincodeTree<PointKey, V>) -> Self {
+        Self { tree }
+    }
+
+    /// Stores `value` at `timestamp` in `series`, replacing any value already there.
+    pub fn append(&self, series: &str, timestamp: u64, value: &V) -> Result<(), Error> {
+        self.tree.insert(&(series.to_string(), timestamp), value)?;
+        Ok(())
+    }
+
+    pub fn get(&self, series: &str, timestamp: u64) -> Result<Option<V>, Error> {
+        self.tree.get(&(series.to_string(), timestamp))
+    }
+
+    /// Iterates over the points of `series` whose timestamp is in `range`, oldest first.
+    pub fn between<R: RangeBounds<u64>>(
+        &self,
+        series: &str,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (u64, V)> + '_, Error> {
+        let points = self.tree.range(series_range(series, range))?;
+
+        Ok(points.map(|((_, timestamp), value)| (timestamp, value)))
+    }
+
+    /// The `n` latest points of `series`, newest first.
+    pub fn latest(&self, series: &str, n: usize) -> Result<Vec<(u64, V)>, Error> {
+        Ok(self.between(series, ..)?.rev().take(n).collect())
+    }
+
+    /// Removes the points of `series` older than `timestamp`, and returns how many
+    /// were removed.
+    pub fn remove_before(&self, series: &str, timestamp: u64) -> Result<usize, Error> {
+        self.tree.remove_range(series_range(series, ..timestamp))
+    }
+
+    /// The names of the series with at least one point, in order. Only reads the first
+    /// point of every series.
+    pub fn series(&self) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        let mut start = Unbounded;
+
+        while let Some(((name, _), _)) = self.tree.range((start, Unbounded))?.next() {
+            start = Excluded((name.clone(), u64::MAX));
+            names.push(name);
+        }
+
+        Ok(names)
+    }
+
+    /// Number of points, in every series.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
+
+/// The keys of `series` whose timestamp is in `range`.
+fn series_range<R: RangeBounds<u64>>(series: &str, range: R) -> (Bound<PointKey>, Bound<PointKey>) {
+    let key = |timestamp: u64| (series.to_string(), timestamp);
+
+    let start = match range.start_bound() {
+        Included(&t) => Included(key(t)),
+        Excluded(&t) => Excluded(key(t)),
+        Unbounded => Included(key(0)),
+    };
+    let end = match range.end_bound() {
+        Included(&t) => Included(key(t)),
+        Excluded(&t) => Excluded(key(t)),
+        Unbounded => Included(key(u64::MAX)),
+    };
+
+    (start, end)
+}
@@ -0,0 +1,1934 @@
+use bincode::config::{Config, Configuration, Limit};
+use sled::IVec;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::sync::Arc;
+use std::{marker::PhantomData, ops::RangeBounds};
+
+use crate::backend::{bytes_range, KvBackend};
+use crate::codec::{BincodeCodec, Decoder, Encoder, ValueCodec};
+use crate::keys::{self, KeyCodec, OrderedKeyCodec};
+use crate::multi_tree;
+use crate::quota::{Quota, QuotaState};
+use crate::subscriber::Subscriber;
+use crate::value_ref::ValueRef;
+use crate::{admin, check, error::Error, patch::Patch, projection::FieldSelector, StrictTree};
+use crate::{BytesRange, DefaultConfig};
+use sled::transaction::ConflictableTransactionError;
+
+/// How many entries [`Tree::bulk_load`] encodes and writes at a time.
+pub const BULK_LOAD_CHUNK_SIZE: usize = 10_000;
+
+/// Type strict tree whose keys are encoded with `KC` and values with `VC`.
+/// [`crate::bincode_tree::BincodeTree`] and [`crate::serde_tree::SerdeTree`] are
+/// instantiations of it; other formats can be used by implementing [`ValueCodec`].
+/// While this should prevent type errors, it is only a best effort:
+/// [`sled`] stores everything as bytes, and therefore it is never a guarantee
+/// that the things stored in the tree are of the type you expect.
+#[derive(Clone)]
+pub struct Tree<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec, B = sled::Tree> {
+    inner_tree: RawTree<C, B>,
+    key_type: PhantomData<K>,
+    value_type: PhantomData<V>,
+    codecs: PhantomData<(VC, KC)>,
+    validate_keys: bool,
+}
+
+/// Type strict tree storing its values as MessagePack. See [`crate::codec::MessagePackCodec`].
+#[cfg(feature = "messagepack")]
+pub type MessagePackTree<K, V, KC = OrderedKeyCodec> =
+    Tree<K, V, crate::codec::MessagePackCodec, DefaultConfig, KC>;
+
+/// Type strict tree storing its values as CBOR. See [`crate::codec::CborCodec`].
+#[cfg(feature = "cbor")]
+pub type CborTree<K, V, KC = OrderedKeyCodec> =
+    Tree<K, V, crate::codec::CborCodec, DefaultConfig, KC>;
+
+/// Type strict tree storing its values with postcard. See [`crate::codec::PostcardCodec`].
+#[cfg(feature = "postcard")]
+pub type PostcardTree<K, V, KC = OrderedKeyCodec> =
+    Tree<K, V, crate::codec::PostcardCodec, DefaultConfig, KC>;
+
+/// Type strict tree storing its keys and values with borsh. See [`crate::codec::BorshCodec`].
+#[cfg(feature = "borsh")]
+pub type BorshTree<K, V, KC = crate::keys::BorshKeyCodec> =
+    Tree<K, V, crate::codec::BorshCodec, DefaultConfig, KC>;
+
+/// Type strict tree storing its values as protobuf. See [`crate::codec::ProtobufCodec`].
+#[cfg(feature = "prost")]
+pub type ProtoTree<K, V, KC = OrderedKeyCodec> =
+    Tree<K, V, crate::codec::ProtobufCodec, DefaultConfig, KC>;
+
+/// The bincode configuration matching the defaults of bincode 1.x:
+/// little-endian, fixed-size integers and no size limit.
+#[cfg(feature = "bincode1-compat")]
+pub type LegacyConfig =
+    Configuration<bincode::config::LittleEndian, bincode::config::Fixint, bincode::config::NoLimit>;
+
+/// Type strict tree whose keys and values are encoded like `bincode::serialize` did in
+/// bincode 1.x, so that sled databases written with it can be opened without migrating.
+/// Keys are encoded with [`keys::SerdeKeyCodec`] and aren't ordered.
+#[cfg(feature = "bincode1-compat")]
+pub type Bincode1Tree<K, V> =
+    Tree<K, V, crate::codec::SerdeCodec, LegacyConfig, keys::SerdeKeyCodec>;
+
+/// Typed `insert` and `remove` operations to apply atomically with `apply_batch`.
+/// Operations are encoded when the batch is applied, using the tree's settings.
+pub struct Batch<K, V> {
+    ops: Vec<(K, Option<V>)>,
+}
+
+impl<K, V> Batch<K, V> {
+    pub fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.ops.push((key, Some(value)));
+    }
+
+    pub fn remove(&mut self, key: K) {
+        self.ops.push((key, None));
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// The operations of the batch in order, a `None` value removing its key.
+impl<K, V> IntoIterator for Batch<K, V> {
+    type Item = (K, Option<V>);
+    type IntoIter = std::vec::IntoIter<(K, Option<V>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.into_iter()
+    }
+}
+
+impl<K, V> Default for Batch<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The part of every tree that works on encoded keys, stored in the backend `B`.
+/// Methods take the functions used to encode or decode keys and values, so that relaxed
+/// and strict trees of every codec can share them.
+#[derive(Clone)]
+pub(crate) struct RawTree<C, B = sled::Tree> {
+    pub(crate) inner_tree: B,
+    pub(crate) config: C,
+    pub(crate) strict_decode: bool,
+    pub(crate) quota: Option<Arc<QuotaState>>,
+}
+
+impl<E, I, L, B> RawTree<Configuration<E, I, L>, B> {
+    pub(crate) fn with_limit<const N: usize>(self) -> RawTree<Configuration<E, I, Limit<N>>, B> {
+        RawTree {
+            inner_tree: self.inner_tree,
+            config: self.config.with_limit::<N>(),
+            strict_decode: self.strict_decode,
+            quota: self.quota,
+        }
+    }
+}
+
+impl<C, B> RawTree<C, B> {
+    pub(crate) fn with_config<C2: Config>(self, config: C2) -> RawTree<C2, B> {
+        RawTree {
+            inner_tree: self.inner_tree,
+            config,
+            strict_decode: self.strict_decode,
+            quota: self.quota,
+        }
+    }
+}
+
+impl<C: Config, B: KvBackend> RawTree<C, B> {
+    pub(crate) fn new(inner_tree: B, config: C, strict_decode: bool) -> Self {
+        Self {
+            inner_tree,
+            config,
+            strict_decode,
+            quota: None,
+        }
+    }
+
+    /// Inserts raw bytes, handling an existing key as told by `policy`. Returns whether
+    /// the value was written.
+    #[cfg(feature = "jsonl")]
+    pub(crate) fn insert_with_policy(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+        policy: crate::jsonl::ConflictPolicy,
+    ) -> Result<bool, Error> {
+        use crate::jsonl::ConflictPolicy;
+
+        if policy == ConflictPolicy::Overwrite {
+            self.insert_raw(key, value)?;
+            return Ok(true);
+        }
+
+        match self.insert_if_absent_bytes(key, value)? {
+            true => Ok(true),
+            false if policy == ConflictPolicy::Skip => Ok(false),
+            false => Err(Error::KeyExists),
+        }
+    }
+
+    /// Inserts raw bytes if `key` has no value, atomically. Returns whether it was
+    /// inserted.
+    pub(crate) fn insert_if_absent_bytes(
+        &self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> Result<bool, Error> {
+        self.compare_and_swap_raw(&key, None, Some(value))
+    }
+
+    pub(crate) fn flush(&self) -> Result<usize, Error> {
+        self.inner_tree.flush()
+    }
+
+    pub(crate) fn inner(&self) -> &B {
+        &self.inner_tree
+    }
+
+    pub(crate) fn get_raw<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<IVec>, Error> {
+        self.inner_tree.get(key.as_ref())
+    }
+
+    pub(crate) fn insert_raw<K: AsRef<[u8]>, V: Into<IVec>>(
+        &self,
+        key: K,
+        value: V,
+    ) -> Result<Option<IVec>, Error> {
+        let (key, value) = (key.as_ref().to_vec(), value.into());
+
+        match &self.quota {
+            Some(quota) => quota.insert(&self.inner_tree, key, value.to_vec()),
+            None => self.inner_tree.insert(&key, &value),
+        }
+    }
+
+    pub(crate) fn remove_raw<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<IVec>, Error> {
+        match &self.quota {
+            Some(quota) => quota.remove(&self.inner_tree, key.as_ref()),
+            None => self.inner_tree.remove(key.as_ref()),
+        }
+    }
+
+    /// Replaces the value of `key` with `new` if it is still `old`. Returns whether it
+    /// was replaced.
+    pub(crate) fn compare_and_swap_raw(
+        &self,
+        key: &[u8],
+        old: Option<IVec>,
+        new: Option<Vec<u8>>,
+    ) -> Result<bool, Error> {
+        match &self.quota {
+            Some(quota) => quota.compare_and_swap(&self.inner_tree, key, old, new),
+            None => self
+                .inner_tree
+                .compare_and_swap(key, old.as_deref(), new.as_deref()),
+        }
+    }
+
+    /// Writes a batch of encoded operations atomically.
+    pub(crate) fn apply_raw_batch(&self, batch: Batch<Vec<u8>, Vec<u8>>) -> Result<(), Error> {
+        match &self.quota {
+            Some(quota) => quota.apply_batch(&self.inner_tree, batch),
+            None => self.inner_tree.apply_batch(batch),
+        }
+    }
+
+    /// Runs `remove`, which removes the entries it returns from the tree.
+    fn remove_entries(
+        &self,
+        remove: impl FnOnce(&B) -> Result<Vec<(IVec, IVec)>, Error>,
+    ) -> Result<Vec<(IVec, IVec)>, Error> {
+        match &self.quota {
+            Some(quota) => quota.remove_entries(|| remove(&self.inner_tree)),
+            None => remove(&self.inner_tree),
+        }
+    }
+
+    /// Removes the entry with the smallest key, or the largest if `from_max`, and
+    /// returns it.
+    pub(crate) fn pop_raw(&self, from_max: bool) -> Result<Option<(IVec, IVec)>, Error> {
+        let popped = self.remove_entries(|tree| {
+            let entry = match from_max {
+                true => tree.pop_max()?,
+                false => tree.pop_min()?,
+            };
+
+            Ok(entry.into_iter().collect())
+        })?;
+
+        Ok(popped.into_iter().next())
+    }
+
+    pub(crate) fn clear(&self) -> Result<(), Error> {
+        match &self.quota {
+            Some(quota) => quota.clear(&self.inner_tree),
+            None => Ok(self.inner_tree.clear()?),
+        }
+    }
+
+    pub(crate) fn decode_stored<V>(
+        &self,
+        decode_value: Decoder<V, C>,
+        key_bytes: &[u8],
+        value_bytes: &[u8],
+    ) -> Result<V, Error> {
+        decode_stored(
+            decode_value,
+            key_bytes,
+            value_bytes,
+            self.config,
+            self.strict_decode,
+        )
+    }
+
+    pub(crate) fn get_bytes<V>(
+        &self,
+        key_bytes: &[u8],
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        match self.inner_tree.get(key_bytes)? {
+            Some(ivec) => Ok(Some(self.decode_stored(decode_value, key_bytes, &ivec)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn insert_bytes<V>(
+        &self,
+        key_bytes: Vec<u8>,
+        value: &V,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        let value_bytes = encode_value(value, self.config)?;
+        let old = match &self.quota {
+            Some(quota) => quota.insert(&self.inner_tree, key_bytes.clone(), value_bytes)?,
+            None => self.inner_tree.insert(&key_bytes, &value_bytes)?,
+        };
+
+        match old {
+            Some(ivec) => Ok(Some(self.decode_stored(decode_value, &key_bytes, &ivec)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn remove_bytes<V>(
+        &self,
+        key_bytes: &[u8],
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        let old = match &self.quota {
+            Some(quota) => quota.remove(&self.inner_tree, key_bytes)?,
+            None => self.inner_tree.remove(key_bytes)?,
+        };
+
+        match old {
+            Some(ivec) => Ok(Some(self.decode_stored(decode_value, key_bytes, &ivec)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn decode_entry<K, V>(
+        &self,
+        entry: Option<(IVec, IVec)>,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<(K, V)>, Error> {
+        match entry {
+            Some((key_ivec, value_ivec)) => {
+                let key = decode_key(&key_ivec, self.config, self.strict_decode)?;
+
+                let value = self.decode_stored(decode_value, &key_ivec, &value_ivec)?;
+
+                Ok(Some((key, value)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes the entries of `iter`, skipping the ones that fail to decode.
+    pub(crate) fn entries<K, V, E>(
+        &self,
+        iter: impl DoubleEndedIterator<Item = Result<(IVec, IVec), E>>,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> impl DoubleEndedIterator<Item = (K, V)> {
+        let (config, strict) = (self.config, self.strict_decode);
+
+        iter.filter_map(move |res| match res {
+            Ok((key_ivec, value_ivec)) => {
+                let key = decode_key(&key_ivec, config, strict).ok();
+
+                let value = decode_value(&value_ivec, config, strict).ok();
+
+                key.zip(value)
+            }
+            Err(_) => None,
+        })
+    }
+
+    pub(crate) fn try_entries<K, V, E>(
+        &self,
+        iter: impl DoubleEndedIterator<Item = Result<(IVec, IVec), E>>,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>>
+    where
+        Error: From<E>,
+    {
+        let (config, strict) = (self.config, self.strict_decode);
+
+        iter.map(move |res| {
+            let (key_ivec, value_ivec) = res?;
+
+            Ok((
+                decode_key(&key_ivec, config, strict)?,
+                decode_stored(decode_value, &key_ivec, &value_ivec, config, strict)?,
+            ))
+        })
+    }
+
+    /// Like `entries`, but keeps keys as raw bytes.
+    pub(crate) fn key_bytes_entries<V, E>(
+        &self,
+        iter: impl DoubleEndedIterator<Item = Result<(IVec, IVec), E>>,
+        decode_value: Decoder<V, C>,
+    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> {
+        let (config, strict) = (self.config, self.strict_decode);
+
+        iter.filter_map(move |res| match res {
+            Ok((key_ivec, value_ivec)) => {
+                let key = key_ivec.to_vec();
+
+                let value = decode_value(&value_ivec, config, strict).ok();
+
+                value.map(|value| (key, value))
+            }
+            Err(_) => None,
+        })
+    }
+
+    pub(crate) fn encode_range<K, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        encode_key: Encoder<K, C>,
+    ) -> Result<BytesRange, Error> {
+        let start_bound_bytes = match range.start_bound() {
+            Included(r) => Included(encode_key(r, self.config)?),
+            Excluded(r) => Excluded(encode_key(r, self.config)?),
+            Unbounded => Unbounded,
+        };
+        let end_bound_bytes = match range.end_bound() {
+            Included(r) => Included(encode_key(r, self.config)?),
+            Excluded(r) => Excluded(encode_key(r, self.config)?),
+            Unbounded => Unbounded,
+        };
+
+        Ok((start_bound_bytes, end_bound_bytes))
+    }
+
+    /// Removes the keys of `range`, a batch at a time, and returns how many were removed.
+    pub(crate) fn remove_range_bytes(&self, range: BytesRange) -> Result<usize, Error> {
+        let mut removed = 0;
+
+        loop {
+            let mut batch = Batch::new();
+            let mut pending = 0;
+
+            for entry in self
+                .inner_tree
+                .range(range.clone())
+                .take(crate::admin::COPY_BATCH_SIZE)
+            {
+                batch.remove(entry?.0.to_vec());
+                pending += 1;
+            }
+            self.apply_raw_batch(batch)?;
+            removed += pending;
+
+            if pending < crate::admin::COPY_BATCH_SIZE {
+                return Ok(removed);
+            }
+        }
+    }
+
+    /// Removes the entries of `range` one at a time as the iterator advances. Keys
+    /// removed by someone else in the meantime are skipped, so that concurrent
+    /// consumers never take the same entry.
+    pub(crate) fn drain_entries<K, V>(
+        &self,
+        range: BytesRange,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> impl Iterator<Item = Result<(K, V), Error>> {
+        let raw = self.clone();
+
+        self.inner_tree.range(range).filter_map(move |res| {
+            let entry = res.and_then(|(key, _)| {
+                let value = raw.remove_raw(&key)?;
+                Ok(value.map(|value| (key, value)))
+            });
+
+            match entry {
+                Ok(entry) => raw
+                    .decode_entry(entry, decode_key, decode_value)
+                    .transpose(),
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
+    pub(crate) fn pop_n_with<K, V>(
+        &self,
+        n: usize,
+        from_max: bool,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Vec<(K, V)>, Error> {
+        self.remove_entries(|tree| tree.pop_n(n, from_max))?
+            .into_iter()
+            .map(|entry| {
+                Ok(self
+                    .decode_entry(Some(entry), decode_key, decode_value)?
+                    .expect("entry is some"))
+            })
+            .collect()
+    }
+
+    pub(crate) fn bucket_with<S, V>(
+        &self,
+        prefix_bytes: Vec<u8>,
+        decode_suffix: Decoder<S, C>,
+        decode_value: Decoder<V, C>,
+    ) -> impl DoubleEndedIterator<Item = (S, V)> {
+        let prefix_len = prefix_bytes.len();
+        let (config, strict) = (self.config, self.strict_decode);
+
+        self.inner_tree
+            .scan_prefix(&prefix_bytes)
+            .filter_map(move |res| match res {
+                Ok((key_ivec, value_ivec)) => {
+                    let suffix = decode_suffix(&key_ivec[prefix_len..], config, strict).ok();
+
+                    let value = decode_value(&value_ivec, config, strict).ok();
+
+                    suffix.zip(value)
+                }
+                Err(_) => None,
+            })
+    }
+
+    /// Decodes the start of the value at `key_bytes`, leaving the rest undecoded on purpose.
+    pub(crate) fn project_bytes<T>(
+        &self,
+        key_bytes: &[u8],
+        decode_start: Decoder<T, C>,
+    ) -> Result<Option<T>, Error> {
+        match self.inner_tree.get(key_bytes)? {
+            Some(ivec) => Ok(Some(decode_start(&ivec, self.config, false)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn patch_bytes<V, P: Patch<V>>(
+        &self,
+        key_bytes: &[u8],
+        patch: &P,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        loop {
+            let Some(old_ivec) = self.inner_tree.get(key_bytes)? else {
+                return Ok(None);
+            };
+
+            let mut value = self.decode_stored(decode_value, key_bytes, &old_ivec)?;
+            patch.apply(&mut value);
+            let new_bytes = encode_value(&value, self.config)?;
+
+            if self.compare_and_swap_raw(key_bytes, Some(old_ivec), Some(new_bytes))? {
+                return Ok(Some(value));
+            }
+        }
+    }
+
+    pub(crate) fn apply_batch_with<K, V>(
+        &self,
+        batch: Batch<K, V>,
+        encode_key: Encoder<K, C>,
+        encode_value: Encoder<V, C>,
+    ) -> Result<(), Error> {
+        let mut raw_batch = Batch::new();
+
+        for (key, value) in batch.ops {
+            let key_bytes = encode_key(&key, self.config)?;
+
+            match value {
+                Some(value) => raw_batch.insert(key_bytes, encode_value(&value, self.config)?),
+                None => raw_batch.remove(key_bytes),
+            }
+        }
+
+        self.apply_raw_batch(raw_batch)
+    }
+
+    /// Encodes `entries` on `threads` scoped threads, then writes them as one batch.
+    pub(crate) fn apply_parallel_batch<K: Sync, V: Sync>(
+        &self,
+        entries: &[(K, V)],
+        threads: usize,
+        encode_key: Encoder<K, C>,
+        encode_value: Encoder<V, C>,
+    ) -> Result<(), Error>
+    where
+        C: Sync,
+    {
+        let config = &self.config;
+        let per_thread = entries.len().div_ceil(threads.max(1)).max(1);
+
+        let encoded = std::thread::scope(|scope| {
+            let workers: Vec<_> = entries
+                .chunks(per_thread)
+                .map(|part| {
+                    scope.spawn(move || {
+                        part.iter()
+                            .map(|(key, value)| {
+                                Ok((encode_key(key, *config)?, encode_value(value, *config)?))
+                            })
+                            .collect::<Result<Vec<_>, Error>>()
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("encoding thread panicked"))
+                .collect::<Result<Vec<_>, Error>>()
+        })?;
+
+        // sled keeps the writes of a batch sorted by key, the last one winning
+        let mut raw_batch = Batch::new();
+        for (key_bytes, value_bytes) in encoded.into_iter().flatten() {
+            raw_batch.insert(key_bytes, value_bytes);
+        }
+
+        self.apply_raw_batch(raw_batch)
+    }
+
+    /// Compare-and-swap loop shared by `update_and_fetch` and `fetch_and_update`.
+    /// Returns the raw value that was replaced and the new value.
+    pub(crate) fn update_bytes<V, F: FnMut(Option<V>) -> Option<V>>(
+        &self,
+        key_bytes: &[u8],
+        mut f: F,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<(Option<IVec>, Option<V>), Error> {
+        loop {
+            let old_ivec = self.inner_tree.get(key_bytes)?;
+            let old_value = match &old_ivec {
+                Some(ivec) => Some(self.decode_stored(decode_value, key_bytes, ivec)?),
+                None => None,
+            };
+
+            let new_value = f(old_value);
+            let new_bytes = match &new_value {
+                Some(value) => Some(encode_value(value, self.config)?),
+                None => None,
+            };
+
+            if self.compare_and_swap_raw(key_bytes, old_ivec.clone(), new_bytes)? {
+                return Ok((old_ivec, new_value));
+            }
+        }
+    }
+
+    pub(crate) fn fetch_and_update_bytes<V, F: FnMut(Option<V>) -> Option<V>>(
+        &self,
+        key_bytes: &[u8],
+        f: F,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        match self
+            .update_bytes(key_bytes, f, encode_value, decode_value)?
+            .0
+        {
+            Some(ivec) => Ok(Some(self.decode_stored(decode_value, key_bytes, &ivec)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<C: Config> RawTree<C> {
+    pub(crate) async fn flush_async(&self) -> Result<usize, Error> {
+        Ok(self.inner_tree.flush_async().await?)
+    }
+
+    pub(crate) fn watch_prefix_with<K: 'static, V: 'static>(
+        &self,
+        prefix_bytes: Vec<u8>,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Subscriber<K, V>
+    where
+        C: Send + Sync + 'static,
+    {
+        let (config, strict) = (self.config, self.strict_decode);
+
+        Subscriber::new(
+            self.inner_tree.watch_prefix(prefix_bytes),
+            Box::new(move |bytes| decode_key(bytes, config, strict)),
+            Box::new(move |bytes| decode_value(bytes, config, strict)),
+        )
+    }
+
+    pub(crate) fn set_merge_operator_with<K: 'static, V: 'static, F>(
+        &self,
+        merge_operator: F,
+        decode_key: Decoder<K, C>,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) where
+        C: 'static,
+        F: Fn(K, Option<V>, V) -> Option<V> + 'static,
+    {
+        let (config, strict) = (self.config, self.strict_decode);
+
+        self.inner_tree.set_merge_operator(
+            move |key_bytes: &[u8], old_bytes: Option<&[u8]>, delta_bytes: &[u8]| {
+                let keep = || old_bytes.map(<[u8]>::to_vec);
+
+                let Ok(key) = decode_key(key_bytes, config, strict) else {
+                    return keep();
+                };
+                let old = match old_bytes.map(|bytes| decode_value(bytes, config, strict)) {
+                    Some(Ok(old)) => Some(old),
+                    Some(Err(_)) => return keep(),
+                    None => None,
+                };
+                let Ok(delta) = decode_value(delta_bytes, config, strict) else {
+                    return keep();
+                };
+
+                match merge_operator(key, old, delta) {
+                    Some(value) => encode_value(&value, config).ok().or_else(keep),
+                    None => None,
+                }
+            },
+        );
+    }
+
+    pub(crate) fn merge_bytes<V>(
+        &self,
+        key_bytes: Vec<u8>,
+        delta: &V,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<Option<V>, Error> {
+        let delta_bytes = encode_value(delta, self.config)?;
+        let merged = match &self.quota {
+            Some(quota) => quota.merge(&self.inner_tree, &key_bytes, delta_bytes)?,
+            None => self.inner_tree.merge(&key_bytes, delta_bytes)?,
+        };
+
+        match merged {
+            Some(ivec) => Ok(Some(self.decode_stored(decode_value, &key_bytes, &ivec)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes every entry strictly, reporting the ones that fail.
+    pub(crate) fn verify_with<K, V>(
+        &self,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<check::TreeReport, Error> {
+        let config = self.config;
+        let validator = move |key: &[u8], value: &[u8]| {
+            decode_key(key, config, true)?;
+            decode_stored(decode_value, key, value, config, true)?;
+
+            Ok(())
+        };
+
+        check::check_tree(
+            &self.inner_tree,
+            Some(validator),
+            check::ReportLevel::Detailed,
+        )
+    }
+
+    /// Like `verify_with`, and moves the failing entries to the quarantine tree.
+    pub(crate) fn repair_with<K, V>(
+        &self,
+        db: &crate::Db,
+        decode_key: Decoder<K, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<check::TreeReport, Error> {
+        let mut report = self.verify_with(decode_key, decode_value)?;
+        check::quarantine_bad_entries(db, &self.inner_tree, &mut report)?;
+
+        Ok(report)
+    }
+
+    pub(crate) fn rewrite_in_place_with<K, V>(
+        &self,
+        encode_key: Encoder<K, C>,
+        decode_key: Decoder<K, C>,
+        encode_value: Encoder<V, C>,
+        decode_value: Decoder<V, C>,
+    ) -> Result<usize, Error> {
+        let reencode = |key_ivec: &[u8], value_ivec: &[u8]| -> Result<_, Error> {
+            let key = decode_key(key_ivec, self.config, self.strict_decode)?;
+            let value = self.decode_stored(decode_value, key_ivec, value_ivec)?;
+
+            Ok((
+                encode_key(&key, self.config)?,
+                encode_value(&value, self.config)?,
+            ))
+        };
+
+        // Nothing is written if an entry doesn't decode
+        for res in self.inner_tree.iter() {
+            let (key_ivec, value_ivec) = res?;
+            reencode(&key_ivec, &value_ivec)?;
+        }
+
+        let rewrite = || {
+            let mut rewritten = 0;
+            let mut start = Unbounded;
+
+            loop {
+                let mut keys = Vec::with_capacity(admin::COPY_BATCH_SIZE);
+                for res in self
+                    .inner_tree
+                    .range::<IVec, _>((start.clone(), Unbounded))
+                    .keys()
+                    .take(admin::COPY_BATCH_SIZE)
+                {
+                    keys.push(res?);
+                }
+                let Some(last) = keys.last() else {
+                    return Ok(rewritten);
+                };
+                start = Excluded(last.clone());
+
+                // Entries are read again in the transaction, as they may have changed
+                // since they were listed
+                rewritten += multi_tree::transaction(&self.inner_tree, |tree| {
+                    let mut rewritten = 0;
+                    for key_ivec in &keys {
+                        let Some(value_ivec) = tree.get(key_ivec)? else {
+                            continue;
+                        };
+                        let (key, value) = reencode(key_ivec, &value_ivec)
+                            .map_err(ConflictableTransactionError::Abort)?;
+                        if key == key_ivec[..] && value == value_ivec[..] {
+                            continue;
+                        }
+
+                        if key != key_ivec[..] {
+                            tree.remove(key_ivec)?;
+                        }
+                        tree.insert(key, value)?;
+                        rewritten += 1;
+                    }
+
+                    Ok(rewritten)
+                })??;
+            }
+        };
+
+        match &self.quota {
+            // Entries change size, so the usage is counted again afterwards
+            Some(quota) => quota.recounting(rewrite),
+            None => rewrite(),
+        }
+    }
+}
+
+/// Decodes the value stored under `key_bytes`, adding the key to checksum errors.
+#[cfg_attr(not(feature = "checksum"), allow(unused_variables))]
+fn decode_stored<V, C>(
+    decode_value: Decoder<V, C>,
+    key_bytes: &[u8],
+    value_bytes: &[u8],
+    config: C,
+    strict: bool,
+) -> Result<V, Error> {
+    match decode_value(value_bytes, config, strict) {
+        #[cfg(feature = "checksum")]
+        Err(Error::ChecksumMismatch { .. }) => Err(Error::ChecksumMismatch {
+            key_bytes: key_bytes.to_vec(),
+        }),
+        res => res,
+    }
+}
+
+/// Checks that a raw entry strictly decodes as `(K, V)` with the configuration `C`.
+/// Bincode configurations are entirely described by their type, so `C::default()`
+/// is the configuration the tree was opened with.
+pub(crate) fn validate_entry<K, V, VC: ValueCodec<V>, C: Config + Default, KC: KeyCodec<K>>(
+    key: &[u8],
+    value: &[u8],
+) -> Result<(), Error> {
+    KC::validate_key(key, C::default())?;
+    VC::decode(value, C::default(), true)?;
+
+    Ok(())
+}
+
+impl<K, V, VC, E, I, L, KC, B> Tree<K, V, VC, Configuration<E, I, L>, KC, B> {
+    /// See [`crate::bincode_tree::RelaxedTree::with_limit`].
+    pub fn with_limit<const N: usize>(
+        self,
+    ) -> Tree<K, V, VC, Configuration<E, I, Limit<N>>, KC, B> {
+        Tree {
+            inner_tree: self.inner_tree.with_limit::<N>(),
+            key_type: PhantomData,
+            value_type: PhantomData,
+            codecs: PhantomData,
+            validate_keys: self.validate_keys,
+        }
+    }
+}
+
+impl<K, V, VC, C, KC, B> Tree<K, V, VC, C, KC, B> {
+    /// See [`crate::bincode_tree::RelaxedTree::with_config`]. Keys encoded with
+    /// [`crate::keys::OrderedKeyCodec`] don't depend on the configuration.
+    pub fn with_config<C2: Config>(self, config: C2) -> Tree<K, V, VC, C2, KC, B> {
+        Tree {
+            inner_tree: self.inner_tree.with_config(config),
+            key_type: PhantomData,
+            value_type: PhantomData,
+            codecs: PhantomData,
+            validate_keys: self.validate_keys,
+        }
+    }
+
+    pub(crate) fn into_raw(self) -> RawTree<C, B> {
+        self.inner_tree
+    }
+
+    /// A strict tree over `raw`, decoding strictly like trees opened by a [`crate::Db`].
+    pub(crate) fn from_raw(mut raw: RawTree<C, B>) -> Self {
+        raw.strict_decode = true;
+
+        Self {
+            inner_tree: raw,
+            key_type: PhantomData,
+            value_type: PhantomData,
+            codecs: PhantomData,
+            validate_keys: false,
+        }
+    }
+}
+
+#[cfg(feature = "keyed-hashing")]
+impl<K, V, VC: ValueCodec<V>, C: Config, Inner: KeyCodec<K>>
+    Tree<K, V, VC, C, keys::HashedKeyCodec<Inner>>
+{
+    /// This tree with its keys hashed with `secret`, which may be loaded at runtime.
+    /// See [`crate::hashed::HashedTree`].
+    pub fn hashed(
+        &self,
+        secret: impl AsRef<[u8]>,
+    ) -> crate::hashed::HashedTree<K, V, VC, C, Inner> {
+        crate::hashed::HashedTree::new(self.inner_tree.clone(), secret.as_ref(), self.validate_keys)
+    }
+
+    /// Iterates over the entries of the tree with their hashed keys, as the
+    /// keys themselves can't be recovered.
+    pub fn iter_hashed(&self) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> {
+        self.inner_tree
+            .key_bytes_entries(self.inner_tree.inner_tree.iter(), VC::decode::<C>)
+    }
+}
+
+impl<V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<u64>> Tree<u64, V, VC, C, KC> {
+    /// Inserts `value` under a new ID from [`crate::Db::generate_id`] and returns the ID.
+    /// IDs are shared by every tree of `db`, so they are unique but not contiguous.
+    /// With [`OrderedKeyCodec`], later IDs sort after earlier ones, so the tree
+    /// iterates in insertion order. `db` must be the database of this tree.
+    pub fn insert_auto(&self, db: &crate::Db, value: &V) -> Result<u64, Error> {
+        let id = db.generate_id()?;
+        self.check_key(&id)?;
+
+        self.inner_tree.insert_bytes(
+            self.encode_key(&id)?,
+            value,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )?;
+
+        Ok(id)
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>, B: KvBackend> Tree<K, V, VC, C, KC, B> {
+    /// See [`crate::bincode_tree::RelaxedTree::with_strict_decode`].
+    /// Enabled by default for strict trees.
+    pub fn with_strict_decode(mut self, strict: bool) -> Self {
+        self.inner_tree.strict_decode = strict;
+        self
+    }
+
+    /// When enabled, `insert` checks that the encoded key decodes back into a key
+    /// with the same encoding, returning [`Error::KeyRoundTrip`] otherwise.
+    /// This catches key types whose encoding isn't symmetric before they corrupt
+    /// range semantics; consider `with_key_validation(cfg!(debug_assertions))`.
+    pub fn with_key_validation(mut self, validate: bool) -> Self {
+        self.validate_keys = validate;
+        self
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::with_quota`].
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.inner_tree.quota = Some(Arc::new(QuotaState::new(quota)));
+        self
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::flush`].
+    pub fn flush(&self) -> Result<usize, Error> {
+        self.inner_tree.flush()
+    }
+
+    /// The backend the tree is stored in: the sled tree, unless it was made with
+    /// [`Tree::with_backend`]. Writes made through it bypass the codecs and the quota.
+    pub fn inner(&self) -> &B {
+        self.inner_tree.inner()
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::get_raw`]. Keys are encoded with `KC`.
+    pub fn get_raw<KB: AsRef<[u8]>>(&self, key: KB) -> Result<Option<IVec>, Error> {
+        self.inner_tree.get_raw(key)
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::insert_raw`]. Key validation doesn't
+    /// apply to raw keys.
+    pub fn insert_raw<KB: AsRef<[u8]>, VB: Into<IVec>>(
+        &self,
+        key: KB,
+        value: VB,
+    ) -> Result<Option<IVec>, Error> {
+        self.inner_tree.insert_raw(key, value)
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::remove_raw`].
+    pub fn remove_raw<KB: AsRef<[u8]>>(&self, key: KB) -> Result<Option<IVec>, Error> {
+        self.inner_tree.remove_raw(key)
+    }
+
+    pub(crate) fn raw(&self) -> &RawTree<C, B> {
+        &self.inner_tree
+    }
+
+    pub(crate) fn encode_key(&self, key: &K) -> Result<Vec<u8>, Error> {
+        KC::encode_key(key, self.inner_tree.config)
+    }
+
+    pub(crate) fn check_key(&self, key: &K) -> Result<(), Error> {
+        if self.validate_keys {
+            keys::check_round_trip::<K, KC, C>(key, self.inner_tree.config)?;
+        }
+
+        Ok(())
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::patch`].
+    pub fn patch<P: Patch<V>>(&self, key: &K, patch: &P) -> Result<Option<V>, Error> {
+        self.inner_tree.patch_bytes(
+            &self.encode_key(key)?,
+            patch,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::apply_batch`]. With key validation
+    /// enabled, every key is checked before anything is written.
+    pub fn apply_batch(&self, batch: Batch<K, V>) -> Result<(), Error> {
+        for (key, _) in &batch.ops {
+            self.check_key(key)?;
+        }
+
+        self.inner_tree
+            .apply_batch_with(batch, KC::encode_key::<C>, VC::encode::<C>)
+    }
+
+    /// Removes every key of `range` and returns how many were removed. Keys are removed
+    /// in batches, so other readers can see the range partially removed.
+    pub fn remove_range<R: RangeBounds<K>>(&self, range: R) -> Result<usize, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        self.inner_tree.remove_range_bytes(bytes_range)
+    }
+
+    /// Removes the entries of the tree, in key order, yielding each one as it is
+    /// removed. See [`Tree::drain_range`].
+    pub fn drain(&self) -> impl Iterator<Item = Result<(K, V), Error>> {
+        self.inner_tree
+            .drain_entries((Unbounded, Unbounded), KC::decode_key::<C>, VC::decode::<C>)
+    }
+
+    /// Removes the entries of `range`, in key order, yielding each one as it is removed.
+    /// Every entry is removed atomically, so concurrent consumers each get different
+    /// entries. Entries are only removed as the iterator advances: dropping it leaves
+    /// the rest of the range in place. An entry that fails to decode is still removed.
+    pub fn drain_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<impl Iterator<Item = Result<(K, V), Error>>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        Ok(self
+            .inner_tree
+            .drain_entries(bytes_range, KC::decode_key::<C>, VC::decode::<C>))
+    }
+
+    /// Removes the entries for which `keep` returns `false`, and returns how many were
+    /// removed. Entries are removed in batches while the tree is scanned, and the first
+    /// entry that fails to decode stops the scan.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> Result<usize, Error> {
+        let mut batch = Batch::new();
+        let mut pending = 0;
+        let mut removed = 0;
+
+        for entry in self.inner_tree.inner_tree.iter() {
+            let (key_bytes, value_bytes) = entry?;
+            let (key, value) = self
+                .inner_tree
+                .decode_entry(
+                    Some((key_bytes.clone(), value_bytes)),
+                    KC::decode_key::<C>,
+                    VC::decode::<C>,
+                )?
+                .expect("entry is some");
+
+            if keep(&key, &value) {
+                continue;
+            }
+            batch.remove(key_bytes.to_vec());
+            pending += 1;
+
+            if pending == crate::admin::COPY_BATCH_SIZE {
+                self.inner_tree
+                    .apply_raw_batch(std::mem::take(&mut batch))?;
+                removed += pending;
+                pending = 0;
+            }
+        }
+        self.inner_tree.apply_raw_batch(batch)?;
+
+        Ok(removed + pending)
+    }
+
+    /// Counts the keys of `range` without decoding them or their values. Still O(n) in
+    /// the size of the range.
+    pub fn count_range<R: RangeBounds<K>>(&self, range: R) -> Result<usize, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        let mut count = 0;
+        for entry in self.inner_tree.inner_tree.range(bytes_range) {
+            entry?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Folds the entries of `range`, in key order, into an accumulator. Stops at the
+    /// first entry that fails to decode.
+    pub fn fold<R, A, F>(&self, range: R, init: A, mut f: F) -> Result<A, Error>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(A, (K, V)) -> A,
+    {
+        let mut acc = init;
+        for entry in self.try_range(range)? {
+            acc = f(acc, entry?);
+        }
+
+        Ok(acc)
+    }
+
+    /// Sums `f` over the entries of `range`. See [`Tree::fold`].
+    pub fn sum_by<R, S, F>(&self, range: R, mut f: F) -> Result<S, Error>
+    where
+        R: RangeBounds<K>,
+        S: std::iter::Sum,
+        F: FnMut(&K, &V) -> S,
+    {
+        self.try_range(range)?
+            .map(|entry| entry.map(|(key, value)| f(&key, &value)))
+            .sum()
+    }
+
+    /// The entry of `range` with the smallest `f`, the first one if several are
+    /// equally small. See [`Tree::fold`].
+    pub fn min_by_key<R, O, F>(&self, range: R, mut f: F) -> Result<Option<(K, V)>, Error>
+    where
+        R: RangeBounds<K>,
+        O: Ord,
+        F: FnMut(&K, &V) -> O,
+    {
+        let min = self.fold(range, None, |min, (key, value)| {
+            let by = f(&key, &value);
+            match min {
+                Some((min_by, min)) if min_by <= by => Some((min_by, min)),
+                _ => Some((by, (key, value))),
+            }
+        })?;
+
+        Ok(min.map(|(_, entry)| entry))
+    }
+
+    /// The entry of `range` with the largest `f`, the last one if several are
+    /// equally large. See [`Tree::fold`].
+    pub fn max_by_key<R, O, F>(&self, range: R, mut f: F) -> Result<Option<(K, V)>, Error>
+    where
+        R: RangeBounds<K>,
+        O: Ord,
+        F: FnMut(&K, &V) -> O,
+    {
+        let max = self.fold(range, None, |max, (key, value)| {
+            let by = f(&key, &value);
+            match max {
+                Some((max_by, max)) if max_by > by => Some((max_by, max)),
+                _ => Some((by, (key, value))),
+            }
+        })?;
+
+        Ok(max.map(|(_, entry)| entry))
+    }
+
+    /// Gets the stored bytes of the value of `key` without decoding them, to decode a
+    /// view of the value borrowing from them with [`ValueRef::decode_borrowed`].
+    pub fn get_ref(&self, key: &K) -> Result<Option<ValueRef<V, VC, C>>, Error> {
+        Ok(self
+            .inner_tree
+            .inner_tree
+            .get(&self.encode_key(key)?)?
+            .map(|bytes| {
+                ValueRef::new(bytes, self.inner_tree.config, self.inner_tree.strict_decode)
+            }))
+    }
+
+    /// Gets the values of `keys`, in the same order. Every key is encoded before the
+    /// first one is fetched, so an unencodable key fails the whole call.
+    pub fn get_many(&self, keys: &[K]) -> Result<Vec<Option<V>>, Error> {
+        let keys_bytes = keys
+            .iter()
+            .map(|key| self.encode_key(key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        keys_bytes
+            .iter()
+            .map(|key_bytes| self.inner_tree.get_bytes(key_bytes, VC::decode::<C>))
+            .collect()
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::insert_if_absent`].
+    pub fn insert_if_absent(&self, key: &K, value: &V) -> Result<bool, Error> {
+        self.check_key(key)?;
+
+        self.inner_tree.insert_if_absent_bytes(
+            self.encode_key(key)?,
+            VC::encode(value, self.inner_tree.config)?,
+        )
+    }
+
+    /// Inserts every entry of `entries` with [`Tree::apply_batch`], in batches, and
+    /// returns how many were inserted. Each batch is applied atomically, so an error
+    /// leaves the batches before it written.
+    pub fn insert_all<I: IntoIterator<Item = (K, V)>>(&self, entries: I) -> Result<usize, Error> {
+        let mut batch = Batch::new();
+        let mut inserted = 0;
+
+        for (key, value) in entries {
+            batch.insert(key, value);
+
+            if batch.len() == crate::admin::COPY_BATCH_SIZE {
+                inserted += batch.len();
+                self.apply_batch(std::mem::take(&mut batch))?;
+            }
+        }
+        inserted += batch.len();
+        self.apply_batch(batch)?;
+
+        Ok(inserted)
+    }
+
+    /// Like [`Tree::insert_all`], for large initial loads: entries are read
+    /// [`BULK_LOAD_CHUNK_SIZE`] at a time, encoded in parallel on one thread per
+    /// available core, and each chunk is written as one batch. Returns how many
+    /// entries were written.
+    pub fn bulk_load<I: IntoIterator<Item = (K, V)>>(&self, entries: I) -> Result<usize, Error>
+    where
+        K: Sync,
+        V: Sync,
+        C: Sync,
+    {
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+        self.bulk_load_with_threads(entries, threads)
+    }
+
+    /// [`Tree::bulk_load`] with `threads` encoding threads.
+    pub fn bulk_load_with_threads<I: IntoIterator<Item = (K, V)>>(
+        &self,
+        entries: I,
+        threads: usize,
+    ) -> Result<usize, Error>
+    where
+        K: Sync,
+        V: Sync,
+        C: Sync,
+    {
+        let mut entries = entries.into_iter().peekable();
+        let mut loaded = 0;
+
+        while entries.peek().is_some() {
+            let chunk: Vec<(K, V)> = entries.by_ref().take(BULK_LOAD_CHUNK_SIZE).collect();
+            for (key, _) in &chunk {
+                self.check_key(key)?;
+            }
+
+            self.inner_tree.apply_parallel_batch(
+                &chunk,
+                threads,
+                KC::encode_key::<C>,
+                VC::encode::<C>,
+            )?;
+            loaded += chunk.len();
+        }
+
+        Ok(loaded)
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::update_and_fetch`].
+    pub fn update_and_fetch<F: FnMut(Option<V>) -> Option<V>>(
+        &self,
+        key: &K,
+        f: F,
+    ) -> Result<Option<V>, Error> {
+        Ok(self
+            .inner_tree
+            .update_bytes(&self.encode_key(key)?, f, VC::encode::<C>, VC::decode::<C>)?
+            .1)
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::fetch_and_update`].
+    pub fn fetch_and_update<F: FnMut(Option<V>) -> Option<V>>(
+        &self,
+        key: &K,
+        f: F,
+    ) -> Result<Option<V>, Error> {
+        self.inner_tree.fetch_and_update_bytes(
+            &self.encode_key(key)?,
+            f,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::try_iter`].
+    pub fn try_iter(&self) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> {
+        self.inner_tree.try_entries(
+            self.inner_tree.inner_tree.iter(),
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// Writes every entry to `writer` as a line of JSON, `{"key": ..., "value": ...}`,
+    /// and returns how many were written. Stops at the first entry that fails to decode.
+    #[cfg(feature = "jsonl")]
+    pub fn export_jsonl<W: std::io::Write>(&self, writer: W) -> Result<usize, Error>
+    where
+        K: serde::Serialize,
+        V: serde::Serialize,
+    {
+        crate::jsonl::export(self.try_iter(), writer)
+    }
+
+    /// Inserts the entries of JSON Lines in the format of `export_jsonl`, handling keys
+    /// that are already in the tree according to `policy`, and returns how many entries
+    /// were written. Blank lines are skipped.
+    #[cfg(feature = "jsonl")]
+    pub fn import_jsonl<R: std::io::BufRead>(
+        &self,
+        reader: R,
+        policy: crate::jsonl::ConflictPolicy,
+    ) -> Result<usize, Error>
+    where
+        K: serde::de::DeserializeOwned,
+        V: serde::de::DeserializeOwned,
+    {
+        crate::jsonl::import(reader, |key: K, value: V| {
+            self.check_key(&key)?;
+            self.inner_tree.insert_with_policy(
+                self.encode_key(&key)?,
+                VC::encode(&value, self.inner_tree.config)?,
+                policy,
+            )
+        })
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::try_range`].
+    pub fn try_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = Result<(K, V), Error>>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        Ok(self.inner_tree.try_entries(
+            self.inner_tree.inner_tree.range(bytes_range),
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        ))
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::scan_prefix`]. `P` is expected to be
+    /// the leading part of `K`, which isn't checked.
+    pub fn scan_prefix<P>(
+        &self,
+        prefix: &P,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error>
+    where
+        KC: KeyCodec<P>,
+    {
+        let prefix_bytes = KC::encode_key(prefix, self.inner_tree.config)?;
+
+        Ok(self.scan_prefix_bytes(prefix_bytes))
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::scan_prefix_bytes`].
+    pub fn scan_prefix_bytes<P: AsRef<[u8]>>(
+        &self,
+        prefix: P,
+    ) -> impl DoubleEndedIterator<Item = (K, V)> {
+        self.inner_tree.entries(
+            self.inner_tree.inner_tree.scan_prefix(prefix.as_ref()),
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::bucket`]. `P` and `S` are expected to be
+    /// the leading and trailing parts of `K`, which isn't checked.
+    pub fn bucket<P, S>(&self, prefix: &P) -> Result<impl DoubleEndedIterator<Item = (S, V)>, Error>
+    where
+        KC: KeyCodec<P> + KeyCodec<S>,
+    {
+        let prefix_bytes = <KC as KeyCodec<P>>::encode_key(prefix, self.inner_tree.config)?;
+
+        Ok(self.inner_tree.bucket_with(
+            prefix_bytes,
+            <KC as KeyCodec<S>>::decode_key::<C>,
+            VC::decode::<C>,
+        ))
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::project`]. Only meaningful for codecs
+    /// that encode struct fields one after the other, like bincode.
+    pub fn project<Before, F>(
+        &self,
+        key: &K,
+        _selector: FieldSelector<V, Before, F>,
+    ) -> Result<Option<F>, Error>
+    where
+        VC: ValueCodec<(Before, F)>,
+    {
+        let field = self.inner_tree.project_bytes(
+            &self.encode_key(key)?,
+            <VC as ValueCodec<(Before, F)>>::decode::<C>,
+        )?;
+
+        Ok(field.map(|(_, field)| field))
+    }
+}
+
+impl<K, V, VC: ValueCodec<V>, C: Config, KC: KeyCodec<K>> Tree<K, V, VC, C, KC> {
+    /// The part of this tree whose keys start with `prefix`, encoded with bincode.
+    /// See [`crate::scoped::ScopedTree`].
+    pub fn scoped<P: bincode::Encode>(
+        &self,
+        prefix: &P,
+    ) -> Result<crate::scoped::ScopedTree<K, V, VC, C, KC>, Error> {
+        Ok(crate::scoped::ScopedTree::new(
+            self.inner_tree.clone(),
+            bincode::encode_to_vec(prefix, self.inner_tree.config)?,
+            self.validate_keys,
+        ))
+    }
+
+    /// This tree with its values encrypted with the keys of `provider`.
+    /// See [`crate::encryption::EncryptedTree`].
+    #[cfg(feature = "encryption")]
+    pub fn encrypted<P: crate::encryption::KeyProvider>(
+        &self,
+        provider: P,
+    ) -> crate::encryption::EncryptedTree<K, V, P, VC, C, KC> {
+        crate::encryption::EncryptedTree::new(self.inner_tree.clone(), provider, self.validate_keys)
+    }
+
+    /// Returns a handle to this tree that can only read from it.
+    pub fn read_only(&self) -> crate::view::ReadOnlyTree<K, V, VC, C, KC> {
+        Tree {
+            inner_tree: self.inner_tree.clone(),
+            key_type: PhantomData,
+            value_type: PhantomData,
+            codecs: PhantomData,
+            validate_keys: self.validate_keys,
+        }
+        .into()
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::flush_async`].
+    pub async fn flush_async(&self) -> Result<usize, Error> {
+        self.inner_tree.flush_async().await
+    }
+
+    /// Keeps count of the entries written through the returned handle in the
+    /// [`crate::counted::COUNTS_TREE`] tree of `db`, so that its `len` is constant
+    /// time. The first call counts the existing entries. `db` must be the database of
+    /// this tree, which can't have a quota.
+    pub fn counted(
+        self,
+        db: &crate::Db,
+    ) -> Result<crate::counted::CountedTree<K, V, VC, C, KC>, Error> {
+        let counts = db.inner_db.open_tree(crate::counted::COUNTS_TREE)?;
+
+        crate::counted::CountedTree::new(self, counts)
+    }
+
+    /// Journals the changes made through the returned handle in the `<tree>_journal`
+    /// tree of `db`, for incremental backups. `db` must be the database of this tree.
+    /// Fails with [`Error::IllegalOperation`] if the tree has a quota, as the journal
+    /// and the quota can't be updated in the same transaction.
+    pub fn journaled(
+        self,
+        db: &crate::Db,
+    ) -> Result<crate::journal::JournaledTree<K, V, VC, C, KC>, Error> {
+        if self.inner_tree.quota.is_some() {
+            return Err(Error::IllegalOperation);
+        }
+
+        let name = String::from_utf8_lossy(&self.inner_tree.inner_tree.name()).into_owned();
+        let journal = db.inner_db.open_tree(crate::journal::journal_name(&name))?;
+
+        Ok(crate::journal::JournaledTree::new(self, journal))
+    }
+
+    /// Copies the entries of `remote` whose key is missing from this tree, and resolves
+    /// the keys whose value differs with `policy`. Returns how many entries were
+    /// written. Entries are written in batches, and the first entry of either tree
+    /// that fails to decode stops the sync.
+    pub fn sync_from<VC2: ValueCodec<V>, C2: Config>(
+        &self,
+        remote: &Tree<K, V, VC2, C2, KC>,
+        policy: crate::diff::ConflictPolicy<K, V>,
+    ) -> Result<usize, Error>
+    where
+        V: PartialEq,
+    {
+        use crate::diff::{ConflictPolicy, DiffEntry};
+
+        let mut batch = Batch::new();
+        let mut pending = 0;
+        let mut written = 0;
+
+        for entry in crate::diff::diff(self, remote) {
+            let (key, value) = match (entry?, &policy) {
+                (DiffEntry::OnlyLeft(..), _) => continue,
+                (DiffEntry::OnlyRight(key, value), _) => (key, value),
+                (DiffEntry::Different { .. }, ConflictPolicy::KeepLocal) => continue,
+                (DiffEntry::Different { key, right, .. }, ConflictPolicy::TakeRemote) => {
+                    (key, right)
+                }
+                (DiffEntry::Different { key, left, right }, ConflictPolicy::Resolve(resolve)) => {
+                    let value = resolve(&key, left, right);
+                    (key, value)
+                }
+            };
+
+            batch.insert(
+                self.encode_key(&key)?,
+                VC::encode(&value, self.inner_tree.config)?,
+            );
+            pending += 1;
+
+            if pending == crate::admin::COPY_BATCH_SIZE {
+                self.inner_tree
+                    .apply_raw_batch(std::mem::take(&mut batch))?;
+                written += pending;
+                pending = 0;
+            }
+        }
+        self.inner_tree.apply_raw_batch(batch)?;
+
+        Ok(written + pending)
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::rewrite_in_place`].
+    pub fn rewrite_in_place(&self) -> Result<usize, Error> {
+        self.inner_tree.rewrite_in_place_with(
+            KC::encode_key::<C>,
+            KC::decode_key::<C>,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// Copies every entry through `map` into `dst`, `batch_size` entries at a time, and
+    /// returns how many were copied. See [`crate::Db::migrate_tree`].
+    pub(crate) fn migrate_into<K2, V2, VC2, C2, KC2, F>(
+        &self,
+        dst: &Tree<K2, V2, VC2, C2, KC2>,
+        progress: &sled::Tree,
+        mut map: F,
+        batch_size: usize,
+    ) -> Result<usize, Error>
+    where
+        VC2: ValueCodec<V2>,
+        C2: Config,
+        KC2: KeyCodec<K2>,
+        F: FnMut(K, V) -> (K2, V2),
+    {
+        let src_tree = &self.inner_tree.inner_tree;
+        let dst_tree = &dst.inner_tree.inner_tree;
+        let progress_key = [&src_tree.name()[..], &[0], &dst_tree.name()[..]].concat();
+
+        let mut start = match progress.get(&progress_key)? {
+            Some(last) => Excluded(last),
+            None => Unbounded,
+        };
+        let mut migrated = 0;
+
+        loop {
+            let mut batch = sled::Batch::default();
+            let mut last = None;
+            let mut count = 0;
+
+            for res in src_tree
+                .range::<IVec, _>((start.clone(), Unbounded))
+                .take(batch_size)
+            {
+                let (key_ivec, value_ivec) = res?;
+                let key = KC::decode_key(
+                    &key_ivec,
+                    self.inner_tree.config,
+                    self.inner_tree.strict_decode,
+                )?;
+                let value =
+                    self.inner_tree
+                        .decode_stored(VC::decode::<C>, &key_ivec, &value_ivec)?;
+                let (new_key, new_value) = map(key, value);
+
+                dst.check_key(&new_key)?;
+                batch.insert(
+                    dst.encode_key(&new_key)?,
+                    VC2::encode(&new_value, dst.inner_tree.config)?,
+                );
+                last = Some(key_ivec);
+                count += 1;
+            }
+
+            let Some(last) = last else {
+                progress.remove(&progress_key)?;
+                return Ok(migrated);
+            };
+
+            let _ = multi_tree::transaction(&(dst_tree, progress), |(dst_tx, progress_tx)| {
+                dst_tx.apply_batch(&batch)?;
+                progress_tx.insert(progress_key.as_slice(), last.clone())?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })?;
+
+            migrated += count;
+            start = Excluded(last);
+        }
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::verify`].
+    pub fn verify(&self) -> Result<check::TreeReport, Error> {
+        self.inner_tree.verify_with(
+            |bytes, config, _| KC::validate_key(bytes, config),
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::repair`].
+    pub fn repair(&self, db: &crate::Db) -> Result<check::TreeReport, Error> {
+        self.inner_tree.repair_with(
+            db,
+            |bytes, config, _| KC::validate_key(bytes, config),
+            VC::decode::<C>,
+        )
+    }
+
+    /// A [`crate::cursor::Cursor`] over every entry, which can seek forward without
+    /// starting a new iterator.
+    pub fn cursor(&self) -> crate::cursor::Cursor<K, V, VC, C, KC> {
+        crate::cursor::Cursor::new(self.inner_tree.clone(), (Unbounded, Unbounded))
+    }
+
+    /// A [`crate::cursor::Cursor`] over the entries of `range`.
+    pub fn cursor_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<crate::cursor::Cursor<K, V, VC, C, KC>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        Ok(crate::cursor::Cursor::new(
+            self.inner_tree.clone(),
+            bytes_range,
+        ))
+    }
+
+    /// A [`crate::query::Query`] over every entry.
+    pub fn query(&self) -> crate::query::Query<'_, K, V> {
+        crate::query::Query::new(self.cursor(), crate::query::Plan::FullScan)
+    }
+
+    /// A [`crate::query::Query`] over the entries of `range`.
+    pub fn query_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<crate::query::Query<'_, K, V>, Error> {
+        Ok(crate::query::Query::new(
+            self.cursor_range(range)?,
+            crate::query::Plan::KeyRange,
+        ))
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::set_merge_operator`].
+    pub fn set_merge_operator<F>(&self, merge_operator: F)
+    where
+        C: 'static,
+        K: 'static,
+        V: 'static,
+        F: Fn(K, Option<V>, V) -> Option<V> + 'static,
+    {
+        self.inner_tree.set_merge_operator_with(
+            merge_operator,
+            KC::decode_key::<C>,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::merge`].
+    pub fn merge(&self, key: &K, delta: &V) -> Result<Option<V>, Error> {
+        self.inner_tree.merge_bytes(
+            self.encode_key(key)?,
+            delta,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    /// See [`crate::bincode_tree::RelaxedTree::watch_prefix`]. `P` is expected to be
+    /// the leading part of `K`, which isn't checked.
+    pub fn watch_prefix<P>(&self, prefix: &P) -> Result<Subscriber<K, V>, Error>
+    where
+        KC: KeyCodec<P>,
+        K: 'static,
+        V: 'static,
+        C: Send + Sync + 'static,
+    {
+        let prefix_bytes = KC::encode_key(prefix, self.inner_tree.config)?;
+
+        Ok(self
+            .inner_tree
+            .watch_prefix_with(prefix_bytes, KC::decode_key::<C>, VC::decode::<C>))
+    }
+
+    /// Subscribes to every change of the tree.
+    pub fn watch(&self) -> Result<Subscriber<K, V>, Error>
+    where
+        K: 'static,
+        V: 'static,
+        C: Send + Sync + 'static,
+    {
+        Ok(self
+            .inner_tree
+            .watch_prefix_with(Vec::new(), KC::decode_key::<C>, VC::decode::<C>))
+    }
+}
+
+impl<K, V, VC, C, KC, B> Tree<K, V, VC, C, KC, B>
+where
+    VC: ValueCodec<V>,
+    C: Config + Default,
+    KC: KeyCodec<K>,
+    B: KvBackend,
+{
+    /// A strict tree stored in `backend`. See [`crate::backend`].
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            inner_tree: RawTree::new(backend, C::default(), true),
+            key_type: PhantomData,
+            value_type: PhantomData,
+            codecs: PhantomData,
+            validate_keys: false,
+        }
+    }
+}
+
+impl<KeyItem, ValueItem, VC, C, KC, B> StrictTree<KeyItem, ValueItem>
+    for Tree<KeyItem, ValueItem, VC, C, KC, B>
+where
+    VC: ValueCodec<ValueItem>,
+    C: Config + Default,
+    KC: KeyCodec<KeyItem>,
+    B: KvBackend,
+{
+    /// Panics if `B` isn't stored in sled, see [`KvBackend::from_sled_tree`].
+    fn new(tree: sled::Tree) -> Self {
+        Self::with_backend(B::from_sled_tree(tree))
+    }
+
+    fn get(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
+        self.inner_tree
+            .get_bytes(&self.encode_key(key)?, VC::decode::<C>)
+    }
+
+    fn get_or_init<F: FnOnce() -> ValueItem>(
+        &self,
+        key: KeyItem,
+        init_func: F,
+    ) -> Result<Option<ValueItem>, Error> {
+        let res = match self.get(&key)? {
+            Some(v) => Some(v),
+            None => {
+                let value = init_func();
+                let _ = self.insert(&key, &value)?;
+                Some(value)
+            }
+        };
+
+        Ok(res)
+    }
+
+    fn insert(&self, key: &KeyItem, value: &ValueItem) -> Result<Option<ValueItem>, Error> {
+        self.check_key(key)?;
+
+        self.inner_tree.insert_bytes(
+            self.encode_key(key)?,
+            value,
+            VC::encode::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn first(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.inner_tree.first()?,
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn last(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.inner_tree.last()?,
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn pop_max(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.pop_raw(true)?,
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn pop_min(&self) -> Result<Option<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree.decode_entry(
+            self.inner_tree.pop_raw(false)?,
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn pop_n_min(&self, n: usize) -> Result<Vec<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree
+            .pop_n_with(n, false, KC::decode_key::<C>, VC::decode::<C>)
+    }
+
+    fn pop_n_max(&self, n: usize) -> Result<Vec<(KeyItem, ValueItem)>, Error> {
+        self.inner_tree
+            .pop_n_with(n, true, KC::decode_key::<C>, VC::decode::<C>)
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (KeyItem, ValueItem)> {
+        self.inner_tree.entries(
+            self.inner_tree.inner_tree.iter(),
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        )
+    }
+
+    fn range_key_bytes<KeyBytes: AsRef<[u8]>, R: RangeBounds<KeyBytes>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, ValueItem)> {
+        self.inner_tree.key_bytes_entries(
+            self.inner_tree.inner_tree.range(bytes_range(range)),
+            VC::decode::<C>,
+        )
+    }
+
+    fn range<R: RangeBounds<KeyItem>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (KeyItem, ValueItem)>, Error> {
+        let bytes_range = self.inner_tree.encode_range(range, KC::encode_key::<C>)?;
+
+        Ok(self.inner_tree.entries(
+            self.inner_tree.inner_tree.range(bytes_range),
+            KC::decode_key::<C>,
+            VC::decode::<C>,
+        ))
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.inner_tree.clear()
+    }
+
+    fn contains_key(&self, key: &KeyItem) -> Result<bool, Error> {
+        self.inner_tree
+            .inner_tree
+            .contains_key(&self.encode_key(key)?)
+    }
+
+    fn len(&self) -> usize {
+        self.inner_tree.inner_tree.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner_tree.inner_tree.is_empty()
+    }
+
+    fn remove(&self, key: &KeyItem) -> Result<Option<ValueItem>, Error> {
+        self.inner_tree
+            .remove_bytes(&self.encode_key(key)?, VC::decode::<C>)
+    }
+}
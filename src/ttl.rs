@@ -0,0 +1,254 @@
+use bincode::{Decode, Encode};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+};
+use sled::IVec;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::bincode_tree::BincodeTree;
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::OrderedKey;
+use crate::multi_tree::{self, decode_u64, now_ms};
+use crate::{error::Error, DefaultConfig, StrictTree};
+
+pub(crate) fn expiries_name(tree_name: &str) -> String {
+    format!("{tree_name}_expiries")
+}
+
+/// Prefix of the keys mapping an entry to its deadline.
+const DEADLINE_PREFIX: u8 = b'k';
+/// Prefix of the keys of the expiry index, ordered by deadline then entry.
+const INDEX_PREFIX: u8 = b'e';
+
+/// A strict tree whose entries can expire, opened with [`crate::Db::open_ttl_tree`].
+///
+/// The deadline of every entry inserted with [`TtlTree::insert_with_ttl`] is stored
+/// in the `<name>_expiries` tree, in the same transaction as the entry, along with an
+/// index of the entries by deadline. Expired entries are no longer returned by
+/// [`TtlTree::get`], but stay stored until [`TtlTree::sweep_expired`] removes them,
+/// either called directly or from the thread started by [`TtlTree::spawn_sweeper`].
+#[derive(Clone)]
+pub struct TtlTree<K, V> {
+    tree: BincodeTree<K, V>,
+    expiries: sled::Tree,
+}
+
+impl<K: OrderedKey, V: Encode + Decode> TtlTree<K, V> {
+    pub(crate) fn new(tree: BincodeTree<K, V>, expiries: sled::Tree) -> Self {
+        Self { tree, expiries }
+    }
+
+    /// Inserts an entry that never expires, replacing any TTL of `key`. Returns the
+    /// previous value, even if it had expired.
+    pub fn insert(&self, key: &K, value: &V) -> Result<Option<V>, Error> {
+        self.insert_bytes(key, value, None)
+    }
+
+    /// Inserts an entry that expires once `ttl` has passed. Returns the previous
+    /// value, even if it had expired.
+    pub fn insert_with_ttl(&self, key: &K, value: &V, ttl: Duration) -> Result<Option<V>, Error> {
+        let deadline = now_ms().saturating_add(ttl.as_millis() as u64);
+        self.insert_bytes(key, value, Some(deadline))
+    }
+
+    /// Gets the value of `key`, unless it expired.
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+        if self.is_expired(&key_bytes)? {
+            return Ok(None);
+        }
+
+        self.tree.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+        Ok(!self.is_expired(&key_bytes)? && self.tree.contains_key(key)?)
+    }
+
+    /// Removes `key`, returning its value even if it had expired.
+    pub fn remove(&self, key: &K) -> Result<Option<V>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+
+        let old = multi_tree::transaction(
+            &(&self.tree.raw().inner_tree, &self.expiries),
+            |(tree, expiries)| {
+                let old = tree.remove(key_bytes.as_slice())?;
+                clear_deadline(expiries, &key_bytes)?;
+                Ok(old)
+            },
+        )?;
+
+        self.decode_old(&key_bytes, old.ok().flatten())
+    }
+
+    /// Time left before `key` expires. Returns `None` if it has no TTL or doesn't
+    /// exist, and [`Duration::ZERO`] if it already expired.
+    pub fn ttl(&self, key: &K) -> Result<Option<Duration>, Error> {
+        let key_bytes = self.tree.encode_key(key)?;
+
+        Ok(self
+            .deadline(&key_bytes)?
+            .map(|deadline| Duration::from_millis(deadline.saturating_sub(now_ms()))))
+    }
+
+    /// Removes the expired entries, and returns how many were removed. Entries given a
+    /// new value or TTL since they expired are left alone.
+    pub fn sweep_expired(&self) -> Result<usize, Error> {
+        sweep_expired(&self.tree.raw().inner_tree, &self.expiries)
+    }
+
+    /// Starts a thread calling [`TtlTree::sweep_expired`] every `interval`, until the
+    /// returned [`Sweeper`] is stopped or dropped. Failed sweeps are retried at the
+    /// next interval.
+    pub fn spawn_sweeper(&self, interval: Duration) -> Sweeper {
+        let tree = self.tree.raw().inner_tree.clone();
+        let expiries = self.expiries.clone();
+        let (stop, stopped) = mpsc::channel();
+
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let _ = sweep_expired(&tree, &expiries);
+            }
+        });
+
+        Sweeper {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Number of entries, including expired entries that weren't swept yet.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// The tree holding the entries, expired or not. Writes through it don't change
+    /// their TTL.
+    pub fn tree(&self) -> &BincodeTree<K, V> {
+        &self.tree
+    }
+
+    fn insert_bytes(&self, key: &K, value: &V, deadline: Option<u64>) -> Result<Option<V>, Error> {
+        self.tree.check_key(key)?;
+        let key_bytes = self.tree.encode_key(key)?;
+        let value = BincodeCodec::encode(value, self.tree.raw().config)?;
+
+        let old = multi_tree::transaction(
+            &(&self.tree.raw().inner_tree, &self.expiries),
+            |(tree, expiries)| {
+                let old = tree.insert(key_bytes.as_slice(), value.as_slice())?;
+                clear_deadline(expiries, &key_bytes)?;
+                if let Some(deadline) = deadline {
+                    let deadline = deadline.to_be_bytes();
+                    expiries.insert(deadline_key(&key_bytes), &deadline)?;
+                    expiries.insert(index_key(&deadline, &key_bytes), key_bytes.as_slice())?;
+                }
+                Ok(old)
+            },
+        )?;
+
+        self.decode_old(&key_bytes, old.ok().flatten())
+    }
+
+    fn deadline(&self, key_bytes: &[u8]) -> Result<Option<u64>, Error> {
+        Ok(self
+            .expiries
+            .get(deadline_key(key_bytes))?
+            .map(|deadline| decode_u64(&deadline)))
+    }
+
+    fn is_expired(&self, key_bytes: &[u8]) -> Result<bool, Error> {
+        Ok(self
+            .deadline(key_bytes)?
+            .is_some_and(|deadline| deadline <= now_ms()))
+    }
+
+    fn decode_old(&self, key: &[u8], old: Option<IVec>) -> Result<Option<V>, Error> {
+        old.map(|old| {
+            self.tree
+                .raw()
+                .decode_stored(BincodeCodec::decode::<DefaultConfig>, key, &old)
+        })
+        .transpose()
+    }
+}
+
+/// Handle to the thread started by [`TtlTree::spawn_sweeper`], which stops it when
+/// dropped.
+pub struct Sweeper {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Sweeper {
+    /// Stops the thread, waiting for the current sweep to finish.
+    pub fn stop(self) {}
+}
+
+impl Drop for Sweeper {
+    fn drop(&mut self) {
+        // Dropping the sender wakes the thread up
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn deadline_key(key_bytes: &[u8]) -> Vec<u8> {
+    [&[DEADLINE_PREFIX], key_bytes].concat()
+}
+
+fn index_key(deadline: &[u8], key_bytes: &[u8]) -> Vec<u8> {
+    [&[INDEX_PREFIX], deadline, key_bytes].concat()
+}
+
+/// Removes the deadline of `key_bytes` and its index entry, if any.
+fn clear_deadline(
+    expiries: &TransactionalTree,
+    key_bytes: &[u8],
+) -> ConflictableTransactionResult<(), ()> {
+    if let Some(deadline) = expiries.remove(deadline_key(key_bytes))? {
+        expiries.remove(index_key(&deadline, key_bytes))?;
+    }
+
+    Ok(())
+}
+
+fn sweep_expired(tree: &sled::Tree, expiries: &sled::Tree) -> Result<usize, Error> {
+    let now = now_ms();
+    let mut removed = 0;
+
+    for entry in expiries.scan_prefix([INDEX_PREFIX]) {
+        let (index_key, key_bytes) = entry?;
+        let Some(deadline) = index_key.get(1..9).map(decode_u64) else {
+            continue;
+        };
+        if deadline > now {
+            break;
+        }
+
+        let swept = multi_tree::transaction(&(tree, expiries), |(tree, expiries)| {
+            if expiries.get(deadline_key(&key_bytes))?.as_deref()
+                != Some(&deadline.to_be_bytes()[..])
+            {
+                return Err(ConflictableTransactionError::Abort(()));
+            }
+            tree.remove(&key_bytes)?;
+            clear_deadline(expiries, &key_bytes)
+        })?;
+
+        if swept.is_ok() {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
@@ -0,0 +1,71 @@
+//! Stored values read without decoding them, see [`crate::tree::Tree::get_ref`].
+
+use bincode::config::Config;
+use bincode::BorrowDecode;
+use sled::IVec;
+use std::marker::PhantomData;
+
+use crate::codec::{check_trailing, BincodeCodec, ValueCodec};
+use crate::error::Error;
+use crate::DefaultConfig;
+
+/// The stored bytes of a value of type `V`, returned by [`crate::tree::Tree::get_ref`].
+/// Views of the value borrowing from them, e.g. a struct with `&str` fields in place
+/// of `String` ones, can be decoded with `decode_borrowed` for bincode and serde trees,
+/// so that large values can be inspected without copying all their contents.
+pub struct ValueRef<V, VC = BincodeCodec, C = DefaultConfig> {
+    bytes: IVec,
+    config: C,
+    strict: bool,
+    value_type: PhantomData<fn() -> (V, VC)>,
+}
+
+impl<V, VC: ValueCodec<V>, C: Config> ValueRef<V, VC, C> {
+    pub(crate) fn new(bytes: IVec, config: C, strict: bool) -> Self {
+        Self {
+            bytes,
+            config,
+            strict,
+            value_type: PhantomData,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_ivec(self) -> IVec {
+        self.bytes
+    }
+
+    /// Decodes the whole value, as [`crate::StrictTree::get`] would have.
+    pub fn decode(&self) -> Result<V, Error> {
+        VC::decode(&self.bytes, self.config, self.strict)
+    }
+}
+
+impl<V, C: Config> ValueRef<V, BincodeCodec, C> {
+    /// Decodes `B`, which must be encoded like `V`, borrowing from the stored bytes.
+    /// Types deriving `Decode` also implement `BorrowDecode`, and `&str` and `&[u8]`
+    /// fields borrow their contents.
+    pub fn decode_borrowed<'a, B: BorrowDecode<'a>>(&'a self) -> Result<B, Error> {
+        let (value, size) = bincode::borrow_decode_from_slice(&self.bytes, self.config)?;
+        check_trailing(size, &self.bytes, self.strict)?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<V, C: Config> ValueRef<V, crate::codec::SerdeCodec, C> {
+    /// Deserializes `B`, which must be serialized like `V`, borrowing from the stored
+    /// bytes: `&str` and `&[u8]` fields (with `#[serde(borrow)]` for `Cow`s) borrow
+    /// their contents.
+    pub fn decode_borrowed<'a, B: serde::Deserialize<'a>>(&'a self) -> Result<B, Error> {
+        let (bincode::serde::BorrowCompat(value), size) =
+            bincode::borrow_decode_from_slice(&self.bytes, self.config)?;
+        check_trailing(size, &self.bytes, self.strict)?;
+
+        Ok(value)
+    }
+}
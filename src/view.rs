@@ -0,0 +1,152 @@
+use bincode::config::Config;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+use crate::codec::{BincodeCodec, ValueCodec};
+use crate::keys::{KeyCodec, OrderedKeyCodec};
+use crate::tree::Tree;
+use crate::{error::Error, DefaultConfig, StrictTree};
+
+/// A read-only view over a strict tree whose values are passed through a mapping
+/// function, created with [`StrictTree::map_view`]. Handing out a view instead of the
+/// tree lets a module read a narrowed projection of the data without being able to write.
+#[derive(Clone)]
+pub struct MappedView<T, K, V, F> {
+    tree: T,
+    map: F,
+    key_type: PhantomData<fn() -> K>,
+    value_type: PhantomData<fn() -> V>,
+}
+
+impl<T, K, V, W, F> MappedView<T, K, V, F>
+where
+    T: StrictTree<K, V>,
+    F: Fn(V) -> W,
+{
+    pub fn new(tree: T, map: F) -> Self {
+        Self {
+            tree,
+            map,
+            key_type: PhantomData,
+            value_type: PhantomData,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Result<Option<W>, Error> {
+        Ok(self.tree.get(key)?.map(&self.map))
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        self.tree.contains_key(key)
+    }
+
+    pub fn first(&self) -> Result<Option<(K, W)>, Error> {
+        Ok(self.tree.first()?.map(|(k, v)| (k, (self.map)(v))))
+    }
+
+    pub fn last(&self) -> Result<Option<(K, W)>, Error> {
+        Ok(self.tree.last()?.map(|(k, v)| (k, (self.map)(v))))
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, W)> + '_ {
+        self.tree.iter().map(|(k, v)| (k, (self.map)(v)))
+    }
+
+    pub fn range<'a, R: RangeBounds<K> + 'a>(
+        &'a self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, W)> + 'a, Error> {
+        Ok(self.tree.range(range)?.map(|(k, v)| (k, (self.map)(v))))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
+
+/// A handle to a strict tree that only exposes its read methods, created with
+/// [`Tree::read_only`] or `From`. Hand it to code that must not be able to write.
+#[derive(Clone)]
+pub struct ReadOnlyTree<K, V, VC = BincodeCodec, C = DefaultConfig, KC = OrderedKeyCodec> {
+    tree: Tree<K, V, VC, C, KC>,
+}
+
+impl<K, V, VC, C, KC> From<Tree<K, V, VC, C, KC>> for ReadOnlyTree<K, V, VC, C, KC> {
+    fn from(tree: Tree<K, V, VC, C, KC>) -> Self {
+        Self { tree }
+    }
+}
+
+impl<K, V, VC, C, KC> ReadOnlyTree<K, V, VC, C, KC>
+where
+    VC: ValueCodec<V>,
+    C: Config + Default,
+    KC: KeyCodec<K>,
+{
+    pub fn get(&self, key: &K) -> Result<Option<V>, Error> {
+        self.tree.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> Result<bool, Error> {
+        self.tree.contains_key(key)
+    }
+
+    pub fn first(&self) -> Result<Option<(K, V)>, Error> {
+        self.tree.first()
+    }
+
+    pub fn last(&self) -> Result<Option<(K, V)>, Error> {
+        self.tree.last()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (K, V)> + '_ {
+        self.tree.iter()
+    }
+
+    pub fn try_iter(&self) -> impl DoubleEndedIterator<Item = Result<(K, V), Error>> {
+        self.tree.try_iter()
+    }
+
+    pub fn range<'a, R: RangeBounds<K> + 'a>(
+        &'a self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)> + 'a, Error> {
+        self.tree.range(range)
+    }
+
+    pub fn try_range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Result<impl DoubleEndedIterator<Item = Result<(K, V), Error>>, Error> {
+        self.tree.try_range(range)
+    }
+
+    pub fn scan_prefix<P>(
+        &self,
+        prefix: &P,
+    ) -> Result<impl DoubleEndedIterator<Item = (K, V)>, Error>
+    where
+        KC: KeyCodec<P>,
+    {
+        self.tree.scan_prefix(prefix)
+    }
+
+    pub fn scan_prefix_bytes<P: AsRef<[u8]>>(
+        &self,
+        prefix: P,
+    ) -> impl DoubleEndedIterator<Item = (K, V)> {
+        self.tree.scan_prefix_bytes(prefix)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}